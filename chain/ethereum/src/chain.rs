@@ -712,6 +712,49 @@ impl TriggersAdapterTrait<Chain> for TriggersAdapter {
 
         Ok(block)
     }
+
+    async fn log_addresses(
+        &self,
+        logger: &Logger,
+        block: &BlockFinality,
+    ) -> Result<HashSet<Vec<u8>>, Error> {
+        if let Some(log_addresses) = receipt_log_addresses(block) {
+            return Ok(log_addresses);
+        }
+
+        let logs = self
+            .chain_client
+            .rpc()?
+            .cheapest_with(&self.capabilities)?
+            .logs_in_block(logger, block.ptr().hash_as_h256())
+            .await?;
+        Ok(logs
+            .iter()
+            .map(|log| log.address.as_bytes().to_vec())
+            .collect())
+    }
+}
+
+/// The distinct addresses of the contracts that emitted logs in `block`, if it carries the
+/// receipts of all of its transactions. Blocks from the log scan don't have any receipts.
+pub(crate) fn receipt_log_addresses(block: &BlockFinality) -> Option<HashSet<Vec<u8>>> {
+    match block {
+        BlockFinality::NonFinal(block)
+            if block.ethereum_block.transaction_receipts.len()
+                == block.ethereum_block.block.transactions.len() =>
+        {
+            Some(
+                block
+                    .ethereum_block
+                    .transaction_receipts
+                    .iter()
+                    .flat_map(|receipt| receipt.logs.iter())
+                    .map(|log| log.address.as_bytes().to_vec())
+                    .collect(),
+            )
+        }
+        _ => None,
+    }
 }
 
 pub struct FirehoseMapper {}
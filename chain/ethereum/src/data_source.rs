@@ -43,6 +43,8 @@ pub struct DataSource {
     pub name: String,
     pub manifest_idx: u32,
     pub address: Option<Address>,
+    /// The address of the data source that created this one from a template.
+    pub creator_address: Option<Address>,
     pub start_block: BlockNumber,
     pub mapping: Mapping,
     pub context: Arc<Option<DataSourceContext>>,
@@ -57,6 +59,7 @@ impl blockchain::DataSource<Chain> for DataSource {
             params,
             context,
             creation_block,
+            creator_address,
         } = info;
         let template = template.into_onchain().ok_or(anyhow!(
             "Cannot create onchain data source from offchain template"
@@ -91,6 +94,9 @@ impl blockchain::DataSource<Chain> for DataSource {
             name: template.name,
             manifest_idx: template.manifest_idx,
             address: Some(address),
+            creator_address: creator_address
+                .filter(|address| address.len() == Address::len_bytes())
+                .map(|address| Address::from_slice(&address)),
             start_block: 0,
             mapping: template.mapping,
             context: Arc::new(context),
@@ -103,6 +109,10 @@ impl blockchain::DataSource<Chain> for DataSource {
         self.address.as_ref().map(|x| x.as_bytes())
     }
 
+    fn creator_address(&self) -> Option<&[u8]> {
+        self.creator_address.as_ref().map(|x| x.as_bytes())
+    }
+
     fn start_block(&self) -> BlockNumber {
         self.start_block
     }
@@ -147,9 +157,10 @@ impl blockchain::DataSource<Chain> for DataSource {
             mapping,
             context,
 
-            // The creation block is ignored for detection duplicate data sources.
+            // The creation block and creator are ignored for detection duplicate data sources.
             // Contract ABI equality is implicit in `mapping.abis` equality.
             creation_block: _,
+            creator_address: _,
             contract_abi: _,
             start_block: _,
         } = self;
@@ -182,6 +193,7 @@ impl blockchain::DataSource<Chain> for DataSource {
             creation_block: self.creation_block,
             done_at: None,
             causality_region: CausalityRegion::ONCHAIN,
+            creator_address: self.creator_address.map(|addr| addr.0.into()),
         }
     }
 
@@ -196,6 +208,7 @@ impl blockchain::DataSource<Chain> for DataSource {
             creation_block,
             done_at,
             causality_region,
+            creator_address,
         } = stored;
 
         ensure!(
@@ -210,12 +223,14 @@ impl blockchain::DataSource<Chain> for DataSource {
         let contract_abi = template.mapping.find_abi(&template.source.abi)?;
 
         let address = param.map(|x| H160::from_slice(&x));
+        let creator_address = creator_address.map(|x| H160::from_slice(&x));
         Ok(DataSource {
             kind: template.kind.to_string(),
             network: template.network.as_ref().map(|s| s.to_string()),
             name: template.name.clone(),
             manifest_idx,
             address,
+            creator_address,
             start_block: 0,
             mapping: template.mapping.clone(),
             context: Arc::new(context),
@@ -310,6 +325,7 @@ impl DataSource {
             name,
             manifest_idx,
             address: source.address,
+            creator_address: None,
             start_block: source.start_block,
             mapping,
             context: Arc::new(context),
@@ -546,7 +562,7 @@ impl DataSource {
                     block.block_ptr(),
                 )))
             }
-            EthereumTrigger::Log(log, receipt) => {
+            EthereumTrigger::Log(log, receipt, signature_ordinal) => {
                 let potential_handlers = self.handlers_for_log(log)?;
 
                 // Map event handlers to (event handler, event ABI) pairs; fail if there are
@@ -648,6 +664,7 @@ impl DataSource {
                         log: log.cheap_clone(),
                         params,
                         receipt: receipt.clone(),
+                        signature_ordinal: *signature_ordinal,
                     },
                     event_handler.handler,
                     block.block_ptr(),
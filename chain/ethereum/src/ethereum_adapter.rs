@@ -54,7 +54,7 @@ use crate::{
         ProviderEthRpcMetrics, SubgraphEthRpcMetrics,
     },
     transport::Transport,
-    trigger::{EthereumBlockTriggerType, EthereumTrigger},
+    trigger::{log_signature_ordinals, EthereumBlockTriggerType, EthereumTrigger},
     TriggerFilter, ENV_VARS,
 };
 
@@ -263,6 +263,36 @@ impl EthereumAdapter {
             .await
     }
 
+    /// All logs of the block with hash `block_hash`, whatever contract emitted them.
+    pub(crate) async fn logs_in_block(
+        &self,
+        logger: &Logger,
+        block_hash: H256,
+    ) -> Result<Vec<Log>, Error> {
+        let web3 = self.web3.cheap_clone();
+        let retry_log_message = format!("eth_getLogs RPC call for block {:?}", block_hash);
+        retry(retry_log_message, logger)
+            .limit(ENV_VARS.request_retries)
+            .timeout_secs(ENV_VARS.json_rpc_timeout.as_secs())
+            .run(move || {
+                let web3 = web3.cheap_clone();
+                async move {
+                    let log_filter: Filter =
+                        FilterBuilder::default().block_hash(block_hash).build();
+                    web3.eth().logs(log_filter).await.map_err(Error::from)
+                }
+            })
+            .await
+            .map_err(move |e| {
+                e.into_inner().unwrap_or_else(move || {
+                    anyhow!(
+                        "Ethereum node took too long to return the logs of block {}",
+                        block_hash
+                    )
+                })
+            })
+    }
+
     fn trace_stream(
         self,
         logger: &Logger,
@@ -409,6 +439,82 @@ impl EthereumAdapter {
         .boxed()
     }
 
+    /// Like `contract_call`, but returns the raw output of the call instead of decoding it.
+    pub(crate) fn contract_call_output(
+        &self,
+        logger: &Logger,
+        call: EthereumContractCall,
+        cache: Arc<dyn EthereumCallCache>,
+    ) -> Box<dyn Future<Item = Vec<u8>, Error = EthereumContractCallError> + Send> {
+        // Emit custom error for type mismatches.
+        for (token, kind) in call
+            .args
+            .iter()
+            .zip(call.function.inputs.iter().map(|p| &p.kind))
+        {
+            if !token.type_check(kind) {
+                return Box::new(future::err(EthereumContractCallError::TypeError(
+                    token.clone(),
+                    kind.clone(),
+                )));
+            }
+        }
+
+        // Encode the call parameters according to the ABI
+        let call_data = match call.function.encode_input(&call.args) {
+            Ok(data) => data,
+            Err(e) => return Box::new(future::err(EthereumContractCallError::EncodingError(e))),
+        };
+
+        debug!(logger, "eth_call";
+            "address" => hex::encode(call.address),
+            "data" => hex::encode(&call_data),
+            "block_hash" => call.block_ptr.hash_hex(),
+            "block_number" => call.block_ptr.block_number()
+        );
+
+        // Check if we have it cached, if not do the call and cache.
+        Box::new(
+            match cache
+                .get_call(call.address, &call_data, call.block_ptr.clone())
+                .map_err(|e| error!(logger, "call cache get error"; "error" => e.to_string()))
+                .ok()
+                .flatten()
+            {
+                Some(result) => {
+                    Box::new(future::ok(result)) as Box<dyn Future<Item = _, Error = _> + Send>
+                }
+                None => {
+                    let cache = cache.clone();
+                    let call = call.clone();
+                    let logger = logger.clone();
+                    Box::new(
+                        self.call(
+                            logger.clone(),
+                            call.address,
+                            Bytes(call_data.clone()),
+                            call.block_ptr.clone(),
+                            call.gas,
+                        )
+                        .map(move |result| {
+                            // Don't block handler execution on writing to the cache.
+                            let for_cache = result.0.clone();
+                            let _ = graph::spawn_blocking_allow_panic(move || {
+                                cache
+                                    .set_call(call.address, &call_data, call.block_ptr, &for_cache)
+                                    .map_err(|e| {
+                                        error!(logger, "call cache set error";
+                                                   "error" => e.to_string())
+                                    })
+                            });
+                            result.0
+                        }),
+                    )
+                }
+            },
+        )
+    }
+
     fn call(
         &self,
         logger: Logger,
@@ -1170,87 +1276,10 @@ impl EthereumAdapterTrait for EthereumAdapter {
         call: EthereumContractCall,
         cache: Arc<dyn EthereumCallCache>,
     ) -> Box<dyn Future<Item = Vec<Token>, Error = EthereumContractCallError> + Send> {
-        // Emit custom error for type mismatches.
-        for (token, kind) in call
-            .args
-            .iter()
-            .zip(call.function.inputs.iter().map(|p| &p.kind))
-        {
-            if !token.type_check(kind) {
-                return Box::new(future::err(EthereumContractCallError::TypeError(
-                    token.clone(),
-                    kind.clone(),
-                )));
-            }
-        }
-
-        // Encode the call parameters according to the ABI
-        let call_data = match call.function.encode_input(&call.args) {
-            Ok(data) => data,
-            Err(e) => return Box::new(future::err(EthereumContractCallError::EncodingError(e))),
-        };
-
-        debug!(logger, "eth_call";
-            "address" => hex::encode(call.address),
-            "data" => hex::encode(&call_data),
-            "block_hash" => call.block_ptr.hash_hex(),
-            "block_number" => call.block_ptr.block_number()
-        );
-
-        // Check if we have it cached, if not do the call and cache.
+        let function = call.function.clone();
         Box::new(
-            match cache
-                .get_call(call.address, &call_data, call.block_ptr.clone())
-                .map_err(|e| error!(logger, "call cache get error"; "error" => e.to_string()))
-                .ok()
-                .flatten()
-            {
-                Some(result) => {
-                    Box::new(future::ok(result)) as Box<dyn Future<Item = _, Error = _> + Send>
-                }
-                None => {
-                    let cache = cache.clone();
-                    let call = call.clone();
-                    let logger = logger.clone();
-                    Box::new(
-                        self.call(
-                            logger.clone(),
-                            call.address,
-                            Bytes(call_data.clone()),
-                            call.block_ptr.clone(),
-                            call.gas,
-                        )
-                        .map(move |result| {
-                            // Don't block handler execution on writing to the cache.
-                            let for_cache = result.0.clone();
-                            let _ = graph::spawn_blocking_allow_panic(move || {
-                                cache
-                                    .set_call(call.address, &call_data, call.block_ptr, &for_cache)
-                                    .map_err(|e| {
-                                        error!(logger, "call cache set error";
-                                                   "error" => e.to_string())
-                                    })
-                            });
-                            result.0
-                        }),
-                    )
-                }
-            }
-            // Decode the return values according to the ABI
-            .and_then(move |output| {
-                if output.is_empty() {
-                    // We got a `0x` response. For old Geth, this can mean a revert. It can also be
-                    // that the contract actually returned an empty response. A view call is meant
-                    // to return something, so we treat empty responses the same as reverts.
-                    Err(EthereumContractCallError::Revert("empty response".into()))
-                } else {
-                    // Decode failures are reverts. The reasoning is that if Solidity fails to
-                    // decode an argument, that's a revert, so the same goes for the output.
-                    call.function.decode_output(&output).map_err(|e| {
-                        EthereumContractCallError::Revert(format!("failed to decode output: {}", e))
-                    })
-                }
-            }),
+            self.contract_call_output(logger, call, cache)
+                .and_then(move |output| decode_call_output(&function, &output)),
         )
     }
 
@@ -1525,6 +1554,25 @@ pub(crate) async fn get_calls(
     }
 }
 
+/// Decode the output of a contract call according to the ABI of `function`.
+pub(crate) fn decode_call_output(
+    function: &ethabi::Function,
+    output: &[u8],
+) -> Result<Vec<Token>, EthereumContractCallError> {
+    if output.is_empty() {
+        // We got a `0x` response. For old Geth, this can mean a revert. It can also be
+        // that the contract actually returned an empty response. A view call is meant
+        // to return something, so we treat empty responses the same as reverts.
+        Err(EthereumContractCallError::Revert("empty response".into()))
+    } else {
+        // Decode failures are reverts. The reasoning is that if Solidity fails to
+        // decode an argument, that's a revert, so the same goes for the output.
+        function.decode_output(output).map_err(|e| {
+            EthereumContractCallError::Revert(format!("failed to decode output: {}", e))
+        })
+    }
+}
+
 pub(crate) fn parse_log_triggers(
     log_filter: &EthereumLogFilter,
     block: &EthereumBlock,
@@ -1533,7 +1581,7 @@ pub(crate) fn parse_log_triggers(
         return vec![];
     }
 
-    block
+    let logs: Vec<_> = block
         .transaction_receipts
         .iter()
         .flat_map(move |receipt| {
@@ -1541,9 +1589,15 @@ pub(crate) fn parse_log_triggers(
                 .logs
                 .iter()
                 .filter(move |log| log_filter.matches(log))
-                .map(move |log| {
-                    EthereumTrigger::Log(Arc::new(log.clone()), Some(receipt.cheap_clone()))
-                })
+                .map(move |log| (log, receipt))
+        })
+        .collect();
+    let ordinals = log_signature_ordinals(&logs.iter().map(|(log, _)| *log).collect::<Vec<_>>());
+
+    logs.into_iter()
+        .zip(ordinals)
+        .map(|((log, receipt), ordinal)| {
+            EthereumTrigger::Log(Arc::new(log.clone()), Some(receipt.cheap_clone()), ordinal)
         })
         .collect()
 }
@@ -1948,12 +2002,13 @@ async fn get_logs_and_transactions(
     .await?;
 
     // Associate each log with its receipt, when possible
+    let ordinals = log_signature_ordinals(&logs.iter().collect::<Vec<_>>());
     let mut log_triggers = Vec::new();
-    for log in logs.into_iter() {
+    for (log, ordinal) in logs.into_iter().zip(ordinals) {
         let optional_receipt = log
             .transaction_hash
             .and_then(|txn| transaction_receipts_by_hash.get(&txn).cloned());
-        let value = EthereumTrigger::Log(Arc::new(log), optional_receipt);
+        let value = EthereumTrigger::Log(Arc::new(log), optional_receipt, ordinal);
         log_triggers.push(value);
     }
 
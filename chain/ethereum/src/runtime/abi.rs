@@ -1,4 +1,4 @@
-use super::runtime_adapter::UnresolvedContractCall;
+use super::runtime_adapter::{ContractCallAtBlock, SupportsInterfaceCall, UnresolvedContractCall};
 use crate::trigger::{
     EthereumBlockData, EthereumCallData, EthereumEventData, EthereumTransactionData,
 };
@@ -15,13 +15,14 @@ use graph::{
 };
 use graph_runtime_derive::AscType;
 use graph_runtime_wasm::asc_abi::class::{
-    Array, AscAddress, AscBigInt, AscEnum, AscH160, AscString, AscWrapped, EthereumValueKind,
-    Uint8Array,
+    Array, AscAddress, AscBigInt, AscEnum, AscEventParam, AscH160, AscString, AscWrapped,
+    EthereumValueKind, Uint8Array,
 };
 use semver::Version;
 
 type AscH256 = Uint8Array;
 type AscH2048 = Uint8Array;
+type AscLogParam = AscEventParam;
 
 pub struct AscLogParamArray(Array<AscPtr<AscLogParam>>);
 
@@ -176,6 +177,136 @@ impl FromAscObj<AscUnresolvedContractCall> for UnresolvedContractCall {
     }
 }
 
+/// The calls passed to `ethereum.callBatch`. `C` is the layout of each call, which depends on
+/// the apiVersion just like it does for `ethereum.call`.
+pub struct AscUnresolvedContractCallArray<C>(Array<AscPtr<C>>);
+
+impl<C> AscType for AscUnresolvedContractCallArray<C> {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl<C> AscIndexId for AscUnresolvedContractCallArray<C> {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArraySmartContractCall;
+}
+
+impl<C> FromAscObj<AscUnresolvedContractCallArray<C>> for Vec<UnresolvedContractCall>
+where
+    C: AscType + AscIndexId,
+    UnresolvedContractCall: FromAscObj<C>,
+{
+    fn from_asc_obj<H: AscHeap + ?Sized>(
+        asc_calls: AscUnresolvedContractCallArray<C>,
+        heap: &H,
+        gas: &GasCounter,
+        depth: usize,
+    ) -> Result<Self, DeterministicHostError> {
+        Vec::from_asc_obj(asc_calls.0, heap, gas, depth)
+    }
+}
+
+/// The results of `ethereum.callBatch`, with null for calls that reverted.
+pub struct AscCallResultArray(Array<AscPtr<Array<AscPtr<AscEnum<EthereumValueKind>>>>>);
+
+impl AscType for AscCallResultArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl ToAscObj<AscCallResultArray> for Vec<Option<Vec<ethabi::Token>>> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscCallResultArray, HostExportError> {
+        let results: Vec<AscPtr<Array<AscPtr<AscEnum<EthereumValueKind>>>>> = self
+            .iter()
+            .map(|result| match result {
+                Some(tokens) => asc_new(heap, tokens.as_slice(), gas),
+                None => Ok(AscPtr::null()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AscCallResultArray(Array::new(&results, heap, gas)?))
+    }
+}
+
+impl AscIndexId for AscCallResultArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArrayArrayEthereumValue;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscContractCallAtBlock {
+    pub call: AscPtr<AscUnresolvedContractCall_0_0_4>,
+    pub block_number: AscPtr<AscBigInt>,
+}
+
+impl AscIndexId for AscContractCallAtBlock {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ContractCallAtBlock;
+}
+
+impl FromAscObj<AscContractCallAtBlock> for ContractCallAtBlock {
+    fn from_asc_obj<H: AscHeap + ?Sized>(
+        asc_call: AscContractCallAtBlock,
+        heap: &H,
+        gas: &GasCounter,
+        depth: usize,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(ContractCallAtBlock {
+            call: asc_get(heap, asc_call.call, gas, depth)?,
+            block_number: asc_get(heap, asc_call.block_number, gas, depth)?,
+        })
+    }
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscSupportsInterfaceCall {
+    pub contract_address: AscPtr<AscAddress>,
+    pub interface_id: AscPtr<Uint8Array>,
+}
+
+impl AscIndexId for AscSupportsInterfaceCall {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::SupportsInterfaceCall;
+}
+
+impl FromAscObj<AscSupportsInterfaceCall> for SupportsInterfaceCall {
+    fn from_asc_obj<H: AscHeap + ?Sized>(
+        asc_call: AscSupportsInterfaceCall,
+        heap: &H,
+        gas: &GasCounter,
+        depth: usize,
+    ) -> Result<Self, DeterministicHostError> {
+        let interface_id: Vec<u8> = asc_get(heap, asc_call.interface_id, gas, depth)?;
+        let interface_id = <[u8; 4]>::try_from(interface_id.as_slice()).map_err(|_| {
+            DeterministicHostError::from(anyhow::anyhow!(
+                "an ERC-165 interface id must be 4 bytes, but got {}",
+                interface_id.len()
+            ))
+        })?;
+        Ok(SupportsInterfaceCall {
+            contract_address: asc_get(heap, asc_call.contract_address, gas, depth)?,
+            interface_id,
+        })
+    }
+}
+
 #[repr(C)]
 #[derive(AscType)]
 pub(crate) struct AscEthereumBlock {
@@ -223,6 +354,36 @@ impl AscIndexId for AscEthereumBlock_0_0_6 {
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EthereumBlock;
 }
 
+/// Introduced in API Version 0.0.8, this is the same as [`AscEthereumBlock_0_0_6`] with added
+/// `extra_data`, `mix_hash`, `nonce` and `logs_bloom` fields.
+#[repr(C)]
+#[derive(AscType)]
+pub(crate) struct AscEthereumBlock_0_0_8 {
+    pub hash: AscPtr<AscH256>,
+    pub parent_hash: AscPtr<AscH256>,
+    pub uncles_hash: AscPtr<AscH256>,
+    pub author: AscPtr<AscH160>,
+    pub state_root: AscPtr<AscH256>,
+    pub transactions_root: AscPtr<AscH256>,
+    pub receipts_root: AscPtr<AscH256>,
+    pub number: AscPtr<AscBigInt>,
+    pub gas_used: AscPtr<AscBigInt>,
+    pub gas_limit: AscPtr<AscBigInt>,
+    pub timestamp: AscPtr<AscBigInt>,
+    pub difficulty: AscPtr<AscBigInt>,
+    pub total_difficulty: AscPtr<AscBigInt>,
+    pub size: AscPtr<AscBigInt>,
+    pub base_fee_per_block: AscPtr<AscBigInt>,
+    pub extra_data: AscPtr<Uint8Array>,
+    pub mix_hash: AscPtr<AscH256>,
+    pub nonce: AscPtr<Uint8Array>,
+    pub logs_bloom: AscPtr<AscH2048>,
+}
+
+impl AscIndexId for AscEthereumBlock_0_0_8 {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EthereumBlock;
+}
+
 #[repr(C)]
 #[derive(AscType)]
 pub(crate) struct AscEthereumTransaction_0_0_1 {
@@ -274,6 +435,28 @@ impl AscIndexId for AscEthereumTransaction_0_0_6 {
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EthereumTransaction;
 }
 
+/// Introduced in API Version 0.0.8, this is the same as [`AscEthereumTransaction_0_0_6`] with
+/// added `max_fee_per_gas` and `max_priority_fee_per_gas` fields.
+#[repr(C)]
+#[derive(AscType)]
+pub(crate) struct AscEthereumTransaction_0_0_8 {
+    pub hash: AscPtr<AscH256>,
+    pub index: AscPtr<AscBigInt>,
+    pub from: AscPtr<AscH160>,
+    pub to: AscPtr<AscH160>,
+    pub value: AscPtr<AscBigInt>,
+    pub gas_limit: AscPtr<AscBigInt>,
+    pub gas_price: AscPtr<AscBigInt>,
+    pub input: AscPtr<Uint8Array>,
+    pub nonce: AscPtr<AscBigInt>,
+    pub max_fee_per_gas: AscPtr<AscBigInt>,
+    pub max_priority_fee_per_gas: AscPtr<AscBigInt>,
+}
+
+impl AscIndexId for AscEthereumTransaction_0_0_8 {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EthereumTransaction;
+}
+
 #[repr(C)]
 #[derive(AscType)]
 pub(crate) struct AscEthereumEvent<T, B>
@@ -365,15 +548,31 @@ impl AscIndexId for AscEthereumEvent_0_0_7<AscEthereumTransaction_0_0_6, AscEthe
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EthereumEvent;
 }
 
+/// Introduced in API Version 0.0.8, this is the same as [`AscEthereumEvent_0_0_7`] with an
+/// added `data` field holding the raw bytes of the log, the hash of the block the log was
+/// included in and whether the log was `removed` by a reorg.
 #[repr(C)]
 #[derive(AscType)]
-pub(crate) struct AscLogParam {
-    pub name: AscPtr<AscString>,
-    pub value: AscPtr<AscEnum<EthereumValueKind>>,
+pub(crate) struct AscEthereumEvent_0_0_8<T, B>
+where
+    T: AscType,
+    B: AscType,
+{
+    pub address: AscPtr<AscAddress>,
+    pub log_index: AscPtr<AscBigInt>,
+    pub transaction_log_index: AscPtr<AscBigInt>,
+    pub log_type: AscPtr<AscString>,
+    pub block: AscPtr<B>,
+    pub transaction: AscPtr<T>,
+    pub params: AscPtr<AscLogParamArray>,
+    pub receipt: AscPtr<AscEthereumTransactionReceipt>,
+    pub data: AscPtr<Uint8Array>,
+    pub block_hash: AscPtr<AscH256>,
+    pub removed: bool,
 }
 
-impl AscIndexId for AscLogParam {
-    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EventParam;
+impl AscIndexId for AscEthereumEvent_0_0_8<AscEthereumTransaction_0_0_8, AscEthereumBlock_0_0_8> {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EthereumEvent;
 }
 
 #[repr(C)]
@@ -481,6 +680,66 @@ impl ToAscObj<AscEthereumBlock_0_0_6> for EthereumBlockData {
     }
 }
 
+impl ToAscObj<AscEthereumBlock_0_0_8> for EthereumBlockData {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscEthereumBlock_0_0_8, HostExportError> {
+        let AscEthereumBlock_0_0_6 {
+            hash,
+            parent_hash,
+            uncles_hash,
+            author,
+            state_root,
+            transactions_root,
+            receipts_root,
+            number,
+            gas_used,
+            gas_limit,
+            timestamp,
+            difficulty,
+            total_difficulty,
+            size,
+            base_fee_per_block,
+        } = self.to_asc_obj(heap, gas)?;
+        let extra_data = asc_new(heap, &*self.extra_data, gas)?;
+        let mix_hash = self
+            .mix_hash
+            .map(|mix_hash| asc_new(heap, &mix_hash, gas))
+            .unwrap_or(Ok(AscPtr::null()))?;
+        let nonce = self
+            .nonce
+            .map(|nonce| asc_new(heap, nonce.as_bytes(), gas))
+            .unwrap_or(Ok(AscPtr::null()))?;
+        let logs_bloom = self
+            .logs_bloom
+            .map(|logs_bloom| asc_new(heap, logs_bloom.as_bytes(), gas))
+            .unwrap_or(Ok(AscPtr::null()))?;
+        Ok(AscEthereumBlock_0_0_8 {
+            hash,
+            parent_hash,
+            uncles_hash,
+            author,
+            state_root,
+            transactions_root,
+            receipts_root,
+            number,
+            gas_used,
+            gas_limit,
+            timestamp,
+            difficulty,
+            total_difficulty,
+            size,
+            base_fee_per_block,
+            extra_data,
+            mix_hash,
+            nonce,
+            logs_bloom,
+        })
+    }
+}
+
 impl ToAscObj<AscEthereumTransaction_0_0_1> for EthereumTransactionData {
     fn to_asc_obj<H: AscHeap + ?Sized>(
         &self,
@@ -547,6 +806,47 @@ impl ToAscObj<AscEthereumTransaction_0_0_6> for EthereumTransactionData {
     }
 }
 
+impl ToAscObj<AscEthereumTransaction_0_0_8> for EthereumTransactionData {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscEthereumTransaction_0_0_8, HostExportError> {
+        let AscEthereumTransaction_0_0_6 {
+            hash,
+            index,
+            from,
+            to,
+            value,
+            gas_limit,
+            gas_price,
+            input,
+            nonce,
+        } = self.to_asc_obj(heap, gas)?;
+        let max_fee_per_gas = self
+            .max_fee_per_gas
+            .map(|fee| asc_new(heap, &BigInt::from_unsigned_u256(&fee), gas))
+            .unwrap_or(Ok(AscPtr::null()))?;
+        let max_priority_fee_per_gas = self
+            .max_priority_fee_per_gas
+            .map(|fee| asc_new(heap, &BigInt::from_unsigned_u256(&fee), gas))
+            .unwrap_or(Ok(AscPtr::null()))?;
+        Ok(AscEthereumTransaction_0_0_8 {
+            hash,
+            index,
+            from,
+            to,
+            value,
+            gas_limit,
+            gas_price,
+            input,
+            nonce,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        })
+    }
+}
+
 impl<T, B> ToAscObj<AscEthereumEvent<T, B>> for EthereumEventData
 where
     T: AscType + AscIndexId,
@@ -620,6 +920,47 @@ where
     }
 }
 
+impl<T, B> ToAscObj<AscEthereumEvent_0_0_8<T, B>>
+    for (EthereumEventData, Option<&TransactionReceipt>)
+where
+    T: AscType + AscIndexId,
+    B: AscType + AscIndexId,
+    EthereumTransactionData: ToAscObj<T>,
+    EthereumBlockData: ToAscObj<B>,
+{
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscEthereumEvent_0_0_8<T, B>, HostExportError> {
+        let AscEthereumEvent_0_0_7 {
+            address,
+            log_index,
+            transaction_log_index,
+            log_type,
+            block,
+            transaction,
+            params,
+            receipt,
+        } = self.to_asc_obj(heap, gas)?;
+        let data = asc_new(heap, self.0.data.as_slice(), gas)?;
+        let block_hash = asc_new(heap, &self.0.block_hash, gas)?;
+        Ok(AscEthereumEvent_0_0_8 {
+            address,
+            log_index,
+            transaction_log_index,
+            log_type,
+            block,
+            transaction,
+            params,
+            receipt,
+            data,
+            block_hash,
+            removed: self.0.removed,
+        })
+    }
+}
+
 impl ToAscObj<AscEthereumLog> for Log {
     fn to_asc_obj<H: AscHeap + ?Sized>(
         &self,
@@ -727,39 +1068,18 @@ impl ToAscObj<AscEthereumCall> for EthereumCallData {
     }
 }
 
-impl ToAscObj<AscEthereumCall_0_0_3<AscEthereumTransaction_0_0_2, AscEthereumBlock>>
-    for EthereumCallData
-{
-    fn to_asc_obj<H: AscHeap + ?Sized>(
-        &self,
-        heap: &mut H,
-        gas: &GasCounter,
-    ) -> Result<
-        AscEthereumCall_0_0_3<AscEthereumTransaction_0_0_2, AscEthereumBlock>,
-        HostExportError,
-    > {
-        Ok(AscEthereumCall_0_0_3 {
-            to: asc_new(heap, &self.to, gas)?,
-            from: asc_new(heap, &self.from, gas)?,
-            block: asc_new(heap, &self.block, gas)?,
-            transaction: asc_new(heap, &self.transaction, gas)?,
-            inputs: asc_new(heap, &self.inputs, gas)?,
-            outputs: asc_new(heap, &self.outputs, gas)?,
-        })
-    }
-}
-
-impl ToAscObj<AscEthereumCall_0_0_3<AscEthereumTransaction_0_0_6, AscEthereumBlock_0_0_6>>
-    for EthereumCallData
+impl<T, B> ToAscObj<AscEthereumCall_0_0_3<T, B>> for EthereumCallData
+where
+    T: AscType + AscIndexId,
+    B: AscType + AscIndexId,
+    EthereumTransactionData: ToAscObj<T>,
+    EthereumBlockData: ToAscObj<B>,
 {
     fn to_asc_obj<H: AscHeap + ?Sized>(
         &self,
         heap: &mut H,
         gas: &GasCounter,
-    ) -> Result<
-        AscEthereumCall_0_0_3<AscEthereumTransaction_0_0_6, AscEthereumBlock_0_0_6>,
-        HostExportError,
-    > {
+    ) -> Result<AscEthereumCall_0_0_3<T, B>, HostExportError> {
         Ok(AscEthereumCall_0_0_3 {
             to: asc_new(heap, &self.to, gas)?,
             from: asc_new(heap, &self.from, gas)?,
@@ -770,16 +1090,3 @@ impl ToAscObj<AscEthereumCall_0_0_3<AscEthereumTransaction_0_0_6, AscEthereumBlo
         })
     }
 }
-
-impl ToAscObj<AscLogParam> for ethabi::LogParam {
-    fn to_asc_obj<H: AscHeap + ?Sized>(
-        &self,
-        heap: &mut H,
-        gas: &GasCounter,
-    ) -> Result<AscLogParam, HostExportError> {
-        Ok(AscLogParam {
-            name: asc_new(heap, self.name.as_str(), gas)?,
-            value: asc_new(heap, &self.value, gas)?,
-        })
-    }
-}
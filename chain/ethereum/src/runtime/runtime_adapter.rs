@@ -1,6 +1,11 @@
-use std::{sync::Arc, time::Instant};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
 
 use crate::data_source::MappingABI;
+use crate::ethereum_adapter::decode_call_output;
 use crate::{
     capabilities::NodeCapabilities, network::EthereumNetworkAdapters, Chain, DataSource,
     EthereumAdapter, EthereumAdapterTrait, EthereumContractCall, EthereumContractCallError,
@@ -16,15 +21,23 @@ use graph::{
     cheap_clone::CheapClone,
     prelude::{
         ethabi::{self, Address, Token},
-        EthereumCallCache, Future01CompatExt,
+        futures03, serde_json,
+        web3::types::H256,
+        BigInt, BlockNumber, ChainStore, EthereumBlock, EthereumCallCache, Future,
+        Future01CompatExt, LightEthereumBlockExt,
     },
     runtime::{asc_get, asc_new, AscPtr, HostExportError},
     semver::Version,
     slog::{info, trace, Logger},
 };
-use graph_runtime_wasm::asc_abi::class::{AscEnumArray, EthereumValueKind};
+use graph_runtime_wasm::asc_abi::class::{
+    Array, AscEnum, AscEnumArray, AscResult, EthereumValueKind,
+};
 
-use super::abi::{AscUnresolvedContractCall, AscUnresolvedContractCall_0_0_4};
+use super::abi::{
+    AscCallResultArray, AscContractCallAtBlock, AscSupportsInterfaceCall,
+    AscUnresolvedContractCall, AscUnresolvedContractCallArray, AscUnresolvedContractCall_0_0_4,
+};
 
 /// Gas limit for `eth_call`. The value of 50_000_000 is a protocol-wide parameter so this
 /// should be changed only for debugging purposes and never on an indexer in the network. This
@@ -50,6 +63,8 @@ pub struct RuntimeAdapter {
     pub eth_adapters: Arc<EthereumNetworkAdapters>,
     pub call_cache: Arc<dyn EthereumCallCache>,
     pub chain_identifier: Arc<ChainIdentifier>,
+    /// Used to look up the blocks that `ethereum.callAtBlock` calls against
+    pub chain_store: Arc<dyn ChainStore>,
 }
 
 impl blockchain::RuntimeAdapter<Chain> for RuntimeAdapter {
@@ -70,6 +85,60 @@ impl blockchain::RuntimeAdapter<Chain> for RuntimeAdapter {
             Some(ETH_CALL_GAS)
         };
 
+        let call_memo = Arc::new(BlockCallMemo::default());
+
+        let ethereum_call_batch = {
+            let abis = abis.clone();
+            let eth_adapters = eth_adapters.cheap_clone();
+            let call_cache = call_cache.cheap_clone();
+            let call_memo = call_memo.cheap_clone();
+            HostFn {
+                name: "ethereum.callBatch",
+                func: Arc::new(move |ctx, wasm_ptr| {
+                    let eth_adapter = eth_adapters.call_or_cheapest(Some(&NodeCapabilities {
+                        archive,
+                        traces: false,
+                    }))?;
+                    ethereum_call_batch(
+                        &eth_adapter,
+                        call_cache.cheap_clone(),
+                        &call_memo,
+                        ctx,
+                        wasm_ptr,
+                        &abis,
+                        eth_call_gas,
+                    )
+                    .map(|ptr| ptr.wasm_ptr())
+                }),
+            }
+        };
+
+        let ethereum_try_call = {
+            let abis = abis.clone();
+            let eth_adapters = eth_adapters.cheap_clone();
+            let call_cache = call_cache.cheap_clone();
+            let call_memo = call_memo.cheap_clone();
+            HostFn {
+                name: "ethereum.tryCall",
+                func: Arc::new(move |ctx, wasm_ptr| {
+                    let eth_adapter = eth_adapters.call_or_cheapest(Some(&NodeCapabilities {
+                        archive,
+                        traces: false,
+                    }))?;
+                    ethereum_try_call(
+                        &eth_adapter,
+                        call_cache.cheap_clone(),
+                        &call_memo,
+                        ctx,
+                        wasm_ptr,
+                        &abis,
+                        eth_call_gas,
+                    )
+                    .map(|ptr| ptr.wasm_ptr())
+                }),
+            }
+        };
+
         let ethereum_call = HostFn {
             name: "ethereum.call",
             func: Arc::new(move |ctx, wasm_ptr| {
@@ -81,6 +150,7 @@ impl blockchain::RuntimeAdapter<Chain> for RuntimeAdapter {
                 ethereum_call(
                     &eth_adapter,
                     call_cache.cheap_clone(),
+                    &call_memo,
                     ctx,
                     wasm_ptr,
                     &abis,
@@ -90,7 +160,136 @@ impl blockchain::RuntimeAdapter<Chain> for RuntimeAdapter {
             }),
         };
 
-        Ok(vec![ethereum_call])
+        let ethereum_supports_interface = {
+            let eth_adapters = self.eth_adapters.cheap_clone();
+            let call_cache = self.call_cache.cheap_clone();
+            HostFn {
+                name: "ethereum.supportsInterface",
+                func: Arc::new(move |ctx, wasm_ptr| {
+                    let eth_adapter = eth_adapters.call_or_cheapest(Some(&NodeCapabilities {
+                        archive,
+                        traces: false,
+                    }))?;
+                    ethereum_supports_interface(
+                        &eth_adapter,
+                        call_cache.cheap_clone(),
+                        ctx,
+                        wasm_ptr,
+                        eth_call_gas,
+                    )
+                    .map(u32::from)
+                }),
+            }
+        };
+
+        let ethereum_call_at_block = {
+            let abis = ds.mapping.abis.clone();
+            let eth_adapters = self.eth_adapters.cheap_clone();
+            let call_cache = self.call_cache.cheap_clone();
+            let chain_store = self.chain_store.cheap_clone();
+            // Separate from `call_memo` since these calls are made against other blocks
+            let call_memo = Arc::new(BlockCallMemo::default());
+            HostFn {
+                name: "ethereum.callAtBlock",
+                func: Arc::new(move |ctx, wasm_ptr| {
+                    // State of earlier blocks is generally only available on archive nodes.
+                    let eth_adapter = eth_adapters.call_or_cheapest(Some(&NodeCapabilities {
+                        archive: true,
+                        traces: false,
+                    }))?;
+                    ethereum_call_at_block(
+                        &eth_adapter,
+                        chain_store.cheap_clone(),
+                        call_cache.cheap_clone(),
+                        &call_memo,
+                        ctx,
+                        wasm_ptr,
+                        &abis,
+                        eth_call_gas,
+                    )
+                    .map(|ptr| ptr.wasm_ptr())
+                }),
+            }
+        };
+
+        Ok(vec![
+            ethereum_call,
+            ethereum_try_call,
+            ethereum_call_batch,
+            ethereum_supports_interface,
+            ethereum_call_at_block,
+        ])
+    }
+}
+
+type CallResult = Result<Option<Vec<Token>>, HostExportError>;
+
+/// The raw output of a contract call, or `None` if it reverted.
+type CallOutput = Result<Option<Vec<u8>>, HostExportError>;
+
+/// Results of the `ethereum.call`s made while processing the current block,
+/// keyed by contract address and encoded calldata. A call's result only
+/// depends on the block it is made against, so repeating it within the same
+/// block can safely return the earlier result. Only the most recent block is
+/// kept; moving to another block discards everything.
+#[derive(Default)]
+struct BlockCallMemo {
+    inner: Mutex<BlockCalls>,
+}
+
+#[derive(Default)]
+struct BlockCalls {
+    block_ptr: Option<BlockPtr>,
+    results: HashMap<(Address, Vec<u8>), Option<Vec<u8>>>,
+}
+
+impl BlockCallMemo {
+    /// Return the remembered result of calling `call_data` on `address` at
+    /// `block_ptr`, or run `call` and remember what it returns. Errors are
+    /// not remembered so that a retry reaches the node again.
+    fn get_or_call(
+        &self,
+        block_ptr: &BlockPtr,
+        address: Address,
+        call_data: Vec<u8>,
+        call: impl FnOnce() -> CallOutput,
+    ) -> CallOutput {
+        if let Some(result) = self.get(block_ptr, address, &call_data) {
+            return Ok(result);
+        }
+
+        let result = call()?;
+        self.remember(block_ptr, address, call_data, result.clone());
+        Ok(result)
+    }
+
+    /// The remembered result of calling `call_data` on `address` at `block_ptr`, if any.
+    fn get(
+        &self,
+        block_ptr: &BlockPtr,
+        address: Address,
+        call_data: &[u8],
+    ) -> Option<Option<Vec<u8>>> {
+        let calls = self.inner.lock().unwrap();
+        if calls.block_ptr.as_ref() != Some(block_ptr) {
+            return None;
+        }
+        calls.results.get(&(address, call_data.to_vec())).cloned()
+    }
+
+    fn remember(
+        &self,
+        block_ptr: &BlockPtr,
+        address: Address,
+        call_data: Vec<u8>,
+        result: Option<Vec<u8>>,
+    ) {
+        let mut calls = self.inner.lock().unwrap();
+        if calls.block_ptr.as_ref() != Some(block_ptr) {
+            calls.block_ptr = Some(block_ptr.cheap_clone());
+            calls.results.clear();
+        }
+        calls.results.insert((address, call_data), result);
     }
 }
 
@@ -98,6 +297,7 @@ impl blockchain::RuntimeAdapter<Chain> for RuntimeAdapter {
 fn ethereum_call(
     eth_adapter: &EthereumAdapter,
     call_cache: Arc<dyn EthereumCallCache>,
+    call_memo: &BlockCallMemo,
     ctx: HostFnCtx<'_>,
     wasm_ptr: u32,
     abis: &[Arc<MappingABI>],
@@ -105,21 +305,255 @@ fn ethereum_call(
 ) -> Result<AscEnumArray<EthereumValueKind>, HostExportError> {
     ctx.gas.consume_host_fn(ETHEREUM_CALL)?;
 
+    let call = unresolved_call(&ctx, wasm_ptr)?;
+    let result = eth_call(
+        eth_adapter,
+        call_cache,
+        call_memo,
+        &ctx.last_return_data,
+        &ctx.logger,
+        &ctx.block_ptr,
+        call,
+        abis,
+        eth_call_gas,
+    )?;
+    match result {
+        Some(tokens) => Ok(asc_new(ctx.heap, tokens.as_slice(), &ctx.gas)?),
+        None => Ok(AscPtr::null()),
+    }
+}
+
+/// function ethereum.tryCall(call: SmartContractCall): Result<Array<Token>, boolean>
+///
+/// Like `ethereum.call`, but a reverted call results in `Err(false)` rather than `null`. Errors
+/// reported by the Ethereum node still fail the handler.
+fn ethereum_try_call(
+    eth_adapter: &EthereumAdapter,
+    call_cache: Arc<dyn EthereumCallCache>,
+    call_memo: &BlockCallMemo,
+    ctx: HostFnCtx<'_>,
+    wasm_ptr: u32,
+    abis: &[Arc<MappingABI>],
+    eth_call_gas: Option<u32>,
+) -> Result<
+    AscPtr<AscResult<AscPtr<Array<AscPtr<AscEnum<EthereumValueKind>>>>, bool>>,
+    HostExportError,
+> {
+    ctx.gas.consume_host_fn(ETHEREUM_CALL)?;
+
+    let call = unresolved_call(&ctx, wasm_ptr)?;
+    let result = try_call_result(eth_call(
+        eth_adapter,
+        call_cache,
+        call_memo,
+        &ctx.last_return_data,
+        &ctx.logger,
+        &ctx.block_ptr,
+        call,
+        abis,
+        eth_call_gas,
+    ))?;
+    asc_new(ctx.heap, &result, &ctx.gas)
+}
+
+/// function ethereum.callBatch(calls: Array<SmartContractCall>): Array<Array<Token> | null>
+///
+/// Makes all `calls` against the current block in one host call, sending them to the node
+/// concurrently. The result for each call is in the same position as the call, with null for
+/// calls that reverted, just like `ethereum.call`. Any other error fails the handler.
+fn ethereum_call_batch(
+    eth_adapter: &EthereumAdapter,
+    call_cache: Arc<dyn EthereumCallCache>,
+    call_memo: &BlockCallMemo,
+    ctx: HostFnCtx<'_>,
+    wasm_ptr: u32,
+    abis: &[Arc<MappingABI>],
+    eth_call_gas: Option<u32>,
+) -> Result<AscPtr<AscCallResultArray>, HostExportError> {
+    let calls = unresolved_calls(&ctx, wasm_ptr)?;
+    ctx.gas.consume_host_fn(ETHEREUM_CALL * calls.len())?;
+
+    let results = call_batch(
+        eth_adapter,
+        call_cache,
+        call_memo,
+        &ctx.last_return_data,
+        &ctx.logger,
+        &ctx.block_ptr,
+        calls,
+        abis,
+        eth_call_gas,
+    )?;
+    asc_new(ctx.heap, &results, &ctx.gas)
+}
+
+/// Make all `calls` against `block_ptr` concurrently. Each call is looked up in `call_memo`
+/// first, like `eth_call` does. The outputs are decoded in the order of the calls so that
+/// `last_return_data` ends up with the output of the last call, and the first call, by
+/// position, that failed fails the whole batch.
+fn call_batch(
+    eth_adapter: &dyn ContractCaller,
+    call_cache: Arc<dyn EthereumCallCache>,
+    call_memo: &BlockCallMemo,
+    last_return_data: &Mutex<Option<Vec<u8>>>,
+    logger: &Logger,
+    block_ptr: &BlockPtr,
+    calls: Vec<UnresolvedContractCall>,
+    abis: &[Arc<MappingABI>],
+    eth_call_gas: Option<u32>,
+) -> Result<Vec<Option<Vec<Token>>>, HostExportError> {
+    let start_time = Instant::now();
+
+    let resolved = calls
+        .iter()
+        .map(|call| resolve_call(call, abis, block_ptr, eth_call_gas))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let outputs = graph::block_on(futures03::future::join_all(
+        calls
+            .iter()
+            .zip(&resolved)
+            .map(|(unresolved_call, (function, call))| {
+                let call_cache = call_cache.cheap_clone();
+                async move {
+                    let call_data = function.encode_input(&call.args).ok();
+                    if let Some(output) = call_data
+                        .as_ref()
+                        .and_then(|call_data| call_memo.get(block_ptr, call.address, call_data))
+                    {
+                        return Ok(output);
+                    }
+
+                    let output = call_output(
+                        eth_adapter,
+                        call_cache,
+                        logger,
+                        call.clone(),
+                        unresolved_call,
+                    )
+                    .await;
+                    if let (Ok(output), Some(call_data)) = (&output, call_data) {
+                        call_memo.remember(block_ptr, call.address, call_data, output.clone());
+                    }
+                    output
+                }
+            }),
+    ));
+
+    let results = outputs
+        .into_iter()
+        .zip(&resolved)
+        .map(|(output, (function, _))| {
+            output.map(|output| decode_call_result(logger, function, output, last_return_data))
+        })
+        .collect();
+
+    trace!(logger, "Contract call batch finished";
+              "calls" => calls.len(),
+              "time" => format!("{}ms", start_time.elapsed().as_millis()));
+
+    results
+}
+
+/// `eth_call` reports a reverted call as `Ok(None)`, which `ethereum.tryCall` turns into
+/// `Err(false)`. Other errors are passed on so that they trap.
+fn try_call_result(result: CallResult) -> Result<Result<Vec<Token>, bool>, HostExportError> {
+    result.map(|tokens| tokens.ok_or(false))
+}
+
+/// Read the `Array<SmartContractCall>` at `wasm_ptr`, with each call laid out the way
+/// `unresolved_call` expects for the apiVersion.
+fn unresolved_calls(
+    ctx: &HostFnCtx<'_>,
+    wasm_ptr: u32,
+) -> Result<Vec<UnresolvedContractCall>, HostExportError> {
+    let calls = if ctx.heap.api_version() >= Version::new(0, 0, 4) {
+        asc_get::<_, AscUnresolvedContractCallArray<AscUnresolvedContractCall_0_0_4>, _>(
+            &*ctx.heap,
+            wasm_ptr.into(),
+            &ctx.gas,
+            0,
+        )?
+    } else {
+        asc_get::<_, AscUnresolvedContractCallArray<AscUnresolvedContractCall>, _>(
+            &*ctx.heap,
+            wasm_ptr.into(),
+            &ctx.gas,
+            0,
+        )?
+    };
+    Ok(calls)
+}
+
+/// Read the `SmartContractCall` at `wasm_ptr`.
+fn unresolved_call(
+    ctx: &HostFnCtx<'_>,
+    wasm_ptr: u32,
+) -> Result<UnresolvedContractCall, HostExportError> {
     // For apiVersion >= 0.0.4 the call passed from the mapping includes the
     // function signature; subgraphs using an apiVersion < 0.0.4 don't pass
     // the signature along with the call.
-    let call: UnresolvedContractCall = if ctx.heap.api_version() >= Version::new(0, 0, 4) {
-        asc_get::<_, AscUnresolvedContractCall_0_0_4, _>(ctx.heap, wasm_ptr.into(), &ctx.gas, 0)?
+    let call = if ctx.heap.api_version() >= Version::new(0, 0, 4) {
+        asc_get::<_, AscUnresolvedContractCall_0_0_4, _>(&*ctx.heap, wasm_ptr.into(), &ctx.gas, 0)?
     } else {
-        asc_get::<_, AscUnresolvedContractCall, _>(ctx.heap, wasm_ptr.into(), &ctx.gas, 0)?
+        asc_get::<_, AscUnresolvedContractCall, _>(&*ctx.heap, wasm_ptr.into(), &ctx.gas, 0)?
     };
+    Ok(call)
+}
+
+/// function ethereum.callAtBlock(call: ContractCallAtBlock): Array<Token> | null
+///
+/// Like `ethereum.call`, but against an earlier block, e.g. to read a checkpoint. Calling
+/// against a block after the current one is a deterministic error.
+fn ethereum_call_at_block(
+    eth_adapter: &EthereumAdapter,
+    chain_store: Arc<dyn ChainStore>,
+    call_cache: Arc<dyn EthereumCallCache>,
+    call_memo: &BlockCallMemo,
+    mut ctx: HostFnCtx<'_>,
+    wasm_ptr: u32,
+    abis: &[Arc<MappingABI>],
+    eth_call_gas: Option<u32>,
+) -> Result<AscEnumArray<EthereumValueKind>, HostExportError> {
+    ctx.gas.consume_host_fn(ETHEREUM_CALL)?;
+
+    let call: ContractCallAtBlock =
+        asc_get::<_, AscContractCallAtBlock, _>(ctx.heap, wasm_ptr.into(), &ctx.gas, 0)?;
+    let block_ptr = call_block_ptr(
+        &ctx.block_ptr,
+        &call.block_number,
+        graph::env::ENV_VARS.reorg_threshold,
+        |offset| {
+            let block = graph::block_on(
+                chain_store
+                    .cheap_clone()
+                    .ancestor_block(ctx.block_ptr.cheap_clone(), offset),
+            )?;
+            block
+                .map(|block| {
+                    let block: EthereumBlock = serde_json::from_value(block)?;
+                    Ok(block.block.block_ptr())
+                })
+                .transpose()
+        },
+        |number| {
+            graph::block_on(
+                eth_adapter
+                    .block_hash_by_block_number(&ctx.logger, number)
+                    .compat(),
+            )
+        },
+    )?;
+    *ctx.chain_read_block = block_ptr.number;
 
     let result = eth_call(
         eth_adapter,
         call_cache,
+        call_memo,
+        &ctx.last_return_data,
         &ctx.logger,
-        &ctx.block_ptr,
-        call,
+        &block_ptr,
+        call.call,
         abis,
         eth_call_gas,
     )?;
@@ -129,10 +563,98 @@ fn ethereum_call(
     }
 }
 
+/// The block to make an `ethereum.callAtBlock` against. Blocks before `current` are looked up
+/// with `ancestor`, which takes their distance from `current`, so that they are on the same
+/// chain as `current`. Only blocks that are more than `reorg_threshold` blocks before `current`
+/// and therefore final can fall back to `block_hash` if the chain store does not have them.
+fn call_block_ptr(
+    current: &BlockPtr,
+    block_number: &BigInt,
+    reorg_threshold: BlockNumber,
+    ancestor: impl FnOnce(BlockNumber) -> Result<Option<BlockPtr>, Error>,
+    block_hash: impl FnOnce(BlockNumber) -> Result<Option<H256>, Error>,
+) -> Result<BlockPtr, HostExportError> {
+    let number = u64::try_from(block_number)
+        .ok()
+        .and_then(|number| BlockNumber::try_from(number).ok())
+        .filter(|number| *number <= current.number)
+        .ok_or_else(|| {
+            HostExportError::Deterministic(anyhow::anyhow!(
+                "ethereum.callAtBlock can not call against block {} since it is not between 0 \
+                 and the current block {}",
+                block_number,
+                current.number
+            ))
+        })?;
+
+    if number == current.number {
+        return Ok(current.cheap_clone());
+    }
+
+    let offset = current.number - number;
+    match ancestor(offset) {
+        Ok(Some(ptr)) => return Ok(ptr),
+        Ok(None) if offset <= reorg_threshold => {
+            return Err(HostExportError::PossibleReorg(anyhow::anyhow!(
+                "block {} is not in the chain store as an ancestor of block {}",
+                number,
+                current
+            )))
+        }
+        Ok(None) => {}
+        Err(e) => {
+            return Err(HostExportError::Unknown(anyhow::anyhow!(
+                "failed to look up block {} as an ancestor of block {}: {}",
+                number,
+                current,
+                e
+            )))
+        }
+    }
+
+    // See `eth_call` for why node errors are retried.
+    match block_hash(number) {
+        Ok(Some(hash)) => Ok(BlockPtr::from((hash, number))),
+        Ok(None) => Err(HostExportError::PossibleReorg(anyhow::anyhow!(
+            "Ethereum node does not know block {}",
+            number
+        ))),
+        Err(e) => Err(HostExportError::PossibleReorg(anyhow::anyhow!(
+            "Ethereum node returned an error when looking up block {}: {}",
+            number,
+            e
+        ))),
+    }
+}
+
+/// Makes contract calls and returns their raw output. `eth_call` only needs this much of an
+/// `EthereumAdapter`, which lets tests count the calls that reach the adapter.
+trait ContractCaller {
+    fn contract_call_output(
+        &self,
+        logger: &Logger,
+        call: EthereumContractCall,
+        cache: Arc<dyn EthereumCallCache>,
+    ) -> Box<dyn Future<Item = Vec<u8>, Error = EthereumContractCallError> + Send>;
+}
+
+impl ContractCaller for EthereumAdapter {
+    fn contract_call_output(
+        &self,
+        logger: &Logger,
+        call: EthereumContractCall,
+        cache: Arc<dyn EthereumCallCache>,
+    ) -> Box<dyn Future<Item = Vec<u8>, Error = EthereumContractCallError> + Send> {
+        EthereumAdapter::contract_call_output(self, logger, call, cache)
+    }
+}
+
 /// Returns `Ok(None)` if the call was reverted.
 fn eth_call(
-    eth_adapter: &EthereumAdapter,
+    eth_adapter: &dyn ContractCaller,
     call_cache: Arc<dyn EthereumCallCache>,
+    call_memo: &BlockCallMemo,
+    last_return_data: &Mutex<Option<Vec<u8>>>,
     logger: &Logger,
     block_ptr: &BlockPtr,
     unresolved_call: UnresolvedContractCall,
@@ -141,6 +663,49 @@ fn eth_call(
 ) -> Result<Option<Vec<Token>>, HostExportError> {
     let start_time = Instant::now();
 
+    let (function, call) = resolve_call(&unresolved_call, abis, block_ptr, eth_call_gas)?;
+
+    let address = call.address;
+    let call_data = function.encode_input(&call.args);
+
+    // Run Ethereum call in tokio runtime
+    let make_call = || {
+        graph::block_on(call_output(
+            eth_adapter,
+            call_cache,
+            logger,
+            call,
+            &unresolved_call,
+        ))
+    };
+
+    // Identical calls within the same block return the same result, so only
+    // the first one needs to reach the node. Calls whose arguments can't be
+    // encoded are passed through so the adapter reports the error.
+    let result = match call_data {
+        Ok(call_data) => call_memo.get_or_call(block_ptr, address, call_data, make_call),
+        Err(_) => make_call(),
+    }
+    .map(|output| decode_call_result(logger, &function, output, last_return_data));
+
+    trace!(logger, "Contract call finished";
+              "address" => &unresolved_call.contract_address.to_string(),
+              "contract" => &unresolved_call.contract_name,
+              "function" => &unresolved_call.function_name,
+              "function_signature" => &unresolved_call.function_signature,
+              "time" => format!("{}ms", start_time.elapsed().as_millis()));
+
+    result
+}
+
+/// Look up the ABI function that `unresolved_call` refers to and build the contract call to
+/// make for it against `block_ptr`.
+fn resolve_call(
+    unresolved_call: &UnresolvedContractCall,
+    abis: &[Arc<MappingABI>],
+    block_ptr: &BlockPtr,
+    eth_call_gas: Option<u32>,
+) -> Result<(ethabi::Function, EthereumContractCall), HostExportError> {
     // Obtain the path to the contract ABI
     let contract = abis
         .iter()
@@ -199,52 +764,175 @@ fn eth_call(
         args: unresolved_call.function_args.clone(),
         gas: eth_call_gas,
     };
+    Ok((function.clone(), call))
+}
 
-    // Run Ethereum call in tokio runtime
-    let logger1 = logger.clone();
-    let call_cache = call_cache.clone();
-    let result = match graph::block_on(
-            eth_adapter.contract_call(&logger1, call, call_cache).compat()
-        ) {
-            Ok(tokens) => Ok(Some(tokens)),
-            Err(EthereumContractCallError::Revert(reason)) => {
-                info!(logger, "Contract call reverted"; "reason" => reason);
-                Ok(None)
-            }
+/// Make `call` and return its raw output, or `None` if it reverted.
+async fn call_output(
+    eth_adapter: &dyn ContractCaller,
+    call_cache: Arc<dyn EthereumCallCache>,
+    logger: &Logger,
+    call: EthereumContractCall,
+    unresolved_call: &UnresolvedContractCall,
+) -> CallOutput {
+    match eth_adapter
+        .contract_call_output(logger, call, call_cache)
+        .compat()
+        .await
+    {
+        Ok(output) => Ok(Some(output)),
+        Err(EthereumContractCallError::Revert(reason)) => {
+            info!(logger, "Contract call reverted"; "reason" => reason);
+            Ok(None)
+        }
 
-            // Any error reported by the Ethereum node could be due to the block no longer being on
-            // the main chain. This is very unespecific but we don't want to risk failing a
-            // subgraph due to a transient error such as a reorg.
-            Err(EthereumContractCallError::Web3Error(e)) => Err(HostExportError::PossibleReorg(anyhow::anyhow!(
-                "Ethereum node returned an error when calling function \"{}\" of contract \"{}\": {}",
-                unresolved_call.function_name,
-                unresolved_call.contract_name,
-                e
-            ))),
+        // Any error reported by the Ethereum node could be due to the block no longer being on
+        // the main chain. This is very unespecific but we don't want to risk failing a
+        // subgraph due to a transient error such as a reorg.
+        Err(EthereumContractCallError::Web3Error(e)) => {
+            Err(HostExportError::PossibleReorg(anyhow::anyhow!(
+            "Ethereum node returned an error when calling function \"{}\" of contract \"{}\": {}",
+            unresolved_call.function_name,
+            unresolved_call.contract_name,
+            e
+        )))
+        }
 
-            // Also retry on timeouts.
-            Err(EthereumContractCallError::Timeout) => Err(HostExportError::PossibleReorg(anyhow::anyhow!(
+        // Also retry on timeouts.
+        Err(EthereumContractCallError::Timeout) => {
+            Err(HostExportError::PossibleReorg(anyhow::anyhow!(
                 "Ethereum node did not respond when calling function \"{}\" of contract \"{}\"",
                 unresolved_call.function_name,
                 unresolved_call.contract_name,
-            ))),
+            )))
+        }
 
-            Err(e) => Err(HostExportError::Unknown(anyhow::anyhow!(
-                "Failed to call function \"{}\" of contract \"{}\": {}",
-                unresolved_call.function_name,
-                unresolved_call.contract_name,
+        Err(e) => Err(HostExportError::Unknown(anyhow::anyhow!(
+            "Failed to call function \"{}\" of contract \"{}\": {}",
+            unresolved_call.function_name,
+            unresolved_call.contract_name,
+            e
+        ))),
+    }
+}
+
+/// Decode the `output` of a call to `function`, which is `None` if the call reverted, and
+/// remember it for `ethereum.lastReturnData`. Output that can't be decoded is treated as a
+/// revert, like the adapter does, but mappings can still get at it that way.
+fn decode_call_result(
+    logger: &Logger,
+    function: &ethabi::Function,
+    output: Option<Vec<u8>>,
+    last_return_data: &Mutex<Option<Vec<u8>>>,
+) -> Option<Vec<Token>> {
+    let tokens = output
+        .as_ref()
+        .map(|output| decode_call_output(function, output))
+        .transpose();
+    *last_return_data.lock().unwrap() = output;
+    match tokens {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            info!(logger, "Contract call reverted"; "reason" => e.to_string());
+            None
+        }
+    }
+}
+
+/// function ethereum.supportsInterface(call: SupportsInterfaceCall): bool
+///
+/// Performs the ERC-165 `supportsInterface(bytes4)` call against the current block. Contracts
+/// that revert or don't implement ERC-165 are treated as not supporting the interface.
+fn ethereum_supports_interface(
+    eth_adapter: &EthereumAdapter,
+    call_cache: Arc<dyn EthereumCallCache>,
+    ctx: HostFnCtx<'_>,
+    wasm_ptr: u32,
+    eth_call_gas: Option<u32>,
+) -> Result<bool, HostExportError> {
+    ctx.gas.consume_host_fn(ETHEREUM_CALL)?;
+
+    let call: SupportsInterfaceCall =
+        asc_get::<_, AscSupportsInterfaceCall, _>(ctx.heap, wasm_ptr.into(), &ctx.gas, 0)?;
+    let contract_call = EthereumContractCall {
+        address: call.contract_address,
+        block_ptr: ctx.block_ptr.cheap_clone(),
+        function: supports_interface_function(),
+        args: vec![Token::FixedBytes(call.interface_id.to_vec())],
+        gas: eth_call_gas,
+    };
+    let result = graph::block_on(
+        eth_adapter
+            .contract_call(&ctx.logger, contract_call, call_cache)
+            .compat(),
+    );
+    supports_interface_result(&call, result)
+}
+
+/// `function supportsInterface(bytes4 interfaceId) external view returns (bool)`
+fn supports_interface_function() -> ethabi::Function {
+    // `constant` is deprecated in favor of `state_mutability` but still has to be set.
+    #[allow(deprecated)]
+    ethabi::Function {
+        name: "supportsInterface".to_owned(),
+        inputs: vec![ethabi::Param {
+            name: "interfaceId".to_owned(),
+            kind: ethabi::ParamType::FixedBytes(4),
+            internal_type: None,
+        }],
+        outputs: vec![ethabi::Param {
+            name: String::new(),
+            kind: ethabi::ParamType::Bool,
+            internal_type: None,
+        }],
+        constant: None,
+        state_mutability: ethabi::StateMutability::View,
+    }
+}
+
+fn supports_interface_result(
+    call: &SupportsInterfaceCall,
+    result: Result<Vec<Token>, EthereumContractCallError>,
+) -> Result<bool, HostExportError> {
+    match result {
+        Ok(tokens) => match tokens.as_slice() {
+            [Token::Bool(supported)] => Ok(*supported),
+            _ => Ok(false),
+        },
+        Err(EthereumContractCallError::Revert(_)) => Ok(false),
+
+        // See `eth_call` for why node errors and timeouts are retried.
+        Err(EthereumContractCallError::Web3Error(e)) => {
+            Err(HostExportError::PossibleReorg(anyhow::anyhow!(
+                "Ethereum node returned an error when calling `supportsInterface` of contract \"{}\": {}",
+                call.contract_address,
                 e
-            ))),
-        };
+            )))
+        }
+        Err(EthereumContractCallError::Timeout) => {
+            Err(HostExportError::PossibleReorg(anyhow::anyhow!(
+                "Ethereum node did not respond when calling `supportsInterface` of contract \"{}\"",
+                call.contract_address,
+            )))
+        }
+        Err(e) => Err(HostExportError::Unknown(anyhow::anyhow!(
+            "Failed to call `supportsInterface` of contract \"{}\": {}",
+            call.contract_address,
+            e
+        ))),
+    }
+}
 
-    trace!(logger, "Contract call finished";
-              "address" => &unresolved_call.contract_address.to_string(),
-              "contract" => &unresolved_call.contract_name,
-              "function" => &unresolved_call.function_name,
-              "function_signature" => &unresolved_call.function_signature,
-              "time" => format!("{}ms", start_time.elapsed().as_millis()));
+#[derive(Clone, Debug)]
+pub struct SupportsInterfaceCall {
+    pub contract_address: Address,
+    pub interface_id: [u8; 4],
+}
 
-    result
+#[derive(Clone, Debug)]
+pub struct ContractCallAtBlock {
+    pub call: UnresolvedContractCall,
+    pub block_number: BigInt,
 }
 
 #[derive(Clone, Debug)]
@@ -259,3 +947,420 @@ pub struct UnresolvedContractCall {
 impl AscIndexId for AscUnresolvedContractCall {
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::SmartContractCall;
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use graph::components::store::CachedEthereumCall;
+    use graph::prelude::web3::types::H256;
+    use graph::slog::{o, Discard};
+
+    use super::*;
+
+    #[test]
+    fn identical_calls_in_a_block_reach_the_node_once() {
+        let memo = BlockCallMemo::default();
+        let calls = AtomicUsize::new(0);
+        let call = || -> CallOutput {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Some(vec![0xca, 0x11]))
+        };
+
+        let address = Address::from_low_u64_be(1);
+        let block1 = BlockPtr::from((H256::from_low_u64_be(1), 1u64));
+        let block2 = BlockPtr::from((H256::from_low_u64_be(2), 2u64));
+
+        for _ in 0..2 {
+            let result = memo.get_or_call(&block1, address, vec![1, 2, 3], call);
+            assert_eq!(Some(vec![0xca, 0x11]), result.unwrap());
+        }
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+
+        memo.get_or_call(&block1, address, vec![4], call).unwrap();
+        assert_eq!(2, calls.load(Ordering::SeqCst));
+
+        memo.get_or_call(&block2, address, vec![1, 2, 3], call)
+            .unwrap();
+        assert_eq!(3, calls.load(Ordering::SeqCst));
+    }
+
+    /// Answers every call with `output` and counts how many calls reach it.
+    struct CountingCaller {
+        calls: AtomicUsize,
+        output: Vec<u8>,
+    }
+
+    impl ContractCaller for CountingCaller {
+        fn contract_call_output(
+            &self,
+            _logger: &Logger,
+            _call: EthereumContractCall,
+            _cache: Arc<dyn EthereumCallCache>,
+        ) -> Box<dyn Future<Item = Vec<u8>, Error = EthereumContractCallError> + Send> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Box::new(graph::prelude::future::ok(self.output.clone()))
+        }
+    }
+
+    struct NoCallCache;
+
+    impl EthereumCallCache for NoCallCache {
+        fn get_call(&self, _: Address, _: &[u8], _: BlockPtr) -> Result<Option<Vec<u8>>, Error> {
+            Ok(None)
+        }
+
+        fn get_calls_in_block(&self, _: BlockPtr) -> Result<Vec<CachedEthereumCall>, Error> {
+            Ok(vec![])
+        }
+
+        fn set_call(&self, _: Address, _: &[u8], _: BlockPtr, _: &[u8]) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn eth_call_reaches_the_adapter_once_per_identical_call_in_a_block() {
+        let runtime = graph::tokio::runtime::Runtime::new().unwrap();
+        let _runtime_guard = runtime.enter();
+
+        let logger = Logger::root(Discard, o!());
+        let abis = vec![Arc::new(MappingABI {
+            name: "Token".to_string(),
+            contract: ethabi::Contract::load(
+                r#"[{
+                    "type": "function",
+                    "name": "balanceOf",
+                    "inputs": [{ "name": "owner", "type": "address" }],
+                    "outputs": [{ "name": "", "type": "uint256" }],
+                    "stateMutability": "view"
+                }]"#
+                .as_bytes(),
+            )
+            .unwrap(),
+        })];
+        // 42, ABI encoded
+        let mut output = vec![0u8; 32];
+        output[31] = 42;
+        let caller = CountingCaller {
+            calls: AtomicUsize::new(0),
+            output,
+        };
+        let memo = BlockCallMemo::default();
+        let last_return_data = Mutex::new(None);
+        let block1 = BlockPtr::from((H256::from_low_u64_be(1), 1u64));
+        let block2 = BlockPtr::from((H256::from_low_u64_be(2), 2u64));
+
+        let balance_of = |block_ptr: &BlockPtr, owner: u64| {
+            let call = UnresolvedContractCall {
+                contract_name: "Token".to_string(),
+                contract_address: Address::from_low_u64_be(1),
+                function_name: "balanceOf".to_string(),
+                function_signature: Some("balanceOf(address):(uint256)".to_string()),
+                function_args: vec![Token::Address(Address::from_low_u64_be(owner))],
+            };
+            eth_call(
+                &caller,
+                Arc::new(NoCallCache),
+                &memo,
+                &last_return_data,
+                &logger,
+                block_ptr,
+                call,
+                &abis,
+                None,
+            )
+            .unwrap()
+        };
+
+        for _ in 0..3 {
+            assert_eq!(Some(vec![Token::Uint(42.into())]), balance_of(&block1, 2));
+        }
+        assert_eq!(1, caller.calls.load(Ordering::SeqCst));
+
+        // Different arguments or another block reach the adapter again
+        balance_of(&block1, 3);
+        assert_eq!(2, caller.calls.load(Ordering::SeqCst));
+        balance_of(&block2, 2);
+        assert_eq!(3, caller.calls.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn last_return_data_is_the_raw_call_output() {
+        let logger = Logger::root(Discard, o!());
+        let function = supports_interface_function();
+        let last_return_data = Mutex::new(None);
+
+        // `true`, ABI encoded
+        let mut output = vec![0u8; 32];
+        output[31] = 1;
+        let tokens =
+            decode_call_result(&logger, &function, Some(output.clone()), &last_return_data);
+        assert_eq!(Some(vec![Token::Bool(true)]), tokens);
+        assert_eq!(Some(output), *last_return_data.lock().unwrap());
+
+        // Output that doesn't match the ABI looks like a revert, but the mapping can still
+        // get at the raw bytes
+        let output = vec![0xde, 0xad, 0xbe, 0xef];
+        let tokens =
+            decode_call_result(&logger, &function, Some(output.clone()), &last_return_data);
+        assert_eq!(None, tokens);
+        assert_eq!(Some(output), *last_return_data.lock().unwrap());
+
+        // Reverted calls have no output
+        assert_eq!(
+            None,
+            decode_call_result(&logger, &function, None, &last_return_data)
+        );
+        assert_eq!(None, *last_return_data.lock().unwrap());
+    }
+
+    #[test]
+    fn failed_calls_are_not_remembered() {
+        let memo = BlockCallMemo::default();
+        let calls = AtomicUsize::new(0);
+        let block = BlockPtr::from((H256::from_low_u64_be(1), 1u64));
+        let address = Address::from_low_u64_be(1);
+
+        for _ in 0..2 {
+            let result = memo.get_or_call(&block, address, vec![], || {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(HostExportError::PossibleReorg(anyhow::anyhow!("timeout")))
+            });
+            assert!(result.is_err());
+        }
+        assert_eq!(2, calls.load(Ordering::SeqCst));
+    }
+
+    /// Reverts calls to `revert`, times out on calls to `fail` and answers `get(n)` with
+    /// `10 * n`.
+    struct ScriptedCaller {
+        calls: AtomicUsize,
+    }
+
+    impl ContractCaller for ScriptedCaller {
+        fn contract_call_output(
+            &self,
+            _logger: &Logger,
+            call: EthereumContractCall,
+            _cache: Arc<dyn EthereumCallCache>,
+        ) -> Box<dyn Future<Item = Vec<u8>, Error = EthereumContractCallError> + Send> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let result = match (call.function.name.as_str(), call.args.as_slice()) {
+                ("revert", _) => Err(EthereumContractCallError::Revert("no".to_string())),
+                ("fail", _) => Err(EthereumContractCallError::Timeout),
+                (_, [Token::Uint(n)]) => {
+                    Ok(ethabi::encode(&[Token::Uint(*n * ethabi::Uint::from(10))]))
+                }
+                _ => unreachable!("unexpected call"),
+            };
+            Box::new(graph::prelude::future::result(result))
+        }
+    }
+
+    #[test]
+    fn call_batch_keeps_results_in_position() {
+        let runtime = graph::tokio::runtime::Runtime::new().unwrap();
+        let _runtime_guard = runtime.enter();
+
+        let logger = Logger::root(Discard, o!());
+        let abis = vec![Arc::new(MappingABI {
+            name: "Counter".to_string(),
+            contract: ethabi::Contract::load(
+                r#"[
+                    {
+                        "type": "function",
+                        "name": "get",
+                        "inputs": [{ "name": "n", "type": "uint256" }],
+                        "outputs": [{ "name": "", "type": "uint256" }],
+                        "stateMutability": "view"
+                    },
+                    {
+                        "type": "function",
+                        "name": "revert",
+                        "inputs": [],
+                        "outputs": [{ "name": "", "type": "uint256" }],
+                        "stateMutability": "view"
+                    },
+                    {
+                        "type": "function",
+                        "name": "fail",
+                        "inputs": [],
+                        "outputs": [{ "name": "", "type": "uint256" }],
+                        "stateMutability": "view"
+                    }
+                ]"#
+                .as_bytes(),
+            )
+            .unwrap(),
+        })];
+        let caller = ScriptedCaller {
+            calls: AtomicUsize::new(0),
+        };
+        let memo = BlockCallMemo::default();
+        let last_return_data = Mutex::new(None);
+        let block = BlockPtr::from((H256::from_low_u64_be(1), 1u64));
+
+        let unresolved = |function_name: &str, function_args: Vec<Token>| UnresolvedContractCall {
+            contract_name: "Counter".to_string(),
+            contract_address: Address::from_low_u64_be(1),
+            function_name: function_name.to_string(),
+            function_signature: None,
+            function_args,
+        };
+        let get = |n: u64| unresolved("get", vec![Token::Uint(n.into())]);
+        let batch = |calls: Vec<UnresolvedContractCall>| {
+            call_batch(
+                &caller,
+                Arc::new(NoCallCache),
+                &memo,
+                &last_return_data,
+                &logger,
+                &block,
+                calls,
+                &abis,
+                None,
+            )
+        };
+
+        let results = batch(vec![get(1), unresolved("revert", vec![]), get(2)]).unwrap();
+        assert_eq!(
+            vec![
+                Some(vec![Token::Uint(10.into())]),
+                None,
+                Some(vec![Token::Uint(20.into())]),
+            ],
+            results
+        );
+        assert_eq!(3, caller.calls.load(Ordering::SeqCst));
+        assert_eq!(
+            Some(ethabi::encode(&[Token::Uint(20.into())])),
+            *last_return_data.lock().unwrap()
+        );
+
+        // Calls in a batch are remembered for the rest of the block
+        let result = eth_call(
+            &caller,
+            Arc::new(NoCallCache),
+            &memo,
+            &last_return_data,
+            &logger,
+            &block,
+            get(1),
+            &abis,
+            None,
+        );
+        assert_eq!(Some(vec![Token::Uint(10.into())]), result.unwrap());
+        assert_eq!(3, caller.calls.load(Ordering::SeqCst));
+
+        assert!(batch(vec![]).unwrap().is_empty());
+        assert!(matches!(
+            batch(vec![get(3), unresolved("fail", vec![])]),
+            Err(HostExportError::PossibleReorg(_))
+        ));
+        assert!(matches!(
+            batch(vec![get(4), unresolved("missing", vec![])]),
+            Err(HostExportError::Unknown(_))
+        ));
+    }
+
+    #[test]
+    fn try_call_distinguishes_reverts_from_errors() {
+        let tokens = vec![Token::Uint(1.into())];
+        assert_eq!(
+            Ok(tokens.clone()),
+            try_call_result(Ok(Some(tokens))).unwrap()
+        );
+        assert_eq!(Err(false), try_call_result(Ok(None)).unwrap());
+        assert!(matches!(
+            try_call_result(Err(HostExportError::PossibleReorg(anyhow::anyhow!(
+                "node error"
+            )))),
+            Err(HostExportError::PossibleReorg(_))
+        ));
+    }
+
+    #[test]
+    fn call_at_block_looks_up_earlier_blocks() {
+        let current = BlockPtr::from((H256::from_low_u64_be(10), 10u64));
+        let ptr =
+            |number: BlockNumber| BlockPtr::from((H256::from_low_u64_be(number as u64), number));
+        let lookups = AtomicUsize::new(0);
+        let ancestor = |offset| {
+            lookups.fetch_add(1, Ordering::SeqCst);
+            Ok(Some(ptr(current.number - offset)))
+        };
+        let no_ancestor = |_| Ok(None);
+        let block_hash = |number| Ok(Some(H256::from_low_u64_be(100 + number as u64)));
+        let no_block_hash = |_| -> Result<Option<H256>, Error> { unreachable!() };
+
+        // Earlier blocks are ancestors of the current block
+        for number in [0, 7] {
+            assert_eq!(
+                ptr(number),
+                call_block_ptr(&current, &BigInt::from(number), 5, ancestor, no_block_hash)
+                    .unwrap()
+            );
+        }
+        assert_eq!(2, lookups.load(Ordering::SeqCst));
+
+        // The current block is known without a lookup
+        assert_eq!(
+            current,
+            call_block_ptr(&current, &BigInt::from(10), 5, ancestor, no_block_hash).unwrap()
+        );
+        for number in [11, -1] {
+            assert!(matches!(
+                call_block_ptr(&current, &BigInt::from(number), 5, ancestor, no_block_hash),
+                Err(HostExportError::Deterministic(_))
+            ));
+        }
+        assert_eq!(2, lookups.load(Ordering::SeqCst));
+
+        // Blocks in the reorg window must be ancestors of the current block,
+        // final blocks can be looked up by number
+        assert!(matches!(
+            call_block_ptr(&current, &BigInt::from(7), 5, no_ancestor, block_hash),
+            Err(HostExportError::PossibleReorg(_))
+        ));
+        assert_eq!(
+            BlockPtr::from((H256::from_low_u64_be(103), 3)),
+            call_block_ptr(&current, &BigInt::from(3), 5, no_ancestor, block_hash).unwrap()
+        );
+        assert!(matches!(
+            call_block_ptr(&current, &BigInt::from(3), 5, no_ancestor, |_| Ok(None)),
+            Err(HostExportError::PossibleReorg(_))
+        ));
+    }
+
+    #[test]
+    fn supports_interface_treats_reverts_as_unsupported() {
+        let call = SupportsInterfaceCall {
+            contract_address: Address::from_low_u64_be(1),
+            interface_id: [0x80, 0xac, 0x58, 0xcd],
+        };
+        let supports = |result| supports_interface_result(&call, result).unwrap();
+
+        assert!(supports(Ok(vec![Token::Bool(true)])));
+        assert!(!supports(Ok(vec![Token::Bool(false)])));
+        assert!(!supports(Err(EthereumContractCallError::Revert(
+            "empty response".to_string()
+        ))));
+        assert!(matches!(
+            supports_interface_result(&call, Err(EthereumContractCallError::Timeout)),
+            Err(HostExportError::PossibleReorg(_))
+        ));
+    }
+
+    #[test]
+    fn supports_interface_call_data_uses_erc165_selector() {
+        let call_data = supports_interface_function()
+            .encode_input(&[Token::FixedBytes(vec![0x80, 0xac, 0x58, 0xcd])])
+            .unwrap();
+        assert_eq!(
+            &[0x01, 0xff, 0xc9, 0xa7, 0x80, 0xac, 0x58, 0xcd],
+            &call_data[..8]
+        );
+        assert_eq!(36, call_data.len());
+    }
+}
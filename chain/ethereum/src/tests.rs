@@ -1,17 +1,22 @@
 use std::sync::Arc;
 
 use graph::{
-    blockchain::{block_stream::BlockWithTriggers, BlockPtr},
+    blockchain::{self, block_stream::BlockWithTriggers, BlockPtr, MappingTriggerTrait},
     prelude::{
-        web3::types::{Address, Bytes, Log, H160, H256, U64},
-        EthereumCall, LightEthereumBlock,
+        ethabi::Contract,
+        web3::types::{Address, Bytes, Log, Transaction, TransactionReceipt, H160, H256, U64},
+        CheapClone, EthereumBlock, EthereumBlockWithCalls, EthereumCall, LightEthereumBlock, Link,
     },
+    semver::Version,
     slog::{self, o, Logger},
 };
 
 use crate::{
-    chain::BlockFinality,
-    trigger::{EthereumBlockTriggerType, EthereumTrigger},
+    adapter::EthereumLogFilter,
+    chain::{receipt_log_addresses, BlockFinality},
+    data_source::{DataSource, Mapping, MappingABI, MappingEventHandler},
+    ethereum_adapter::parse_log_triggers,
+    trigger::{EthereumBlockTriggerType, EthereumTrigger, MappingTrigger},
 };
 
 #[test]
@@ -64,15 +69,15 @@ fn test_trigger_ordering() {
 
     // Event with transaction_index 1 and log_index 0;
     // should be the first element after sorting
-    let log1 = EthereumTrigger::Log(create_log(1, 0), None);
+    let log1 = EthereumTrigger::Log(create_log(1, 0), None, None);
 
     // Event with transaction_index 1 and log_index 1;
     // should be the second element after sorting
-    let log2 = EthereumTrigger::Log(create_log(1, 1), None);
+    let log2 = EthereumTrigger::Log(create_log(1, 1), None, None);
 
     // Event with transaction_index 2 and log_index 5;
     // should come after call1 and before call2 after sorting
-    let log3 = EthereumTrigger::Log(create_log(2, 5), None);
+    let log3 = EthereumTrigger::Log(create_log(2, 5), None, None);
 
     let triggers = vec![
         // Call triggers; these should be in the order 1, 2, 4, 3 after sorting
@@ -161,9 +166,9 @@ fn test_trigger_dedup() {
         })
     }
 
-    let log1 = EthereumTrigger::Log(create_log(1, 0), None);
-    let log2 = EthereumTrigger::Log(create_log(1, 1), None);
-    let log3 = EthereumTrigger::Log(create_log(2, 5), None);
+    let log1 = EthereumTrigger::Log(create_log(1, 0), None, None);
+    let log2 = EthereumTrigger::Log(create_log(1, 1), None, None);
+    let log3 = EthereumTrigger::Log(create_log(2, 5), None, None);
     // duplicate logs 2 and 3
     let log4 = log2.clone();
     let log5 = log3.clone();
@@ -208,3 +213,192 @@ fn test_trigger_dedup() {
         vec![log1, log2, call1, log3, call2, call3, block2, block1]
     );
 }
+
+#[test]
+fn test_receipt_log_addresses() {
+    fn create_receipt(logs: Vec<(H160, u64)>) -> Arc<TransactionReceipt> {
+        let logs = logs
+            .into_iter()
+            .map(|(address, log_index)| Log {
+                address,
+                topics: vec![],
+                data: Bytes::default(),
+                block_hash: Some(H256::zero()),
+                block_number: Some(U64::zero()),
+                transaction_hash: Some(H256::zero()),
+                transaction_index: Some(0.into()),
+                log_index: Some(log_index.into()),
+                transaction_log_index: Some(log_index.into()),
+                log_type: Some("".into()),
+                removed: Some(false),
+            })
+            .collect();
+        Arc::new(TransactionReceipt {
+            logs,
+            ..Default::default()
+        })
+    }
+
+    let address1 = H160::from_low_u64_be(1);
+    let address2 = H160::from_low_u64_be(2);
+    let address3 = H160::from_low_u64_be(3);
+
+    let mut b: LightEthereumBlock = Default::default();
+    b.number = Some(Default::default());
+    b.hash = Some(Default::default());
+    b.transactions = vec![Transaction::default(); 2];
+
+    let block = BlockFinality::NonFinal(EthereumBlockWithCalls {
+        ethereum_block: EthereumBlock {
+            block: Arc::new(b.clone()),
+            transaction_receipts: vec![
+                create_receipt(vec![(address1, 0), (address2, 1), (address1, 2)]),
+                create_receipt(vec![(address3, 3), (address2, 4), (address1, 5)]),
+            ],
+        },
+        calls: None,
+    });
+
+    let log_addresses = receipt_log_addresses(&block).unwrap();
+    assert_eq!(3, log_addresses.len());
+    for address in [address1, address2, address3] {
+        assert!(log_addresses.contains(address.as_bytes()));
+    }
+
+    // Without all of the receipts, the logs have to come from the node
+    let block = BlockFinality::NonFinal(EthereumBlockWithCalls {
+        ethereum_block: EthereumBlock {
+            block: Arc::new(b.clone()),
+            transaction_receipts: vec![create_receipt(vec![(address1, 0)])],
+        },
+        calls: None,
+    });
+    assert_eq!(None, receipt_log_addresses(&block));
+    assert_eq!(
+        None,
+        receipt_log_addresses(&BlockFinality::Final(Arc::new(b)))
+    );
+}
+
+#[test]
+fn test_log_signature_ordinal_of_log_triggers() {
+    const PINGER_ABI: &str = r#"[{"type":"event","name":"Ping","inputs":[],"anonymous":false}]"#;
+
+    let contract = Contract::load(PINGER_ABI.as_bytes()).unwrap();
+    let ping = contract.event("Ping").unwrap().signature();
+    let abi = Arc::new(MappingABI {
+        name: "Pinger".into(),
+        contract,
+    });
+    let data_source = |address: Option<H160>| DataSource {
+        kind: "ethereum/contract".into(),
+        network: None,
+        name: "Pinger".into(),
+        manifest_idx: 0,
+        address,
+        creator_address: None,
+        start_block: 0,
+        mapping: Mapping {
+            kind: "ethereum/events".into(),
+            api_version: Version::new(0, 0, 8),
+            language: "wasm/assemblyscript".into(),
+            entities: vec![],
+            abis: vec![abi.cheap_clone()],
+            block_handlers: vec![],
+            call_handlers: vec![],
+            event_handlers: vec![MappingEventHandler {
+                event: "Ping()".into(),
+                topic0: None,
+                handler: "handlePing".into(),
+                receipt: false,
+            }],
+            runtime: Arc::new(vec![]),
+            link: Link {
+                link: "pinger".into(),
+            },
+        },
+        context: Arc::new(None),
+        creation_block: None,
+        contract_abi: abi.cheap_clone(),
+    };
+
+    let pinger = H160::from_low_u64_be(1);
+    let other_pinger = H160::from_low_u64_be(2);
+    let tx_hash = H256::from_low_u64_be(3);
+    let create_log = |address: H160, log_index: u64| Log {
+        address,
+        topics: vec![ping],
+        data: Bytes::default(),
+        block_hash: Some(H256::zero()),
+        block_number: Some(U64::zero()),
+        transaction_hash: Some(tx_hash),
+        transaction_index: Some(0.into()),
+        log_index: Some(log_index.into()),
+        transaction_log_index: Some(log_index.into()),
+        log_type: None,
+        removed: Some(false),
+    };
+
+    let mut b: LightEthereumBlock = Default::default();
+    b.number = Some(Default::default());
+    b.hash = Some(Default::default());
+    b.transactions = vec![Transaction {
+        hash: tx_hash,
+        ..Default::default()
+    }];
+    let ethereum_block = EthereumBlock {
+        block: Arc::new(b),
+        transaction_receipts: vec![Arc::new(TransactionReceipt {
+            transaction_hash: tx_hash,
+            logs: vec![
+                create_log(pinger, 0),
+                create_log(other_pinger, 1),
+                create_log(pinger, 2),
+            ],
+            ..Default::default()
+        })],
+    };
+    let block = Arc::new(BlockFinality::NonFinal(EthereumBlockWithCalls {
+        ethereum_block: ethereum_block.clone(),
+        calls: None,
+    }));
+
+    let logger = Logger::root(slog::Discard, o!());
+    let ordinals = |data_source: DataSource| {
+        let filter = EthereumLogFilter::from_data_sources([&data_source]);
+        parse_log_triggers(&filter, &ethereum_block)
+            .into_iter()
+            .map(|trigger| {
+                let trigger = blockchain::DataSource::match_and_decode(
+                    &data_source,
+                    &trigger,
+                    &block,
+                    &logger,
+                )
+                .unwrap()
+                .unwrap()
+                .trigger;
+                let address = match &trigger {
+                    MappingTrigger::Log { log, .. } => log.address,
+                    _ => unreachable!(),
+                };
+                (address, trigger.log_signature_ordinal())
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Logs from other contracts are counted separately
+    assert_eq!(
+        vec![
+            (pinger, Some(0)),
+            (other_pinger, Some(0)),
+            (pinger, Some(1))
+        ],
+        ordinals(data_source(None))
+    );
+    // A filter that only matches the logs of one contract still has all the logs to count
+    assert_eq!(
+        vec![(pinger, Some(0)), (pinger, Some(1))],
+        ordinals(data_source(Some(pinger)))
+    );
+}
@@ -3,6 +3,7 @@ use graph::blockchain::TriggerData;
 use graph::data::subgraph::API_VERSION_0_0_2;
 use graph::data::subgraph::API_VERSION_0_0_6;
 use graph::data::subgraph::API_VERSION_0_0_7;
+use graph::data::subgraph::API_VERSION_0_0_8;
 use graph::prelude::ethabi::ethereum_types::H160;
 use graph::prelude::ethabi::ethereum_types::H256;
 use graph::prelude::ethabi::ethereum_types::U128;
@@ -15,6 +16,9 @@ use graph::prelude::web3::types::Block;
 use graph::prelude::web3::types::Log;
 use graph::prelude::web3::types::Transaction;
 use graph::prelude::web3::types::TransactionReceipt;
+use graph::prelude::web3::types::H2048;
+use graph::prelude::web3::types::H64;
+use graph::prelude::BigInt;
 use graph::prelude::BlockNumber;
 use graph::prelude::BlockPtr;
 use graph::prelude::{CheapClone, EthereumCall};
@@ -25,23 +29,28 @@ use graph::runtime::AscPtr;
 use graph::runtime::HostExportError;
 use graph::semver::Version;
 use graph_runtime_wasm::module::ToAscPtr;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::ops::Deref;
 use std::{cmp::Ordering, sync::Arc};
 
 use crate::runtime::abi::AscEthereumBlock;
 use crate::runtime::abi::AscEthereumBlock_0_0_6;
+use crate::runtime::abi::AscEthereumBlock_0_0_8;
 use crate::runtime::abi::AscEthereumCall;
 use crate::runtime::abi::AscEthereumCall_0_0_3;
 use crate::runtime::abi::AscEthereumEvent;
 use crate::runtime::abi::AscEthereumEvent_0_0_7;
+use crate::runtime::abi::AscEthereumEvent_0_0_8;
 use crate::runtime::abi::AscEthereumTransaction_0_0_1;
 use crate::runtime::abi::AscEthereumTransaction_0_0_2;
 use crate::runtime::abi::AscEthereumTransaction_0_0_6;
+use crate::runtime::abi::AscEthereumTransaction_0_0_8;
 
 // ETHDEP: This should be defined in only one place.
 type LightEthereumBlock = Block<Transaction>;
 
+#[derive(Clone)]
 pub enum MappingTrigger {
     Log {
         block: Arc<LightEthereumBlock>,
@@ -49,6 +58,7 @@ pub enum MappingTrigger {
         log: Arc<Log>,
         params: Vec<LogParam>,
         receipt: Option<Arc<TransactionReceipt>>,
+        signature_ordinal: Option<u32>,
     },
     Call {
         block: Arc<LightEthereumBlock>,
@@ -75,6 +85,105 @@ impl MappingTriggerTrait for MappingTrigger {
             None => String::new(),
         }
     }
+
+    fn log_signature_ordinal(&self) -> Option<u32> {
+        match self {
+            MappingTrigger::Log {
+                signature_ordinal, ..
+            } => *signature_ordinal,
+            MappingTrigger::Call { .. } | MappingTrigger::Block { .. } => None,
+        }
+    }
+
+    fn event_params(&self) -> Option<&[LogParam]> {
+        match self {
+            MappingTrigger::Log { params, .. } => Some(params),
+            MappingTrigger::Call { .. } | MappingTrigger::Block { .. } => None,
+        }
+    }
+
+    fn transaction_selector(&self) -> Option<[u8; 4]> {
+        match self {
+            MappingTrigger::Log { transaction, .. } | MappingTrigger::Call { transaction, .. } => {
+                let selector = transaction.input.0.get(..4)?;
+                Some(selector.try_into().expect("slice has 4 bytes"))
+            }
+            MappingTrigger::Block { .. } => None,
+        }
+    }
+
+    fn transaction_gas_used(&self) -> Option<BigInt> {
+        match self {
+            MappingTrigger::Log { receipt, .. } => receipt
+                .as_ref()
+                .and_then(|receipt| receipt.gas_used)
+                .map(|gas_used| BigInt::from_unsigned_u256(&gas_used)),
+            MappingTrigger::Call { .. } | MappingTrigger::Block { .. } => None,
+        }
+    }
+
+    fn log_address(&self) -> Option<&[u8]> {
+        match self {
+            MappingTrigger::Log { log, .. } => Some(log.address.as_bytes()),
+            MappingTrigger::Call { .. } | MappingTrigger::Block { .. } => None,
+        }
+    }
+
+    fn block_header_rlp(&self) -> Option<Vec<u8>> {
+        let block = match self {
+            MappingTrigger::Log { block, .. }
+            | MappingTrigger::Call { block, .. }
+            | MappingTrigger::Block { block } => block,
+        };
+        let fields = EthereumBlockData::from(block.as_ref()).header_fields()?;
+        let rlp = rlp_encode_list(&fields);
+
+        // The header of blocks since the Shanghai upgrade commits to fields that the block data
+        // doesn't carry, so only return the header if it really is the one that was hashed.
+        (tiny_keccak::keccak256(&rlp) == block.hash?.0).then_some(rlp)
+    }
+
+    fn block_logs_bloom(&self) -> Option<&[u8]> {
+        let block = match self {
+            MappingTrigger::Log { block, .. }
+            | MappingTrigger::Call { block, .. }
+            | MappingTrigger::Block { block } => block,
+        };
+        block.logs_bloom.as_ref().map(|bloom| bloom.as_bytes())
+    }
+}
+
+/// The position of each of `logs` among the logs in `logs` from the same transaction that the
+/// same contract emitted with the same signature (`topic0`), or `None` for logs without topics.
+///
+/// Log filters select logs by contract and signature, so the logs that match a filter include all
+/// the logs that are counted for each of them. That makes the ordinals the same whether the logs
+/// come from the receipts of a block or from `eth_getLogs`.
+pub(crate) fn log_signature_ordinals(logs: &[&Log]) -> Vec<Option<u32>> {
+    let key = |log: &Log| {
+        log.topics
+            .first()
+            .map(|signature| (log.transaction_hash, log.address, *signature))
+    };
+
+    let mut log_indexes: HashMap<_, Vec<_>> = HashMap::new();
+    for log in logs {
+        if let Some(key) = key(log) {
+            log_indexes.entry(key).or_default().push(log.log_index);
+        }
+    }
+    for indexes in log_indexes.values_mut() {
+        indexes.sort();
+        indexes.dedup();
+    }
+
+    logs.iter()
+        .map(|log| {
+            let indexes = &log_indexes[&key(log)?];
+            let ordinal = indexes.partition_point(|index| *index < log.log_index);
+            u32::try_from(ordinal).ok()
+        })
+        .collect()
 }
 
 // Logging the block is too verbose, so this strips the block from the trigger for Debug.
@@ -103,6 +212,7 @@ impl std::fmt::Debug for MappingTrigger {
                 log,
                 params,
                 receipt: _,
+                signature_ordinal: _,
             } => MappingTriggerWithoutBlock::Log {
                 _transaction: transaction.cheap_clone(),
                 _log: log.cheap_clone(),
@@ -140,6 +250,7 @@ impl ToAscPtr for MappingTrigger {
                 log,
                 params,
                 receipt,
+                signature_ordinal: _,
             } => {
                 let api_version = heap.api_version();
                 let ethereum_event_data = EthereumEventData {
@@ -150,8 +261,21 @@ impl ToAscPtr for MappingTrigger {
                     transaction_log_index: log.log_index.unwrap_or(U256::zero()),
                     log_type: log.log_type.clone(),
                     params,
+                    data: log.data.0.clone(),
+                    block_hash: log.block_hash.or(block.hash).unwrap_or_default(),
+                    removed: log.removed.unwrap_or(false),
                 };
-                if api_version >= API_VERSION_0_0_7 {
+                if api_version >= API_VERSION_0_0_8 {
+                    asc_new::<
+                        AscEthereumEvent_0_0_8<
+                            AscEthereumTransaction_0_0_8,
+                            AscEthereumBlock_0_0_8,
+                        >,
+                        _,
+                        _,
+                    >(heap, &(ethereum_event_data, receipt.as_deref()), gas)?
+                    .erase()
+                } else if api_version >= API_VERSION_0_0_7 {
                     asc_new::<
                         AscEthereumEvent_0_0_7<
                             AscEthereumTransaction_0_0_6,
@@ -199,7 +323,14 @@ impl ToAscPtr for MappingTrigger {
                     inputs,
                     outputs,
                 };
-                if heap.api_version() >= Version::new(0, 0, 6) {
+                if heap.api_version() >= API_VERSION_0_0_8 {
+                    asc_new::<
+                        AscEthereumCall_0_0_3<AscEthereumTransaction_0_0_8, AscEthereumBlock_0_0_8>,
+                        _,
+                        _,
+                    >(heap, &call, gas)?
+                    .erase()
+                } else if heap.api_version() >= Version::new(0, 0, 6) {
                     asc_new::<
                         AscEthereumCall_0_0_3<AscEthereumTransaction_0_0_6, AscEthereumBlock_0_0_6>,
                         _,
@@ -219,7 +350,9 @@ impl ToAscPtr for MappingTrigger {
             }
             MappingTrigger::Block { block } => {
                 let block = EthereumBlockData::from(block.as_ref());
-                if heap.api_version() >= Version::new(0, 0, 6) {
+                if heap.api_version() >= API_VERSION_0_0_8 {
+                    asc_new::<AscEthereumBlock_0_0_8, _, _>(heap, &block, gas)?.erase()
+                } else if heap.api_version() >= Version::new(0, 0, 6) {
                     asc_new::<AscEthereumBlock_0_0_6, _, _>(heap, &block, gas)?.erase()
                 } else {
                     asc_new::<AscEthereumBlock, _, _>(heap, &block, gas)?.erase()
@@ -233,7 +366,10 @@ impl ToAscPtr for MappingTrigger {
 pub enum EthereumTrigger {
     Block(BlockPtr, EthereumBlockTriggerType),
     Call(Arc<EthereumCall>),
-    Log(Arc<Log>, Option<Arc<TransactionReceipt>>),
+    /// A log with the receipt of its transaction, if it was fetched, and its position among the
+    /// logs in its transaction with the same contract and signature (see
+    /// `log_signature_ordinals`).
+    Log(Arc<Log>, Option<Arc<TransactionReceipt>>, Option<u32>),
 }
 
 impl PartialEq for EthereumTrigger {
@@ -245,7 +381,7 @@ impl PartialEq for EthereumTrigger {
 
             (Self::Call(a), Self::Call(b)) => a == b,
 
-            (Self::Log(a, a_receipt), Self::Log(b, b_receipt)) => {
+            (Self::Log(a, a_receipt, _), Self::Log(b, b_receipt, _)) => {
                 a.transaction_hash == b.transaction_hash
                     && a.log_index == b.log_index
                     && a_receipt == b_receipt
@@ -269,7 +405,7 @@ impl EthereumTrigger {
         match self {
             EthereumTrigger::Block(block_ptr, _) => block_ptr.number,
             EthereumTrigger::Call(call) => call.block_number,
-            EthereumTrigger::Log(log, _) => {
+            EthereumTrigger::Log(log, ..) => {
                 i32::try_from(log.block_number.unwrap().as_u64()).unwrap()
             }
         }
@@ -279,7 +415,7 @@ impl EthereumTrigger {
         match self {
             EthereumTrigger::Block(block_ptr, _) => block_ptr.hash_as_h256(),
             EthereumTrigger::Call(call) => call.block_hash,
-            EthereumTrigger::Log(log, _) => log.block_hash.unwrap(),
+            EthereumTrigger::Log(log, ..) => log.block_hash.unwrap(),
         }
     }
 
@@ -290,7 +426,7 @@ impl EthereumTrigger {
                 Some(address)
             }
             EthereumTrigger::Call(call) => Some(&call.to),
-            EthereumTrigger::Log(log, _) => Some(&log.address),
+            EthereumTrigger::Log(log, ..) => Some(&log.address),
 
             // Unfiltered block triggers match any data source address.
             EthereumTrigger::Block(_, EthereumBlockTriggerType::Every) => None,
@@ -312,24 +448,24 @@ impl Ord for EthereumTrigger {
             (Self::Call(a), Self::Call(b)) => a.transaction_index.cmp(&b.transaction_index),
 
             // Events are ordered by their log index
-            (Self::Log(a, _), Self::Log(b, _)) => a.log_index.cmp(&b.log_index),
+            (Self::Log(a, ..), Self::Log(b, ..)) => a.log_index.cmp(&b.log_index),
 
             // Calls vs. events are logged by their tx index;
             // if they are from the same transaction, events come first
-            (Self::Call(a), Self::Log(b, _))
+            (Self::Call(a), Self::Log(b, ..))
                 if a.transaction_index == b.transaction_index.unwrap().as_u64() =>
             {
                 Ordering::Greater
             }
-            (Self::Log(a, _), Self::Call(b))
+            (Self::Log(a, ..), Self::Call(b))
                 if a.transaction_index.unwrap().as_u64() == b.transaction_index =>
             {
                 Ordering::Less
             }
-            (Self::Call(a), Self::Log(b, _)) => a
+            (Self::Call(a), Self::Log(b, ..)) => a
                 .transaction_index
                 .cmp(&b.transaction_index.unwrap().as_u64()),
-            (Self::Log(a, _), Self::Call(b)) => a
+            (Self::Log(a, ..), Self::Call(b)) => a
                 .transaction_index
                 .unwrap()
                 .as_u64()
@@ -347,7 +483,7 @@ impl PartialOrd for EthereumTrigger {
 impl TriggerData for EthereumTrigger {
     fn error_context(&self) -> std::string::String {
         let transaction_id = match self {
-            EthereumTrigger::Log(log, _) => log.transaction_hash,
+            EthereumTrigger::Log(log, ..) => log.transaction_hash,
             EthereumTrigger::Call(call) => call.transaction_hash,
             EthereumTrigger::Block(..) => None,
         };
@@ -386,6 +522,10 @@ pub struct EthereumBlockData {
     pub total_difficulty: U256,
     pub size: Option<U256>,
     pub base_fee_per_gas: Option<U256>,
+    pub logs_bloom: Option<H2048>,
+    pub extra_data: Bytes,
+    pub mix_hash: Option<H256>,
+    pub nonce: Option<H64>,
 }
 
 impl<'a, T> From<&'a Block<T>> for EthereumBlockData {
@@ -406,8 +546,78 @@ impl<'a, T> From<&'a Block<T>> for EthereumBlockData {
             total_difficulty: block.total_difficulty.unwrap_or_default(),
             size: block.size,
             base_fee_per_gas: block.base_fee_per_gas,
+            logs_bloom: block.logs_bloom,
+            extra_data: block.extra_data.0.clone(),
+            mix_hash: block.mix_hash,
+            nonce: block.nonce,
+        }
+    }
+}
+
+impl EthereumBlockData {
+    /// The fields of the block header in the order in which they are hashed, with integers
+    /// encoded as big-endian bytes without leading zeros, or `None` if the block lacks the logs
+    /// bloom or the seal fields. The header of blocks since the Shanghai upgrade also commits to
+    /// fields that are not part of `EthereumBlockData`, such as `withdrawalsRoot`, so the hash of
+    /// these fields only matches the block hash for earlier blocks.
+    pub fn header_fields(&self) -> Option<Vec<Vec<u8>>> {
+        fn int(value: U256) -> Vec<u8> {
+            let mut bytes = [0u8; 32];
+            value.to_big_endian(&mut bytes);
+            let zeros = bytes.iter().take_while(|b| **b == 0).count();
+            bytes[zeros..].to_vec()
+        }
+
+        let mut fields = vec![
+            self.parent_hash.as_bytes().to_vec(),
+            self.uncles_hash.as_bytes().to_vec(),
+            self.author.as_bytes().to_vec(),
+            self.state_root.as_bytes().to_vec(),
+            self.transactions_root.as_bytes().to_vec(),
+            self.receipts_root.as_bytes().to_vec(),
+            self.logs_bloom?.as_bytes().to_vec(),
+            int(self.difficulty),
+            int(U256::from(self.number.as_u64())),
+            int(self.gas_limit),
+            int(self.gas_used),
+            int(self.timestamp),
+            self.extra_data.clone(),
+            self.mix_hash?.as_bytes().to_vec(),
+            self.nonce?.as_bytes().to_vec(),
+        ];
+        // Only present since the London upgrade
+        if let Some(base_fee_per_gas) = self.base_fee_per_gas {
+            fields.push(int(base_fee_per_gas));
+        }
+        Some(fields)
+    }
+}
+
+/// RLP-encodes `items` as a list of byte strings.
+fn rlp_encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+    fn length_prefix(len: usize, offset: u8, out: &mut Vec<u8>) {
+        if len < 56 {
+            out.push(offset + len as u8);
+        } else {
+            let len = len.to_be_bytes();
+            let len = &len[len.iter().take_while(|b| **b == 0).count()..];
+            out.push(offset + 55 + len.len() as u8);
+            out.extend_from_slice(len);
+        }
+    }
+
+    let mut payload = Vec::new();
+    for item in items {
+        // A single byte below 0x80 is its own encoding
+        if item.len() != 1 || item[0] >= 0x80 {
+            length_prefix(item.len(), 0x80, &mut payload);
         }
+        payload.extend_from_slice(item);
     }
+    let mut out = Vec::with_capacity(payload.len() + 9);
+    length_prefix(payload.len(), 0xc0, &mut out);
+    out.extend(payload);
+    out
 }
 
 /// Ethereum transaction data.
@@ -422,6 +632,10 @@ pub struct EthereumTransactionData {
     pub gas_price: U256,
     pub input: Bytes,
     pub nonce: U256,
+    /// Only set for EIP-1559 transactions
+    pub max_fee_per_gas: Option<U256>,
+    /// Only set for EIP-1559 transactions
+    pub max_priority_fee_per_gas: Option<U256>,
 }
 
 impl From<&'_ Transaction> for EthereumTransactionData {
@@ -439,6 +653,8 @@ impl From<&'_ Transaction> for EthereumTransactionData {
             gas_price: tx.gas_price.unwrap_or(U256::zero()), // EIP-1559 made this optional.
             input: tx.input.0.clone(),
             nonce: tx.nonce,
+            max_fee_per_gas: tx.max_fee_per_gas,
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
         }
     }
 }
@@ -453,6 +669,12 @@ pub struct EthereumEventData {
     pub block: EthereumBlockData,
     pub transaction: EthereumTransactionData,
     pub params: Vec<LogParam>,
+    /// The non-indexed event arguments, ABI encoded.
+    pub data: Bytes,
+    /// The hash of the block the log was included in.
+    pub block_hash: H256,
+    /// Whether the log was removed from the chain by a reorg.
+    pub removed: bool,
 }
 
 /// An Ethereum call executed within a transaction within a block to a contract address.
@@ -465,3 +687,536 @@ pub struct EthereumCallData {
     pub inputs: Vec<LogParam>,
     pub outputs: Vec<LogParam>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use graph::{
+        anyhow::anyhow,
+        prelude::{web3::types::Bytes as Web3Bytes, BigInt},
+        runtime::{asc_get, DeterministicHostError},
+        util::mem::init_slice,
+    };
+    use graph_runtime_wasm::asc_abi::class::AscBigInt;
+
+    #[test]
+    fn log_trigger_passes_through_event_data() {
+        let data = vec![0xde, 0xad, 0xbe, 0xef, 0x00, 0x01];
+        let (heap, event) = LogTriggerFixture {
+            data: data.clone(),
+            ..Default::default()
+        }
+        .asc_event();
+        let event_data: Vec<u8> = asc_get(&heap, event.data, &GasCounter::default(), 0).unwrap();
+
+        assert_eq!(event_data, data);
+    }
+
+    #[test]
+    fn log_trigger_passes_through_removed_and_block_hash() {
+        let asc_event = |removed: Option<bool>| {
+            let (heap, event) = LogTriggerFixture {
+                removed,
+                ..Default::default()
+            }
+            .asc_event();
+            let block_hash: H256 =
+                asc_get(&heap, event.block_hash, &GasCounter::default(), 0).unwrap();
+            (event.removed, block_hash)
+        };
+
+        let block_hash = test_block().hash.unwrap();
+        assert_eq!((false, block_hash), asc_event(Some(false)));
+        assert_eq!((true, block_hash), asc_event(Some(true)));
+        // Nodes that don't report `removed` only return logs that are on the chain
+        assert_eq!((false, block_hash), asc_event(None));
+    }
+
+    #[test]
+    fn block_data_includes_state_root() {
+        let state_root = H256::from_low_u64_be(0x5747e);
+        let block = Block {
+            state_root,
+            ..test_block()
+        };
+        assert_eq!(state_root, EthereumBlockData::from(&block).state_root);
+
+        let gas = GasCounter::default();
+        let (heap, event) = LogTriggerFixture {
+            block,
+            ..Default::default()
+        }
+        .asc_event();
+        let asc_block = event.block.read_ptr(&heap, &gas).unwrap();
+        let asc_state_root: H256 = asc_get(&heap, asc_block.state_root, &gas, 0).unwrap();
+
+        assert_eq!(state_root, asc_state_root);
+    }
+
+    #[test]
+    fn block_data_includes_logs_bloom() {
+        let mut logs_bloom = H2048::zero();
+        logs_bloom.0[0] = 0x80;
+        logs_bloom.0[255] = 0x01;
+        let block = Block {
+            logs_bloom: Some(logs_bloom),
+            ..test_block()
+        };
+
+        let gas = GasCounter::default();
+        let (heap, event) = LogTriggerFixture {
+            block,
+            ..Default::default()
+        }
+        .asc_event();
+        let asc_block = event.block.read_ptr(&heap, &gas).unwrap();
+        let asc_logs_bloom: Vec<u8> = asc_get(&heap, asc_block.logs_bloom, &gas, 0).unwrap();
+
+        assert_eq!(logs_bloom.as_bytes(), asc_logs_bloom.as_slice());
+    }
+
+    #[test]
+    fn block_data_includes_seal_fields() {
+        let block = Block {
+            extra_data: vec![0x11, 0xbb, 0xe8].into(),
+            mix_hash: Some(H256::from_low_u64_be(2)),
+            nonce: Some(H64::from_low_u64_be(0x42)),
+            ..test_block()
+        };
+
+        let gas = GasCounter::default();
+        let (heap, event) = LogTriggerFixture {
+            block,
+            ..Default::default()
+        }
+        .asc_event();
+        let asc_block = event.block.read_ptr(&heap, &gas).unwrap();
+        let extra_data: Vec<u8> = asc_get(&heap, asc_block.extra_data, &gas, 0).unwrap();
+        let mix_hash: H256 = asc_get(&heap, asc_block.mix_hash, &gas, 0).unwrap();
+        let nonce: Vec<u8> = asc_get(&heap, asc_block.nonce, &gas, 0).unwrap();
+
+        assert_eq!(vec![0x11, 0xbb, 0xe8], extra_data);
+        assert_eq!(H256::from_low_u64_be(2), mix_hash);
+        assert_eq!(H64::from_low_u64_be(0x42).as_bytes(), nonce.as_slice());
+    }
+
+    #[test]
+    fn block_header_rlp_hashes_to_the_block_hash() {
+        let hex = |s: &str| hex::decode(s).unwrap();
+        // The mainnet genesis block
+        let block = Block {
+            hash: Some(H256::from_slice(&hex(
+                "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3",
+            ))),
+            uncles_hash: H256::from_slice(&hex(
+                "1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+            )),
+            state_root: H256::from_slice(&hex(
+                "d7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544",
+            )),
+            transactions_root: H256::from_slice(&hex(
+                "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            )),
+            receipts_root: H256::from_slice(&hex(
+                "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            )),
+            number: Some(U64::from(0)),
+            gas_limit: U256::from(5000),
+            extra_data: Web3Bytes(hex(
+                "11bbe8db4e347b4e8c937c1c8370e4b5ed33adb3db69cbdb7a38e1e50b1b82fa",
+            )),
+            logs_bloom: Some(H2048::zero()),
+            difficulty: U256::from(0x4_0000_0000u64),
+            mix_hash: Some(H256::zero()),
+            nonce: Some(H64::from_low_u64_be(0x42)),
+            ..Default::default()
+        };
+        let hash = block.hash.unwrap();
+
+        let rlp = LogTriggerFixture {
+            block: block.clone(),
+            ..Default::default()
+        }
+        .trigger()
+        .block_header_rlp()
+        .unwrap();
+        assert_eq!(hash.as_bytes(), tiny_keccak::keccak256(&rlp));
+
+        // A header that commits to fields the block data doesn't carry, like `withdrawalsRoot`
+        // since Shanghai, doesn't hash to the block hash and is left out
+        let other_header = Block {
+            hash: Some(H256::from_low_u64_be(1)),
+            ..block.clone()
+        };
+        let trigger = LogTriggerFixture {
+            block: other_header,
+            ..Default::default()
+        }
+        .trigger();
+        assert_eq!(None, trigger.block_header_rlp());
+
+        // Without the seal fields, the header can't be reconstructed
+        let block = Block {
+            nonce: None,
+            ..block
+        };
+        let trigger = LogTriggerFixture {
+            block,
+            ..Default::default()
+        }
+        .trigger();
+        assert_eq!(None, trigger.block_header_rlp());
+    }
+
+    #[test]
+    fn block_logs_bloom_comes_from_the_trigger_block() {
+        let mut logs_bloom = H2048::zero();
+        logs_bloom.0[7] = 0x42;
+        let trigger = LogTriggerFixture {
+            block: Block {
+                logs_bloom: Some(logs_bloom),
+                ..test_block()
+            },
+            ..Default::default()
+        }
+        .trigger();
+        assert_eq!(Some(logs_bloom.as_bytes()), trigger.block_logs_bloom());
+
+        let trigger = LogTriggerFixture {
+            block: Block {
+                logs_bloom: None,
+                ..test_block()
+            },
+            ..Default::default()
+        }
+        .trigger();
+        assert_eq!(None, trigger.block_logs_bloom());
+    }
+
+    #[test]
+    fn creation_transaction_has_null_to() {
+        let asc_to = |to: Option<H160>| {
+            let gas = GasCounter::default();
+            let (heap, event) = LogTriggerFixture {
+                transaction: Transaction {
+                    to,
+                    ..test_transaction()
+                },
+                ..Default::default()
+            }
+            .asc_event();
+            let asc_transaction = event.transaction.read_ptr(&heap, &gas).unwrap();
+            if asc_transaction.to.is_null() {
+                None
+            } else {
+                Some(asc_get::<H160, _, _>(&heap, asc_transaction.to, &gas, 0).unwrap())
+            }
+        };
+
+        // A contract creation has no recipient
+        assert_eq!(None, asc_to(None));
+        let to = H160::from_low_u64_be(4);
+        assert_eq!(Some(to), asc_to(Some(to)));
+    }
+
+    #[test]
+    fn transaction_data_includes_fee_fields() {
+        let asc_fees = |transaction: Transaction| {
+            let gas = GasCounter::default();
+            let (heap, event) = LogTriggerFixture {
+                transaction,
+                ..Default::default()
+            }
+            .asc_event();
+            let asc_transaction = event.transaction.read_ptr(&heap, &gas).unwrap();
+            let big_int = |ptr: AscPtr<AscBigInt>| {
+                if ptr.is_null() {
+                    None
+                } else {
+                    Some(asc_get::<BigInt, _, _>(&heap, ptr, &gas, 0).unwrap())
+                }
+            };
+            (
+                big_int(asc_transaction.gas_price),
+                big_int(asc_transaction.max_fee_per_gas),
+                big_int(asc_transaction.max_priority_fee_per_gas),
+            )
+        };
+
+        let legacy = Transaction {
+            gas_price: Some(U256::from(20_000_000_000u64)),
+            transaction_type: Some(U64::from(0)),
+            ..test_transaction()
+        };
+        assert_eq!(
+            (Some(BigInt::from(20_000_000_000u64)), None, None),
+            asc_fees(legacy)
+        );
+
+        let eip1559 = Transaction {
+            gas_price: Some(U256::from(15_000_000_000u64)),
+            max_fee_per_gas: Some(U256::from(30_000_000_000u64)),
+            max_priority_fee_per_gas: Some(U256::from(2_000_000_000u64)),
+            transaction_type: Some(U64::from(2)),
+            ..test_transaction()
+        };
+        assert_eq!(
+            (
+                Some(BigInt::from(15_000_000_000u64)),
+                Some(BigInt::from(30_000_000_000u64)),
+                Some(BigInt::from(2_000_000_000u64))
+            ),
+            asc_fees(eip1559)
+        );
+    }
+
+    #[test]
+    fn log_signature_ordinals_count_per_transaction_contract_and_signature() {
+        let transfer = H256::from_low_u64_be(1);
+        let approval = H256::from_low_u64_be(2);
+        let contract = H160::from_low_u64_be(3);
+        let other_contract = H160::from_low_u64_be(4);
+        let tx = H256::from_low_u64_be(5);
+        let other_tx = H256::from_low_u64_be(6);
+        let log = |tx: H256, address: H160, log_index: u64, topic0: H256| Log {
+            address,
+            topics: vec![topic0],
+            data: Web3Bytes(vec![]),
+            block_hash: None,
+            block_number: None,
+            transaction_hash: Some(tx),
+            transaction_index: None,
+            log_index: Some(U256::from(log_index)),
+            transaction_log_index: None,
+            log_type: None,
+            removed: None,
+        };
+        let logs = vec![
+            log(tx, contract, 4, transfer),
+            log(tx, contract, 5, approval),
+            log(tx, other_contract, 6, transfer),
+            // `eth_getLogs` results are not necessarily in order, and overlapping filters can
+            // return the same log twice
+            log(tx, contract, 8, transfer),
+            log(tx, contract, 7, transfer),
+            log(tx, contract, 7, transfer),
+            log(other_tx, contract, 9, transfer),
+            Log {
+                topics: vec![],
+                ..log(tx, contract, 10, transfer)
+            },
+        ];
+
+        assert_eq!(
+            vec![
+                Some(0),
+                Some(0),
+                Some(0),
+                Some(2),
+                Some(1),
+                Some(1),
+                Some(0),
+                None
+            ],
+            log_signature_ordinals(&logs.iter().collect::<Vec<_>>())
+        );
+    }
+
+    #[test]
+    fn transaction_gas_used_comes_from_receipt() {
+        let trigger = |receipt: Option<TransactionReceipt>| {
+            LogTriggerFixture {
+                receipt,
+                ..Default::default()
+            }
+            .trigger()
+        };
+
+        let receipt = TransactionReceipt {
+            gas_used: Some(U256::from(52_139)),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(BigInt::from(52_139)),
+            trigger(Some(receipt)).transaction_gas_used()
+        );
+
+        // Handlers that don't ask for the receipt don't get it
+        assert_eq!(None, trigger(None).transaction_gas_used());
+    }
+
+    #[test]
+    fn transaction_selector_is_first_four_bytes_of_input() {
+        let selector = |input: &[u8]| {
+            LogTriggerFixture {
+                transaction: Transaction {
+                    input: Web3Bytes(input.to_vec()),
+                    ..test_transaction()
+                },
+                ..Default::default()
+            }
+            .trigger()
+            .transaction_selector()
+        };
+
+        // transfer(address,uint256) with its arguments
+        let input = hex::decode(
+            "a9059cbb\
+             000000000000000000000000000000000000000000000000000000000000dead\
+             0000000000000000000000000000000000000000000000000000000000000001",
+        )
+        .unwrap();
+        assert_eq!(Some([0xa9, 0x05, 0x9c, 0xbb]), selector(&input));
+        assert_eq!(Some([0xa9, 0x05, 0x9c, 0xbb]), selector(&input[..4]));
+
+        // Plain transfers of ether have no input
+        assert_eq!(None, selector(&[]));
+        assert_eq!(None, selector(&input[..3]));
+    }
+
+    fn test_block() -> Block<Transaction> {
+        Block {
+            hash: Some(H256::from_low_u64_be(1)),
+            number: Some(U64::from(1)),
+            ..Default::default()
+        }
+    }
+
+    fn test_transaction() -> Transaction {
+        Transaction {
+            from: Some(H160::from_low_u64_be(2)),
+            transaction_index: Some(U64::from(0)),
+            ..Default::default()
+        }
+    }
+
+    /// A log trigger for the tests. Each test only sets the parts of the trigger it is about
+    /// and takes the defaults for everything else.
+    struct LogTriggerFixture {
+        block: Block<Transaction>,
+        transaction: Transaction,
+        data: Vec<u8>,
+        removed: Option<bool>,
+        receipt: Option<TransactionReceipt>,
+    }
+
+    impl Default for LogTriggerFixture {
+        fn default() -> Self {
+            Self {
+                block: test_block(),
+                transaction: test_transaction(),
+                data: vec![],
+                removed: Some(false),
+                receipt: None,
+            }
+        }
+    }
+
+    impl LogTriggerFixture {
+        fn trigger(self) -> MappingTrigger {
+            let log = Log {
+                address: H160::from_low_u64_be(3),
+                topics: vec![],
+                data: Web3Bytes(self.data),
+                block_hash: self.block.hash,
+                block_number: self.block.number,
+                transaction_hash: Some(self.transaction.hash),
+                transaction_index: Some(U64::from(0)),
+                log_index: Some(U256::zero()),
+                transaction_log_index: Some(U256::zero()),
+                log_type: None,
+                removed: self.removed,
+            };
+
+            MappingTrigger::Log {
+                block: Arc::new(self.block),
+                transaction: Arc::new(self.transaction),
+                log: Arc::new(log),
+                params: vec![],
+                receipt: self.receipt.map(Arc::new),
+                signature_ordinal: None,
+            }
+        }
+
+        /// Write the trigger to a heap the way it is passed to a mapping with apiVersion
+        /// 0.0.8, and read the event back.
+        fn asc_event(
+            self,
+        ) -> (
+            BytesHeap,
+            AscEthereumEvent_0_0_8<AscEthereumTransaction_0_0_8, AscEthereumBlock_0_0_8>,
+        ) {
+            let mut heap = BytesHeap::new(API_VERSION_0_0_8);
+            let gas = GasCounter::default();
+            let ptr = self.trigger().to_asc_ptr(&mut heap, &gas).unwrap();
+            let event = AscPtr::new(ptr.wasm_ptr()).read_ptr(&heap, &gas).unwrap();
+            (heap, event)
+        }
+    }
+
+    struct BytesHeap {
+        api_version: Version,
+        memory: Vec<u8>,
+    }
+
+    impl BytesHeap {
+        fn new(api_version: Version) -> Self {
+            Self {
+                api_version,
+                memory: vec![],
+            }
+        }
+    }
+
+    impl AscHeap for BytesHeap {
+        fn raw_new(
+            &mut self,
+            bytes: &[u8],
+            _gas: &GasCounter,
+        ) -> Result<u32, DeterministicHostError> {
+            self.memory.extend_from_slice(bytes);
+            Ok((self.memory.len() - bytes.len()) as u32)
+        }
+
+        fn read_u32(&self, offset: u32, gas: &GasCounter) -> Result<u32, DeterministicHostError> {
+            let mut data = [std::mem::MaybeUninit::<u8>::uninit(); 4];
+            let init = self.read(offset, &mut data, gas)?;
+            Ok(u32::from_le_bytes(init.try_into().unwrap()))
+        }
+
+        fn read<'a>(
+            &self,
+            offset: u32,
+            buffer: &'a mut [std::mem::MaybeUninit<u8>],
+            _gas: &GasCounter,
+        ) -> Result<&'a mut [u8], DeterministicHostError> {
+            let start_offset = offset as usize;
+            let end_offset_exclusive = start_offset + buffer.len();
+
+            if end_offset_exclusive > self.memory.len() {
+                return Err(DeterministicHostError::from(anyhow!(
+                    "Read of {}..{} is outside of allocated memory of {} bytes",
+                    start_offset,
+                    end_offset_exclusive,
+                    self.memory.len()
+                )));
+            }
+
+            let src = &self.memory[start_offset..end_offset_exclusive];
+
+            Ok(init_slice(src, buffer))
+        }
+
+        fn api_version(&self) -> Version {
+            self.api_version.clone()
+        }
+
+        fn asc_type_id(
+            &mut self,
+            type_id_index: graph::runtime::IndexForAscTypeId,
+        ) -> Result<u32, HostExportError> {
+            Ok(type_id_index as u32)
+        }
+    }
+}
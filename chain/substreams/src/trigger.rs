@@ -27,7 +27,7 @@ use lazy_static::__Deref;
 use crate::codec;
 use crate::{codec::entity_change::Operation, Block, Chain, NoopDataSourceTemplate};
 
-#[derive(Eq, PartialEq, PartialOrd, Ord, Debug)]
+#[derive(Clone, Eq, PartialEq, PartialOrd, Ord, Debug)]
 pub struct TriggerData {}
 
 impl MappingTriggerTrait for TriggerData {
@@ -108,10 +108,19 @@ where
             }
         };
 
+        // Instances of a template are numbered in the order in which they were created. Since
+        // hosts are ordered by creation block and reverts remove the newest hosts, the numbering
+        // is the same after a revert or a restart
+        let instance_index = match data_source.creation_block() {
+            None => 0,
+            Some(_) => i32::try_from(self.hosts.template_instances(data_source.name()))?,
+        };
+
         self.host_builder.build(
             self.network.clone(),
             self.subgraph_id.clone(),
             data_source,
+            instance_index,
             self.templates.cheap_clone(),
             mapping_request_sender,
             self.host_metrics.cheap_clone(),
@@ -258,6 +267,15 @@ impl<C: Blockchain, T: RuntimeHostBuilder<C>> Hosts<C, T> {
         self.hosts.len()
     }
 
+    /// The number of hosts for data sources created from the template `name`.
+    fn template_instances(&self, name: &str) -> usize {
+        self.hosts
+            .iter()
+            .filter(|host| host.creation_block_number().is_some())
+            .filter(|host| host.data_source().name() == name)
+            .count()
+    }
+
     fn push(&mut self, host: Arc<T::Host>) {
         self.hosts.push(host.cheap_clone());
         let idx = self.hosts.len() - 1;
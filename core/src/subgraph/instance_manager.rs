@@ -367,6 +367,8 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
             deployment.hash.as_str(),
             stopwatch_metrics.clone(),
         ));
+        // Keep counting entity writes from where the deployment left off
+        host_metrics.add_entity_writes(store.entity_writes().await?);
 
         let subgraph_metrics = Arc::new(SubgraphInstanceMetrics::new(
             registry.cheap_clone(),
@@ -9,7 +9,9 @@ use graph::blockchain::{Block, Blockchain, DataSource as _, TriggerFilter as _};
 use graph::components::store::{EmptyStore, EntityKey, GetScope, StoredDynamicDataSource};
 use graph::components::{
     store::ModificationsAndCache,
-    subgraph::{MappingError, PoICausalityRegion, ProofOfIndexing, SharedProofOfIndexing},
+    subgraph::{
+        LogAddresses, MappingError, PoICausalityRegion, ProofOfIndexing, SharedProofOfIndexing,
+    },
 };
 use graph::data::store::scalar::Bytes;
 use graph::data::subgraph::schema::POI_DIGEST;
@@ -219,6 +221,20 @@ where
                 "block_hash" => format!("{}", block_ptr.hash)
         ));
 
+        // Only fetched from the chain if a handler asks for them
+        let log_addresses = {
+            let triggers_adapter = self.inputs.triggers_adapter.cheap_clone();
+            let logger = logger.cheap_clone();
+            let block = block.cheap_clone();
+            Arc::new(LogAddresses::new(move || {
+                graph::block_on(triggers_adapter.log_addresses(&logger, &block))
+            }))
+        };
+
+        // The chain head and its timestamp are read once for the whole block. They are only used
+        // by non-deterministic host exports, so not knowing them is not an error
+        let (chain_head_ptr, chain_head_timestamp) = self.chain_head(&logger).await;
+
         debug!(logger, "Start processing block";
                "triggers" => triggers.len());
 
@@ -248,6 +264,9 @@ where
                     &proof_of_indexing,
                     &block,
                     triggers.into_iter().map(TriggerData::Onchain),
+                    log_addresses,
+                    chain_head_ptr,
+                    chain_head_timestamp,
                     &causality_region,
                 )
                 .await
@@ -275,6 +294,24 @@ where
             }
         };
 
+        // Handlers read chain state within the reorg threshold, which a reorg may have changed
+        // since. Only keep their results if the block is still on the main chain, and otherwise
+        // restart the block stream so that it detects the reorg, like for a possible reorg above.
+        if block_state.is_reorg_sensitive()
+            && !self
+                .inputs
+                .triggers_adapter
+                .is_on_main_chain(block_ptr.cheap_clone())
+                .await
+                .map_err(BlockProcessingError::Unknown)?
+        {
+            info!(logger,
+                "Block is no longer on the main chain after handlers read chain state, retrying";
+                "reorg_threshold" => ENV_VARS.reorg_threshold,
+            );
+            return Ok(Action::Restart);
+        }
+
         // If new onchain data sources have been created, and static filters are not in use, it is necessary
         // to restart the block stream with the new filters.
         let needs_restart =
@@ -399,6 +436,10 @@ where
             return Err(BlockProcessingError::Canceled);
         }
 
+        block_state
+            .store_raw_events(block.number())
+            .map_err(BlockProcessingError::Unknown)?;
+
         if let Some(proof_of_indexing) = proof_of_indexing {
             let proof_of_indexing = Arc::try_unwrap(proof_of_indexing).unwrap().into_inner();
             update_proof_of_indexing(
@@ -459,6 +500,13 @@ where
             );
         }
 
+        let warning_count = block_state.warnings.len();
+        for (i, warning) in block_state.warnings.iter().enumerate() {
+            warn!(&logger, "Subgraph warning {}/{}", i + 1, warning_count;
+                "warning" => warning.replace('\n', "\t")
+            );
+        }
+
         // Transact entity operations into the store and update the
         // subgraph's block stream pointer
         let _section = self.metrics.host.stopwatch.start_section("transact_block");
@@ -480,6 +528,7 @@ where
 
         let BlockState {
             deterministic_errors,
+            warnings,
             mut persisted_data_sources,
             ..
         } = block_state;
@@ -487,6 +536,7 @@ where
         let first_error = deterministic_errors.first().cloned();
 
         persisted_data_sources.extend(persisted_off_chain_data_sources);
+        let entity_writes = mods.len() as u64;
         store
             .transact_block_operations(
                 block_ptr,
@@ -495,11 +545,13 @@ where
                 &self.metrics.host.stopwatch,
                 persisted_data_sources,
                 deterministic_errors,
+                warnings,
                 processed_data_sources,
                 is_non_fatal_errors_active,
             )
             .await
             .context("Failed to transact block operations")?;
+        self.metrics.host.add_entity_writes(entity_writes);
 
         // For subgraphs with `nonFatalErrors` feature disabled, we consider
         // any error as fatal.
@@ -537,17 +589,44 @@ where
         }
     }
 
+    /// The current chain head and its timestamp, as far as the chain store knows them
+    async fn chain_head(&self, logger: &Logger) -> (Option<BlockPtr>, Option<u64>) {
+        let chain_store = self.inputs.chain.chain_store();
+
+        let head = chain_store.cached_head_ptr().await.unwrap_or_else(|e| {
+            warn!(logger, "Failed to read the chain head"; "error" => format!("{:#}", e));
+            None
+        });
+        let timestamp = match &head {
+            Some(head) => chain_store
+                .block_number(&head.hash)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!(logger, "Failed to read the chain head timestamp"; "error" => format!("{:#}", e));
+                    None
+                })
+                .and_then(|(_, _, timestamp)| timestamp),
+            None => None,
+        };
+        (head, timestamp)
+    }
+
     async fn process_triggers(
         &mut self,
         proof_of_indexing: &SharedProofOfIndexing,
         block: &Arc<C::Block>,
         triggers: impl Iterator<Item = TriggerData<C>>,
+        log_addresses: Arc<LogAddresses>,
+        chain_head_ptr: Option<BlockPtr>,
+        chain_head_timestamp: Option<u64>,
         causality_region: &str,
     ) -> Result<BlockState<C>, MappingError> {
         let mut block_state = BlockState::new(
             self.inputs.store.clone(),
             std::mem::take(&mut self.state.entity_lfu_cache),
         );
+        block_state.set_log_addresses(log_addresses);
+        block_state.set_chain_head(chain_head_ptr, chain_head_timestamp);
 
         for trigger in triggers {
             block_state = self
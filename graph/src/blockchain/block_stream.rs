@@ -1,6 +1,7 @@
 use anyhow::Error;
 use async_stream::stream;
 use futures03::Stream;
+use std::collections::HashSet;
 use std::fmt;
 use std::sync::Arc;
 use thiserror::Error;
@@ -269,6 +270,17 @@ pub trait TriggersAdapter<C: Blockchain>: Send + Sync {
 
     /// Get pointer to parent of `block`. This is called when reverting `block`.
     async fn parent_ptr(&self, block: &BlockPtr) -> Result<Option<BlockPtr>, Error>;
+
+    /// The distinct addresses of the contracts that emitted logs in `block`. Unlike the
+    /// triggers of a block, this covers all of its logs, not only the ones matching a filter.
+    /// Chains without event logs have none.
+    async fn log_addresses(
+        &self,
+        _logger: &Logger,
+        _block: &C::Block,
+    ) -> Result<HashSet<Vec<u8>>, Error> {
+        Ok(HashSet::new())
+    }
 }
 
 #[async_trait]
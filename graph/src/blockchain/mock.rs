@@ -234,7 +234,7 @@ impl TriggerData for MockTriggerData {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct MockMappingTrigger {}
 
 impl MappingTriggerTrait for MockMappingTrigger {
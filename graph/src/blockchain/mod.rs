@@ -40,7 +40,7 @@ use std::{
     collections::HashMap,
     fmt::{self, Debug},
     str::FromStr,
-    sync::Arc,
+    sync::{Arc, Mutex},
 };
 use web3::types::H256;
 
@@ -167,7 +167,7 @@ pub trait Blockchain: Debug + Sized + Send + Sync + Unpin + 'static {
 
     /// Decoded trigger ready to be processed by the mapping.
     /// New implementations should have this be the same as `TriggerData`.
-    type MappingTrigger: MappingTriggerTrait + Send + Sync + Debug;
+    type MappingTrigger: MappingTriggerTrait + Clone + Send + Sync + Debug;
 
     /// Trigger filter used as input to the triggers adapter.
     type TriggerFilter: TriggerFilter<Self>;
@@ -263,6 +263,13 @@ pub trait DataSource<C: Blockchain>: 'static + Sized + Send + Sync + Clone {
     ) -> Result<Self, Error>;
 
     fn address(&self) -> Option<&[u8]>;
+
+    /// The address of the data source that created this one from a template. Data sources
+    /// declared in the manifest have no creator.
+    fn creator_address(&self) -> Option<&[u8]> {
+        None
+    }
+
     fn start_block(&self) -> BlockNumber;
     fn name(&self) -> &str;
     fn kind(&self) -> &str;
@@ -348,6 +355,48 @@ pub trait MappingTriggerTrait {
     /// If there is an error when processing this trigger, this will called to add relevant context.
     /// For example an useful return is: `"block #<N> (<hash>), transaction <tx_hash>".
     fn error_context(&self) -> String;
+
+    /// For triggers caused by an event log, the position of that log among the logs in its
+    /// transaction that the same contract emitted with the same signature, if the chain can
+    /// determine it.
+    fn log_signature_ordinal(&self) -> Option<u32> {
+        None
+    }
+
+    /// For triggers caused by an event log, the decoded parameters of the event.
+    fn event_params(&self) -> Option<&[ethabi::LogParam]> {
+        None
+    }
+
+    /// For triggers caused by a transaction, the selector of the function it called, i.e. the
+    /// first 4 bytes of its input, if the input is at least that long.
+    fn transaction_selector(&self) -> Option<[u8; 4]> {
+        None
+    }
+
+    /// For triggers caused by a transaction, the gas used by that transaction, if its receipt
+    /// is available.
+    fn transaction_gas_used(&self) -> Option<crate::prelude::BigInt> {
+        None
+    }
+
+    /// For triggers caused by an event log, the address of the contract that emitted it.
+    fn log_address(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// For chains that hash the RLP encoding of block headers, the RLP encoding of the header of
+    /// the block containing the trigger, if it can be reconstructed from the block data and
+    /// hashes to the block hash.
+    fn block_header_rlp(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// For chains with a logs bloom in their block headers, the logs bloom of the block
+    /// containing the trigger, if the block data carries it.
+    fn block_logs_bloom(&self) -> Option<&[u8]> {
+        None
+    }
 }
 
 pub struct HostFnCtx<'a> {
@@ -355,6 +404,12 @@ pub struct HostFnCtx<'a> {
     pub block_ptr: BlockPtr,
     pub heap: &'a mut dyn AscHeap,
     pub gas: GasCounter,
+    /// Where host fns that call contracts put the raw output of the call, for
+    /// `ethereum.lastReturnData`. It is `None` if the last call reverted.
+    pub last_return_data: Arc<Mutex<Option<Vec<u8>>>>,
+    /// The number of the block whose chain state the host fn read. It starts out as the number
+    /// of `block_ptr`, and host fns that read another block set it to that block.
+    pub chain_read_block: &'a mut BlockNumber,
 }
 
 /// Host fn that receives one u32 argument and returns an u32.
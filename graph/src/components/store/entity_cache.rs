@@ -1,7 +1,7 @@
 use anyhow::anyhow;
 use inflector::Inflector;
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt::{self, Debug};
 use std::sync::Arc;
 
@@ -11,6 +11,7 @@ use crate::components::store::{self as s, Entity, EntityKey, EntityOperation};
 use crate::data::store::IntoEntityIterator;
 use crate::prelude::ENV_VARS;
 use crate::schema::InputSchema;
+use crate::util::cache_weight::CacheWeight;
 use crate::util::intern::Error as InternError;
 use crate::util::lfu_cache::{EvictStats, LfuCache};
 
@@ -43,6 +44,13 @@ impl EntityOp {
         Ok(())
     }
 
+    fn weight(&self) -> usize {
+        match self {
+            EntityOp::Remove => 0,
+            EntityOp::Update(entity) | EntityOp::Overwrite(entity) => entity.weight(),
+        }
+    }
+
     fn accumulate(&mut self, next: EntityOp) {
         use EntityOp::*;
         let update = match next {
@@ -195,6 +203,115 @@ impl EntityCache {
         Ok(entity)
     }
 
+    /// Return the block at which the entity for `key` was last written.
+    /// Entities that have been set in the block that is currently being
+    /// processed report `block`, entities that have been removed in it
+    /// report `None`, and for all others, the store is consulted
+    pub fn last_modified_block(
+        &self,
+        key: &EntityKey,
+        block: BlockNumber,
+    ) -> Result<Option<BlockNumber>, StoreError> {
+        match self
+            .handler_updates
+            .get(key)
+            .or_else(|| self.updates.get(key))
+        {
+            Some(EntityOp::Remove) => Ok(None),
+            Some(EntityOp::Update(_)) | Some(EntityOp::Overwrite(_)) => Ok(Some(block)),
+            None => self.store.last_modified_block(key),
+        }
+    }
+
+    /// Return `true` if the entity for `key` has been created in the block
+    /// that is currently being processed, i.e., if it has been set during
+    /// the block but did not exist in the store before it. Entities that
+    /// were only read, or that already existed and were updated, are not
+    /// new
+    pub fn is_new_in_block(&mut self, key: &EntityKey) -> Result<bool, StoreError> {
+        let written = matches!(
+            self.handler_updates
+                .get(key)
+                .or_else(|| self.updates.get(key)),
+            Some(EntityOp::Update(_)) | Some(EntityOp::Overwrite(_))
+        );
+        if !written {
+            return Ok(false);
+        }
+
+        if !self.current.contains_key(key) {
+            let entity = self.store.get(key)?;
+            self.current.insert(key.clone(), entity);
+        }
+        // Unwrap: we just made sure the entity is in `current`
+        Ok(self.current.get(key).unwrap().is_none())
+    }
+
+    /// An estimate of the memory taken up by the changes that have been
+    /// made in the current block and that have not been written to the
+    /// store yet. Entities that were only read from the store are not
+    /// counted
+    pub fn pending_weight(&self) -> usize {
+        self.updates
+            .iter()
+            .chain(self.handler_updates.iter())
+            .map(|(key, op)| key.weight() + op.weight())
+            .sum()
+    }
+
+    /// A canonical encoding of the changes that have been made in the
+    /// current block and that have not been written to the store yet.
+    /// Changes are sorted by key, and the attributes of each entity by
+    /// name, so that the encoding does not depend on the order in which
+    /// entities were changed
+    pub fn encode_pending_changes(&self) -> Result<Vec<u8>, anyhow::Error> {
+        let mut ops: BTreeMap<&EntityKey, EntityOp> = self
+            .updates
+            .iter()
+            .map(|(key, op)| (key, op.clone()))
+            .collect();
+        for (key, op) in &self.handler_updates {
+            match ops.get_mut(key) {
+                Some(current) => current.accumulate(op.clone()),
+                None => {
+                    ops.insert(key, op.clone());
+                }
+            }
+        }
+
+        let changes: Vec<_> = ops
+            .iter()
+            .map(|(key, op)| {
+                let (kind, entity) = match op {
+                    EntityOp::Remove => ("remove", None),
+                    EntityOp::Update(entity) => ("update", Some(entity.sorted_ref())),
+                    EntityOp::Overwrite(entity) => ("overwrite", Some(entity.sorted_ref())),
+                };
+                (
+                    key.entity_type.as_str(),
+                    key.entity_id.as_str(),
+                    key.causality_region.to_string(),
+                    kind,
+                    entity,
+                )
+            })
+            .collect();
+        Ok(serde_json::to_vec(&changes)?)
+    }
+
+    /// The keys of all entities that have been changed in the current
+    /// block, formatted as `type:id` and sorted so that the order does not
+    /// depend on the order in which entities were changed
+    pub fn changed_keys(&self) -> Vec<String> {
+        let keys: BTreeSet<String> = self
+            .updates
+            .keys()
+            .chain(self.handler_updates.keys())
+            .map(|key| format!("{}:{}", key.entity_type, key.entity_id))
+            .collect();
+        keys.into_iter().collect()
+    }
+
     pub fn load_related(
         &mut self,
         eref: &LoadRelatedRequest,
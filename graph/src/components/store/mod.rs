@@ -59,6 +59,10 @@ impl EntityType {
     pub fn is_poi(&self) -> bool {
         self.0.as_str() == "Poi$"
     }
+
+    pub fn is_raw_event(&self) -> bool {
+        self.0.as_str() == "RawEvent$"
+    }
 }
 
 impl fmt::Display for EntityType {
@@ -943,6 +947,8 @@ pub struct StoredDynamicDataSource {
     pub creation_block: Option<BlockNumber>,
     pub done_at: Option<i32>,
     pub causality_region: CausalityRegion,
+    /// The address of the data source that created this one, if known.
+    pub creator_address: Option<Bytes>,
 }
 
 /// An internal identifer for the specific instance of a deployment. The
@@ -1130,6 +1136,10 @@ impl ReadStore for EmptyStore {
         Ok(None)
     }
 
+    fn last_modified_block(&self, _key: &EntityKey) -> Result<Option<BlockNumber>, StoreError> {
+        Ok(None)
+    }
+
     fn get_many(&self, _: BTreeSet<EntityKey>) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
         Ok(BTreeMap::new())
     }
@@ -1144,6 +1154,10 @@ impl ReadStore for EmptyStore {
     fn input_schema(&self) -> Arc<InputSchema> {
         self.schema.cheap_clone()
     }
+
+    fn supports_raw_events(&self) -> bool {
+        false
+    }
 }
 
 /// An estimate of the number of entities and the number of entity versions
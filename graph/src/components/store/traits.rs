@@ -208,6 +208,10 @@ pub trait ReadStore: Send + Sync + 'static {
     /// Looks up an entity using the given store key at the latest block.
     fn get(&self, key: &EntityKey) -> Result<Option<Entity>, StoreError>;
 
+    /// Return the block at which the entity for `key` was last written,
+    /// or `None` if the entity does not exist as of the latest block
+    fn last_modified_block(&self, key: &EntityKey) -> Result<Option<BlockNumber>, StoreError>;
+
     /// Look up multiple entities as of the latest block.
     fn get_many(
         &self,
@@ -221,6 +225,11 @@ pub trait ReadStore: Send + Sync + 'static {
     ) -> Result<BTreeMap<EntityKey, Entity>, StoreError>;
 
     fn input_schema(&self) -> Arc<InputSchema>;
+
+    /// Whether the deployment has a table for raw events. Deployments
+    /// created before raw events were introduced get one when they are
+    /// opened for writing
+    fn supports_raw_events(&self) -> bool;
 }
 
 // This silly impl is needed until https://github.com/rust-lang/rust/issues/65991 is stable.
@@ -229,6 +238,10 @@ impl<T: ?Sized + ReadStore> ReadStore for Arc<T> {
         (**self).get(key)
     }
 
+    fn last_modified_block(&self, key: &EntityKey) -> Result<Option<BlockNumber>, StoreError> {
+        (**self).last_modified_block(key)
+    }
+
     fn get_many(
         &self,
         keys: BTreeSet<EntityKey>,
@@ -246,6 +259,10 @@ impl<T: ?Sized + ReadStore> ReadStore for Arc<T> {
     fn input_schema(&self) -> Arc<InputSchema> {
         (**self).input_schema()
     }
+
+    fn supports_raw_events(&self) -> bool {
+        (**self).supports_raw_events()
+    }
 }
 
 pub trait DeploymentCursorTracker: Sync + Send + 'static {
@@ -319,6 +336,7 @@ pub trait WritableStore: ReadStore + DeploymentCursorTracker {
         stopwatch: &StopwatchMetrics,
         data_sources: Vec<StoredDynamicDataSource>,
         deterministic_errors: Vec<SubgraphError>,
+        warnings: Vec<String>,
         offchain_to_remove: Vec<StoredDynamicDataSource>,
         is_non_fatal_errors_active: bool,
     ) -> Result<(), StoreError>;
@@ -349,6 +367,10 @@ pub trait WritableStore: ReadStore + DeploymentCursorTracker {
 
     async fn health(&self) -> Result<SubgraphHealth, StoreError>;
 
+    /// The number of entity modifications that were ever transacted for this
+    /// deployment, including those that are still queued for writing
+    async fn entity_writes(&self) -> Result<u64, StoreError>;
+
     /// Wait for the background writer to finish processing its queue
     async fn flush(&self) -> Result<(), StoreError>;
 
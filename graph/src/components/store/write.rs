@@ -368,6 +368,20 @@ impl RowGroup {
             .map(|emod| emod.as_entity_op(at))
     }
 
+    /// Like `last_op`, but also return the block at which the underlying
+    /// modification was made. For a `Write`, that is the block at which
+    /// the entity version was written
+    pub fn last_op_with_block(
+        &self,
+        key: &EntityKey,
+        at: BlockNumber,
+    ) -> Option<(BlockNumber, EntityOp<'_>)> {
+        self.rows
+            .iter()
+            .rfind(|emod| emod.key() == key && emod.block() <= at)
+            .map(|emod| (emod.block(), emod.as_entity_op(at)))
+    }
+
     pub fn effective_ops(&self, at: BlockNumber) -> impl Iterator<Item = EntityOp<'_>> {
         let mut seen = HashSet::new();
         self.rows
@@ -626,9 +640,15 @@ pub struct Batch {
     /// New data sources
     pub data_sources: DataSources,
     pub deterministic_errors: Vec<SubgraphError>,
+    /// The warnings that handlers emitted for the last block in this batch
+    /// that had any, together with the number of that block
+    pub warnings: Option<(BlockNumber, Vec<String>)>,
     pub offchain_to_remove: DataSources,
     pub error: Option<StoreError>,
     pub is_non_fatal_errors_active: bool,
+    /// The number of entity modifications the blocks in this batch made,
+    /// before they were combined into `mods`
+    pub entity_writes: usize,
 }
 
 impl Batch {
@@ -639,10 +659,12 @@ impl Batch {
         mut raw_mods: Vec<EntityModification>,
         data_sources: Vec<StoredDynamicDataSource>,
         deterministic_errors: Vec<SubgraphError>,
+        warnings: Vec<String>,
         offchain_to_remove: Vec<StoredDynamicDataSource>,
         is_non_fatal_errors_active: bool,
     ) -> Result<Self, StoreError> {
         let block = block_ptr.number;
+        let entity_writes = raw_mods.len();
 
         // Sort the modifications such that writes and clamps are
         // consecutive. It's not needed for correctness but helps with some
@@ -662,6 +684,11 @@ impl Batch {
 
         let data_sources = DataSources::new(block_ptr.cheap_clone(), data_sources);
         let offchain_to_remove = DataSources::new(block_ptr.cheap_clone(), offchain_to_remove);
+        let warnings = if warnings.is_empty() {
+            None
+        } else {
+            Some((block, warnings))
+        };
         let first_block = block_ptr.number;
         Ok(Self {
             block_ptr,
@@ -670,9 +697,11 @@ impl Batch {
             mods,
             data_sources,
             deterministic_errors,
+            warnings,
             offchain_to_remove,
             error: None,
             is_non_fatal_errors_active,
+            entity_writes,
         })
     }
 
@@ -687,7 +716,11 @@ impl Batch {
         self.data_sources.append(batch.data_sources);
         self.deterministic_errors
             .append(&mut batch.deterministic_errors);
+        if batch.warnings.is_some() {
+            self.warnings = batch.warnings;
+        }
         self.offchain_to_remove.append(batch.offchain_to_remove);
+        self.entity_writes += batch.entity_writes;
         Ok(())
     }
 
@@ -718,6 +751,18 @@ impl Batch {
         self.mods.group(&key.entity_type)?.last_op(key, block)
     }
 
+    /// Like `last_op`, but also return the block of the underlying
+    /// modification; see `RowGroup::last_op_with_block`
+    pub fn last_op_with_block(
+        &self,
+        key: &EntityKey,
+        block: BlockNumber,
+    ) -> Option<(BlockNumber, EntityOp<'_>)> {
+        self.mods
+            .group(&key.entity_type)?
+            .last_op_with_block(key, block)
+    }
+
     pub fn effective_ops(
         &self,
         entity_type: &EntityType,
@@ -1132,5 +1177,14 @@ mod test {
 
         let op = group.last_op(&key, 0);
         assert_eq!(None, op);
+
+        let written_at = |at| match group.last_op_with_block(&key, at) {
+            Some((block, EntityOp::Write { .. })) => Some(block),
+            _ => None,
+        };
+        assert_eq!(None, written_at(5));
+        assert_eq!(Some(2), written_at(2));
+        assert_eq!(Some(1), written_at(1));
+        assert_eq!(None, written_at(0));
     }
 }
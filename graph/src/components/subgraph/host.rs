@@ -1,4 +1,5 @@
 use std::cmp::PartialEq;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -75,6 +76,10 @@ pub trait RuntimeHost<C: Blockchain>: Send + Sync + 'static {
     /// Returns `None` for static data sources.
     fn creation_block_number(&self) -> Option<BlockNumber>;
 
+    /// The creation ordinal of this host's data source among the data sources created from the
+    /// same template, starting at 0. Returns 0 for static data sources.
+    fn instance_index(&self) -> i32;
+
     /// Offchain data sources track done_at which is set once the
     /// trigger has been processed.
     fn done_at(&self) -> Option<BlockNumber>;
@@ -87,7 +92,11 @@ pub trait RuntimeHost<C: Blockchain>: Send + Sync + 'static {
 pub struct HostMetrics {
     handler_execution_time: Box<HistogramVec>,
     host_fn_execution_time: Box<HistogramVec>,
+    mapping_metrics: Box<CounterVec>,
     pub stopwatch: StopwatchMetrics,
+    /// Entity operations ever transacted for the deployment, seeded from the
+    /// count that the store keeps when the deployment is started
+    entity_writes: AtomicU64,
 }
 
 impl HostMetrics {
@@ -114,10 +123,20 @@ impl HostMetrics {
                 vec![0.025, 0.05, 0.2, 2.0, 8.0, 20.0],
             )
             .expect("failed to create `deployment_host_fn_execution_time` histogram");
+        let mapping_metrics = registry
+            .new_deployment_counter_vec(
+                "deployment_mapping_metric",
+                "Counts events reported by mappings through `runtime.metricInc`",
+                subgraph,
+                vec![String::from("metric")],
+            )
+            .expect("failed to create `deployment_mapping_metric` counter");
         Self {
             handler_execution_time,
             host_fn_execution_time,
+            mapping_metrics,
             stopwatch,
+            entity_writes: AtomicU64::new(0),
         }
     }
 
@@ -133,6 +152,24 @@ impl HostMetrics {
             .observe(duration);
     }
 
+    /// Increment the mapping-defined metric `name` by `value`. Callers
+    /// must make sure that `name` comes from a bounded set of names
+    pub fn increment_mapping_metric(&self, name: &str, value: f64) {
+        self.mapping_metrics
+            .with_label_values(&[name][..])
+            .inc_by(value);
+    }
+
+    /// Record that `count` entity operations were transacted
+    pub fn add_entity_writes(&self, count: u64) {
+        self.entity_writes.fetch_add(count, Ordering::SeqCst);
+    }
+
+    /// The number of entity operations ever transacted for the deployment
+    pub fn entity_writes(&self) -> u64 {
+        self.entity_writes.load(Ordering::SeqCst)
+    }
+
     pub fn time_host_fn_execution_region(
         self: Arc<HostMetrics>,
         fn_name: &'static str,
@@ -164,12 +201,14 @@ pub trait RuntimeHostBuilder<C: Blockchain>: Clone + Send + Sync + 'static {
     type Host: RuntimeHost<C> + PartialEq;
     type Req: 'static + Send;
 
-    /// Build a new runtime host for a subgraph data source.
+    /// Build a new runtime host for a subgraph data source. `instance_index` is the creation
+    /// ordinal of the data source among the instances of its template.
     fn build(
         &self,
         network_name: String,
         subgraph_id: DeploymentHash,
         data_source: DataSource<C>,
+        instance_index: i32,
         top_level_templates: Arc<Vec<DataSourceTemplate<C>>>,
         mapping_request_sender: mpsc::Sender<Self::Req>,
         metrics: Arc<HostMetrics>,
@@ -184,3 +223,36 @@ pub trait RuntimeHostBuilder<C: Blockchain>: Clone + Send + Sync + 'static {
         metrics: Arc<HostMetrics>,
     ) -> Result<mpsc::Sender<Self::Req>, anyhow::Error>;
 }
+
+#[cfg(test)]
+mod tests {
+    use prometheus::Registry;
+
+    use super::*;
+
+    #[test]
+    fn increment_mapping_metric() {
+        let logger = Logger::root(slog::Discard, o!());
+        let registry = Arc::new(Registry::new());
+        let metrics_registry = Arc::new(MetricsRegistry::new(logger.clone(), registry.clone()));
+        let deployment = DeploymentHash::new("mappingMetrics").unwrap();
+        let stopwatch =
+            StopwatchMetrics::new(logger, deployment.clone(), "test", metrics_registry.clone());
+        let host_metrics = HostMetrics::new(metrics_registry, deployment.as_str(), stopwatch);
+
+        host_metrics.increment_mapping_metric("swaps", 2.0);
+        host_metrics.increment_mapping_metric("swaps", 3.0);
+
+        let family = registry
+            .gather()
+            .into_iter()
+            .find(|family| family.get_name() == "deployment_mapping_metric")
+            .expect("the mapping metric is registered");
+        let metric = &family.get_metric()[0];
+        assert!(metric
+            .get_label()
+            .iter()
+            .any(|label| label.get_name() == "metric" && label.get_value() == "swaps"));
+        assert_eq!(5.0, metric.get_counter().get_value());
+    }
+}
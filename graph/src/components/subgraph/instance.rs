@@ -1,7 +1,13 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Mutex;
+
 use crate::{
     blockchain::Blockchain,
     components::store::{EntityKey, ReadStore, StoredDynamicDataSource},
-    data::subgraph::schema::SubgraphError,
+    data::subgraph::schema::{
+        SubgraphError, RAW_EVENT_OBJECT, RAW_EVENT_PAYLOAD, RAW_EVENT_SEQUENCE, RAW_EVENT_TAG,
+    },
     data_source::DataSourceTemplate,
     prelude::*,
     util::lfu_cache::LfuCache,
@@ -13,12 +19,74 @@ pub struct DataSourceTemplateInfo<C: Blockchain> {
     pub params: Vec<String>,
     pub context: Option<DataSourceContext>,
     pub creation_block: BlockNumber,
+    /// The address of the data source that created this one, if it has an address.
+    pub creator_address: Option<Vec<u8>>,
+}
+
+/// A record that a handler appended to the raw event table with
+/// `store.appendRaw`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawEvent {
+    pub tag: String,
+    /// A JSON document
+    pub payload: String,
+}
+
+/// The distinct addresses of the contracts that emitted logs in a block, see
+/// `TriggersAdapter::log_addresses`. Most handlers never ask for them, so they are only loaded
+/// the first time they are needed and then kept for the rest of the block.
+pub struct LogAddresses {
+    load: Box<dyn Fn() -> Result<HashSet<Vec<u8>>, Error> + Send + Sync>,
+    loaded: Mutex<Option<Arc<HashSet<Vec<u8>>>>>,
+}
+
+impl LogAddresses {
+    pub fn new(load: impl Fn() -> Result<HashSet<Vec<u8>>, Error> + Send + Sync + 'static) -> Self {
+        LogAddresses {
+            load: Box::new(load),
+            loaded: Mutex::new(None),
+        }
+    }
+
+    pub fn get(&self) -> Result<Arc<HashSet<Vec<u8>>>, Error> {
+        let mut loaded = self.loaded.lock().unwrap();
+        if let Some(addresses) = loaded.as_ref() {
+            return Ok(addresses.cheap_clone());
+        }
+        let addresses = Arc::new((self.load)()?);
+        *loaded = Some(addresses.cheap_clone());
+        Ok(addresses)
+    }
+}
+
+impl Default for LogAddresses {
+    fn default() -> Self {
+        LogAddresses::new(|| Ok(HashSet::new()))
+    }
+}
+
+impl fmt::Debug for LogAddresses {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogAddresses")
+            .field("loaded", &self.loaded)
+            .finish()
+    }
 }
 
 #[derive(Debug)]
 pub struct BlockState<C: Blockchain> {
     pub entity_cache: EntityCache,
     pub deterministic_errors: Vec<SubgraphError>,
+    /// Non-fatal warnings that handlers emitted through `runtime.warn`
+    pub warnings: Vec<String>,
+    /// Warnings emitted by the current handler. They are dropped if the handler fails
+    handler_warnings: Vec<String>,
+    /// The handlers that ran successfully for this block, as pairs of the
+    /// name of the data source or template and the name of the handler
+    triggered_handlers: HashSet<(String, String)>,
+    /// Whether handlers read chain state for a block that could still be
+    /// reorged, so that their results might change on reprocessing
+    reorg_sensitive: bool,
     created_data_sources: Vec<DataSourceTemplateInfo<C>>,
 
     // Data sources to be transacted into the store.
@@ -27,11 +95,24 @@ pub struct BlockState<C: Blockchain> {
     // Data sources created in the current handler.
     handler_created_data_sources: Vec<DataSourceTemplateInfo<C>>,
 
+    // Raw events appended in this block, in order, and those appended in
+    // the current handler.
+    raw_events: Vec<RawEvent>,
+    handler_raw_events: Vec<RawEvent>,
+
     // data source that have been processed.
     pub processed_data_sources: Vec<StoredDynamicDataSource>,
 
     // Marks whether a handler is currently executing.
     in_handler: bool,
+
+    /// The addresses that emitted logs in the block
+    log_addresses: Arc<LogAddresses>,
+
+    /// The chain head when processing of the block started, and its
+    /// timestamp if the chain store knows it
+    chain_head_ptr: Option<BlockPtr>,
+    chain_head_timestamp: Option<u64>,
 }
 
 impl<C: Blockchain> BlockState<C> {
@@ -39,11 +120,20 @@ impl<C: Blockchain> BlockState<C> {
         BlockState {
             entity_cache: EntityCache::with_current(Arc::new(store), lfu_cache),
             deterministic_errors: Vec::new(),
+            warnings: Vec::new(),
+            handler_warnings: Vec::new(),
+            triggered_handlers: HashSet::new(),
+            reorg_sensitive: false,
             created_data_sources: Vec::new(),
             persisted_data_sources: Vec::new(),
             handler_created_data_sources: Vec::new(),
+            raw_events: Vec::new(),
+            handler_raw_events: Vec::new(),
             processed_data_sources: Vec::new(),
             in_handler: false,
+            log_addresses: Default::default(),
+            chain_head_ptr: None,
+            chain_head_timestamp: None,
         }
     }
 
@@ -53,18 +143,37 @@ impl<C: Blockchain> BlockState<C> {
         let BlockState {
             entity_cache,
             deterministic_errors,
+            warnings,
+            handler_warnings,
+            triggered_handlers,
+            reorg_sensitive,
             created_data_sources,
             persisted_data_sources,
             handler_created_data_sources,
+            raw_events,
+            handler_raw_events,
             processed_data_sources,
             in_handler,
+            log_addresses: _,
+            chain_head_ptr: _,
+            chain_head_timestamp: _,
         } = self;
 
         match in_handler {
-            true => handler_created_data_sources.extend(other.created_data_sources),
-            false => created_data_sources.extend(other.created_data_sources),
+            true => {
+                handler_created_data_sources.extend(other.created_data_sources);
+                handler_raw_events.extend(other.raw_events);
+                handler_warnings.extend(other.warnings);
+            }
+            false => {
+                created_data_sources.extend(other.created_data_sources);
+                raw_events.extend(other.raw_events);
+                warnings.extend(other.warnings);
+            }
         }
         deterministic_errors.extend(other.deterministic_errors);
+        triggered_handlers.extend(other.triggered_handlers);
+        *reorg_sensitive |= other.reorg_sensitive;
         entity_cache.extend(other.entity_cache);
         processed_data_sources.extend(other.processed_data_sources);
         persisted_data_sources.extend(other.persisted_data_sources);
@@ -105,6 +214,8 @@ impl<C: Blockchain> BlockState<C> {
         self.in_handler = false;
         self.created_data_sources
             .append(&mut self.handler_created_data_sources);
+        self.raw_events.append(&mut self.handler_raw_events);
+        self.warnings.append(&mut self.handler_warnings);
         self.entity_cache.exit_handler()
     }
 
@@ -112,6 +223,8 @@ impl<C: Blockchain> BlockState<C> {
         assert!(self.in_handler);
         self.in_handler = false;
         self.handler_created_data_sources.clear();
+        self.handler_raw_events.clear();
+        self.handler_warnings.clear();
         self.entity_cache.exit_handler_and_discard_changes();
         self.deterministic_errors.push(e);
     }
@@ -121,7 +234,92 @@ impl<C: Blockchain> BlockState<C> {
         self.handler_created_data_sources.push(ds);
     }
 
+    /// Forget the data sources that the current handler created so far
+    pub fn reset_handler_created_data_sources(&mut self) {
+        assert!(self.in_handler);
+        self.handler_created_data_sources.clear();
+    }
+
+    pub fn record_triggered_handler(&mut self, data_source: &str, handler: &str) {
+        self.triggered_handlers
+            .insert((data_source.to_string(), handler.to_string()));
+    }
+
+    /// Return `true` if the handler with the given name of the data source
+    /// or template `data_source` has already run successfully for this
+    /// block
+    pub fn has_triggered_handler(&self, data_source: &str, handler: &str) -> bool {
+        self.triggered_handlers
+            .contains(&(data_source.to_string(), handler.to_string()))
+    }
+
+    pub fn mark_reorg_sensitive(&mut self) {
+        self.reorg_sensitive = true;
+    }
+
+    pub fn is_reorg_sensitive(&self) -> bool {
+        self.reorg_sensitive
+    }
+
+    pub fn set_log_addresses(&mut self, log_addresses: Arc<LogAddresses>) {
+        self.log_addresses = log_addresses;
+    }
+
+    pub fn log_addresses(&self) -> &Arc<LogAddresses> {
+        &self.log_addresses
+    }
+
+    pub fn set_chain_head(
+        &mut self,
+        chain_head_ptr: Option<BlockPtr>,
+        chain_head_timestamp: Option<u64>,
+    ) {
+        self.chain_head_ptr = chain_head_ptr;
+        self.chain_head_timestamp = chain_head_timestamp;
+    }
+
+    pub fn chain_head_ptr(&self) -> Option<&BlockPtr> {
+        self.chain_head_ptr.as_ref()
+    }
+
+    pub fn chain_head_timestamp(&self) -> Option<u64> {
+        self.chain_head_timestamp
+    }
+
+    pub fn push_warning(&mut self, warning: String) {
+        assert!(self.in_handler);
+        self.handler_warnings.push(warning);
+    }
+
     pub fn persist_data_source(&mut self, ds: StoredDynamicDataSource) {
         self.persisted_data_sources.push(ds)
     }
+
+    pub fn push_raw_event(&mut self, event: RawEvent) {
+        assert!(self.in_handler);
+        self.handler_raw_events.push(event);
+    }
+
+    /// Turn the raw events appended in `block` into entities in the entity
+    /// cache. Their sequence is the order in which they were appended, which
+    /// is deterministic since handlers run in a deterministic order
+    pub fn store_raw_events(&mut self, block: BlockNumber) -> Result<(), anyhow::Error> {
+        assert!(!self.in_handler);
+        for (sequence, event) in std::mem::take(&mut self.raw_events).into_iter().enumerate() {
+            let sequence = i32::try_from(sequence)?;
+            let key = EntityKey::data(RAW_EVENT_OBJECT.as_str(), format!("{}-{}", block, sequence));
+            let data = vec![
+                (
+                    crate::data::store::ID.clone(),
+                    Value::from(key.entity_id.to_string()),
+                ),
+                (RAW_EVENT_TAG.clone(), Value::from(event.tag)),
+                (RAW_EVENT_PAYLOAD.clone(), Value::from(event.payload)),
+                (RAW_EVENT_SEQUENCE.clone(), Value::from(sequence)),
+            ];
+            let entity = self.entity_cache.make_entity(data)?;
+            self.entity_cache.set(key, entity)?;
+        }
+        Ok(())
+    }
 }
@@ -846,10 +846,10 @@ impl Entity {
             }
         }
 
-        if key.entity_type.is_poi() {
-            // Users can't modify Poi entities, and therefore they do not
-            // need to be validated. In addition, the schema has no object
-            // type for them, and validation would therefore fail
+        if key.entity_type.is_poi() || key.entity_type.is_raw_event() {
+            // Users can't modify Poi and raw event entities, and therefore
+            // they do not need to be validated. In addition, the schema has
+            // no object type for them, and validation would therefore fail
             return Ok(());
         }
         let object_type = schema.find_object_type(&key.entity_type).with_context(|| {
@@ -13,7 +13,7 @@ use web3::types::*;
 use std::convert::{TryFrom, TryInto};
 use std::fmt::{self, Display, Formatter};
 use std::io::Write;
-use std::ops::{Add, BitAnd, BitOr, Deref, Div, Mul, Rem, Shl, Shr, Sub};
+use std::ops::{Add, BitAnd, BitOr, BitXor, Deref, Div, Mul, Rem, Shl, Shr, Sub};
 use std::str::FromStr;
 
 pub use num_bigint::Sign as BigIntSign;
@@ -563,6 +563,14 @@ impl BitAnd for BigInt {
     }
 }
 
+impl BitXor for BigInt {
+    type Output = Self;
+
+    fn bitxor(self, other: Self) -> Self {
+        BigInt::unchecked_new(self.inner().bitxor(other.inner()))
+    }
+}
+
 impl Shl<u8> for BigInt {
     type Output = Self;
 
@@ -696,6 +704,60 @@ mod test {
         }
     }
 
+    #[test]
+    fn bigint_from_str() {
+        let parse = |s: &str| BigInt::from_str(s).map(|n| n.to_string());
+
+        // Explicit signs
+        assert_eq!("5", parse("+5").unwrap());
+        assert_eq!("-5", parse("-5").unwrap());
+        assert_eq!("0", parse("-0").unwrap());
+        assert!(parse("+-5").is_err());
+        assert!(parse("-").is_err());
+        assert!(parse("").is_err());
+
+        // Whitespace is not trimmed
+        for s in [" 5", "5 ", "\t5", "5\n", "- 5"] {
+            assert!(parse(s).is_err(), "`{}` was accepted", s);
+        }
+
+        // Large magnitudes up to the digit limit
+        let large = format!("-{}", "9".repeat(1000));
+        assert_eq!(large, parse(&large).unwrap());
+        let too_large = format!("1{}", "0".repeat(131072));
+        assert!(parse(&too_large).is_err());
+    }
+
+    #[test]
+    fn bitwise_ops_use_twos_complement() {
+        let b = |n: i64| BigInt::from(n);
+        for (x, y) in [(-5, 3), (-1, 255), (12, -10), (-12, -10), (6, 3)] {
+            assert_eq!(b(x | y), b(x) | b(y));
+            assert_eq!(b(x & y), b(x) & b(y));
+            assert_eq!(b(x ^ y), b(x) ^ b(y));
+        }
+    }
+
+    #[test]
+    fn shifts_are_arithmetic() {
+        let b = |n: i64| BigInt::from(n);
+        assert_eq!(b(-10), b(-5) << 1);
+        assert_eq!(b(5120), b(5) << 10);
+        // Right shifts round towards negative infinity and preserve the sign
+        assert_eq!(b(-3), b(-5) >> 1);
+        assert_eq!(b(-16), b(-256) >> 4);
+        // Shifting out all bits of the magnitude
+        assert_eq!(b(0), b(255) >> 8);
+        assert_eq!(b(0), b(5) >> 200);
+        assert_eq!(b(-1), b(-255) >> 8);
+        assert_eq!(b(-1), b(-5) >> 200);
+        assert_eq!(
+            BigInt::from_str("-8034690221294951377709810461705813012611014968913964176506880")
+                .unwrap(),
+            b(-5) << 200
+        );
+    }
+
     fn crypto_stable_hash(value: impl StableHash) -> <SetHasher as StableHasher>::Out {
         stable_hash::<SetHasher, _>(&value)
     }
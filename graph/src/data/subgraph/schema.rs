@@ -19,10 +19,18 @@ use crate::util::stable_hash_glue::impl_stable_hash;
 use crate::{blockchain::Blockchain, components::store::EntityType};
 
 pub const POI_TABLE: &str = "poi2$";
+pub const RAW_EVENT_TABLE: &str = "raw_event$";
 lazy_static! {
     pub static ref POI_OBJECT: EntityType = EntityType::new("Poi$".to_string());
     /// The name of the digest attribute of POI entities
     pub static ref POI_DIGEST: Word = Word::from("digest");
+    /// The records that handlers append with `store.appendRaw`
+    pub static ref RAW_EVENT_OBJECT: EntityType = EntityType::new("RawEvent$".to_string());
+    /// The attributes of raw event entities: the type tag and JSON payload
+    /// passed by the handler, and the position of the event in its block
+    pub static ref RAW_EVENT_TAG: Word = Word::from("tag");
+    pub static ref RAW_EVENT_PAYLOAD: Word = Word::from("payload");
+    pub static ref RAW_EVENT_SEQUENCE: Word = Word::from("sequence");
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize)]
@@ -103,6 +103,9 @@ pub struct Info {
     pub health: SubgraphHealth,
     pub fatal_error: Option<SubgraphError>,
     pub non_fatal_errors: Vec<SubgraphError>,
+    /// The warnings that handlers emitted with `runtime.warn` for the most
+    /// recent block for which they emitted any
+    pub warnings: Vec<String>,
 
     /// Indexing status on different chains involved in the subgraph's data sources.
     pub chains: Vec<ChainInfo>,
@@ -126,6 +129,7 @@ impl IntoValue for Info {
             health,
             node,
             non_fatal_errors,
+            warnings,
             synced,
             history_blocks,
         } = self;
@@ -166,6 +170,7 @@ impl IntoValue for Info {
             health: r::Value::from(health),
             fatalError: fatal_error_val,
             nonFatalErrors: non_fatal_errors,
+            warnings: warnings,
             chains: chains.into_iter().map(|chain| chain.into_value()).collect::<Vec<_>>(),
             entityCount: format!("{}", entity_count),
             node: node,
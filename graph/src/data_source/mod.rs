@@ -100,6 +100,13 @@ impl<C: Blockchain> DataSource<C> {
         }
     }
 
+    pub fn creator_address(&self) -> Option<Vec<u8>> {
+        match self {
+            Self::Onchain(ds) => ds.creator_address().map(ToOwned::to_owned),
+            Self::Offchain(_) => None,
+        }
+    }
+
     pub fn name(&self) -> &str {
         match self {
             Self::Onchain(ds) => ds.name(),
@@ -436,6 +443,13 @@ impl<C: Blockchain> MappingTrigger<C> {
             Self::Offchain(_) => None, // TODO: Add error context for offchain triggers
         }
     }
+
+    pub fn as_onchain(&self) -> Option<&C::MappingTrigger> {
+        match self {
+            Self::Onchain(trigger) => Some(trigger),
+            Self::Offchain(_) => None,
+        }
+    }
 }
 
 macro_rules! clone_data_source {
@@ -163,6 +163,7 @@ impl DataSource {
             creation_block: self.creation_block,
             done_at,
             causality_region: self.causality_region,
+            creator_address: None,
         }
     }
 
@@ -177,6 +178,7 @@ impl DataSource {
             creation_block,
             done_at,
             causality_region,
+            creator_address: _,
         } = stored;
 
         let param = param.context("no param on stored data source")?;
@@ -1,4 +1,5 @@
 use std::fmt;
+use std::path::PathBuf;
 
 use super::*;
 
@@ -16,7 +17,7 @@ pub struct EnvVarsMapping {
     /// kilobytes). The default value is 10 megabytes.
     pub entity_cache_size: usize,
     /// Set by the environment variable `GRAPH_MAX_API_VERSION`. The default
-    /// value is `0.0.7`.
+    /// value is `0.0.8`.
     pub max_api_version: Version,
     /// Set by the environment variable `GRAPH_MAPPING_HANDLER_TIMEOUT`
     /// (expressed in seconds). No default is provided.
@@ -57,6 +58,36 @@ pub struct EnvVarsMapping {
     /// Set by the flag `GRAPH_ALLOW_NON_DETERMINISTIC_IPFS`. Off by
     /// default.
     pub allow_non_deterministic_ipfs: bool,
+
+    /// Links host exports that are only meant for debugging and internal
+    /// use, such as `dataSource.resetCreated`, and host exports whose
+    /// results differ between indexers, such as `runtime.headTimestamp`.
+    ///
+    /// Set by the flag `GRAPH_ALLOW_DEBUG_HOST_EXPORTS`. Off by default.
+    pub allow_debug_host_exports: bool,
+
+    /// The names of the metrics that mappings may increment with
+    /// `runtime.metricInc`; increments of any other metric are ignored.
+    ///
+    /// Set by the environment variable `GRAPH_MAPPING_METRICS_ALLOWLIST` as a
+    /// comma-separated list. Empty by default.
+    pub metrics_allowlist: Vec<String>,
+
+    /// How many blocks a block must be behind the chain head for
+    /// `runtime.isBackfilling` to consider the subgraph to be backfilling.
+    ///
+    /// Set by the environment variable `GRAPH_MAPPING_BACKFILL_THRESHOLD`.
+    /// The default value is 1000 blocks.
+    pub backfill_threshold: BlockNumber,
+
+    /// The directory in which compiled WASM modules are cached so that they
+    /// don't need to be recompiled when a subgraph is restarted. Cached
+    /// modules are executed without validation, so the directory must only
+    /// be writable by graph-node.
+    ///
+    /// Set by the environment variable `GRAPH_WASM_MODULE_CACHE_DIR`. No
+    /// default is provided, and modules are not cached if it is not set.
+    pub module_cache_dir: Option<PathBuf>,
 }
 
 // This does not print any values avoid accidentally leaking any sensitive env vars
@@ -83,6 +114,16 @@ impl From<InnerMappingHandlers> for EnvVarsMapping {
             max_ipfs_file_bytes: x.max_ipfs_file_bytes.0,
             ipfs_request_limit: x.ipfs_request_limit,
             allow_non_deterministic_ipfs: x.allow_non_deterministic_ipfs.0,
+            allow_debug_host_exports: x.allow_debug_host_exports.0,
+            metrics_allowlist: x
+                .metrics_allowlist
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect(),
+            backfill_threshold: x.backfill_threshold,
+            module_cache_dir: x.module_cache_dir,
         }
     }
 }
@@ -93,7 +134,7 @@ pub struct InnerMappingHandlers {
     entity_cache_dead_weight: EnvVarBoolean,
     #[envconfig(from = "GRAPH_ENTITY_CACHE_SIZE", default = "10000")]
     entity_cache_size_in_kb: usize,
-    #[envconfig(from = "GRAPH_MAX_API_VERSION", default = "0.0.7")]
+    #[envconfig(from = "GRAPH_MAX_API_VERSION", default = "0.0.8")]
     max_api_version: Version,
     #[envconfig(from = "GRAPH_MAPPING_HANDLER_TIMEOUT")]
     mapping_handler_timeout_in_secs: Option<u64>,
@@ -115,4 +156,12 @@ pub struct InnerMappingHandlers {
     ipfs_request_limit: u16,
     #[envconfig(from = "GRAPH_ALLOW_NON_DETERMINISTIC_IPFS", default = "false")]
     allow_non_deterministic_ipfs: EnvVarBoolean,
+    #[envconfig(from = "GRAPH_ALLOW_DEBUG_HOST_EXPORTS", default = "false")]
+    allow_debug_host_exports: EnvVarBoolean,
+    #[envconfig(from = "GRAPH_MAPPING_METRICS_ALLOWLIST", default = "")]
+    metrics_allowlist: String,
+    #[envconfig(from = "GRAPH_MAPPING_BACKFILL_THRESHOLD", default = "1000")]
+    backfill_threshold: BlockNumber,
+    #[envconfig(from = "GRAPH_WASM_MODULE_CACHE_DIR")]
+    module_cache_dir: Option<PathBuf>,
 }
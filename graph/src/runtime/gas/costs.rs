@@ -55,6 +55,16 @@ pub const CREATE_DATA_SOURCE: Gas = Gas(CONST_MAX_GAS_PER_HANDLER / 100_000);
 
 pub const ENS_NAME_BY_HASH: Gas = Gas(DEFAULT_BASE_COST);
 
+// Recovering a public key from a signature takes around 50µs.
+pub const ECRECOVER: Gas = Gas(GAS_PER_SECOND / 20_000);
+
+// Block headers are rarely larger than this many bytes.
+pub const BLOCK_HEADER_SIZE: usize = 1024;
+
+// Encoding and hashing a block header of up to `BLOCK_HEADER_SIZE` bytes.
+pub const BLOCK_HEADER_HASH: Gas =
+    Gas(DEFAULT_BASE_COST + BLOCK_HEADER_SIZE as u64 * DEFAULT_GAS_PER_BYTE);
+
 pub const LOG_OP: GasOp = GasOp {
     // Allow up to 100,000 logs
     base_cost: CONST_MAX_GAS_PER_HANDLER / 100_000,
@@ -261,10 +261,23 @@ pub enum IndexForAscTypeId {
     ArrayH256 = 1002,
     ArrayLog = 1003,
     ArrayTypedMapStringStoreValue = 1004,
+    SupportsInterfaceCall = 1005,
+    TypedMapEntryStringEthereumValue = 1006,
+    ArrayTypedMapEntryStringEthereumValue = 1007,
+    TypedMapStringEthereumValue = 1008,
+    ArrayBigInt = 1009,
+    ContractCallAtBlock = 1010,
+    ResultArrayEthereumValueBool = 1011,
+    WrappedArrayEthereumValue = 1012,
+    ArrayArrayEthereumValue = 1013,
+    TypedMapEntryStringString = 1014,
+    ArrayTypedMapEntryStringString = 1015,
+    TypedMapStringString = 1016,
+    ArraySmartContractCall = 1017,
     // Continue to add more Ethereum type IDs here.
     // e.g.:
-    // NextEthereumType = 1004,
-    // AnotherEthereumType = 1005,
+    // NextEthereumType = 1018,
+    // AnotherEthereumType = 1019,
     // ...
     // LastEthereumType = 1499,
 
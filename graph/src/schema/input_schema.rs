@@ -10,7 +10,9 @@ use crate::components::store::{EntityKey, EntityType, LoadRelatedRequest};
 use crate::data::graphql::ext::DirectiveFinder;
 use crate::data::graphql::{DirectiveExt, DocumentExt, ObjectTypeExt, TypeExt, ValueExt};
 use crate::data::store::{self, scalar, IntoEntityIterator, TryIntoEntityIterator};
-use crate::data::subgraph::schema::POI_DIGEST;
+use crate::data::subgraph::schema::{
+    POI_DIGEST, RAW_EVENT_PAYLOAD, RAW_EVENT_SEQUENCE, RAW_EVENT_TAG,
+};
 use crate::prelude::q::Value;
 use crate::prelude::{s, DeploymentHash};
 use crate::schema::api_schema;
@@ -221,7 +223,8 @@ impl InputSchema {
     }
 
     pub fn is_immutable(&self, entity_type: &EntityType) -> bool {
-        self.inner.immutable_types.contains(entity_type)
+        // Raw events are append-only
+        entity_type.is_raw_event() || self.inner.immutable_types.contains(entity_type)
     }
 
     pub fn get_named_type(&self, name: &str) -> Option<&s::TypeDefinition> {
@@ -328,6 +331,11 @@ impl InputSchema {
 fn atom_pool(document: &s::Document) -> AtomPool {
     let mut pool = AtomPool::new();
     pool.intern(POI_DIGEST.as_str()); // Attribute of PoI object
+
+    // Attributes of raw event objects
+    pool.intern(RAW_EVENT_TAG.as_str());
+    pool.intern(RAW_EVENT_PAYLOAD.as_str());
+    pool.intern(RAW_EVENT_SEQUENCE.as_str());
     for definition in &document.definitions {
         match definition {
             s::Definition::TypeDefinition(typedef) => match typedef {
@@ -151,6 +151,7 @@ pub async fn run(
         )),
         Arc::new(EthereumRuntimeAdapter {
             call_cache: chain_store.cheap_clone(),
+            chain_store: chain_store.cheap_clone(),
             eth_adapters: Arc::new(eth_adapters2),
             chain_identifier: Arc::new(chain_store.chain_identifier.clone()),
         }),
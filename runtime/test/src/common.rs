@@ -35,14 +35,14 @@ fn mock_host_exports(
             manifest_idx: 0,
             network: Some(String::from("mainnet")),
             source: TemplateSource {
-                abi: String::from("foo"),
+                abi: String::from("mock_abi"),
             },
             mapping: Mapping {
                 kind: String::from("ethereum/events"),
                 api_version,
                 language: String::from("wasm/assemblyscript"),
                 entities: vec![],
-                abis: vec![],
+                abis: vec![Arc::new(mock_abi())],
                 event_handlers: vec![],
                 call_handlers: vec![],
                 block_handlers: vec![],
@@ -59,6 +59,7 @@ fn mock_host_exports(
     HostExports::new(
         subgraph_id,
         &data_source::DataSource::Onchain(data_source),
+        0,
         network,
         Arc::new(templates),
         Arc::new(graph_core::LinkResolver::new(
@@ -102,6 +103,11 @@ pub fn mock_context(
             hash: Default::default(),
             number: 0,
         },
+        chain_head_ptr: None,
+        chain_head_timestamp: None,
+        trigger: None,
+        last_return_data: Default::default(),
+        block_log_addresses: Default::default(),
         host_exports: Arc::new(mock_host_exports(
             deployment.hash.clone(),
             data_source,
@@ -134,6 +140,7 @@ pub fn mock_data_source(path: &str, api_version: Version) -> DataSource {
         manifest_idx: 0,
         network: Some(String::from("mainnet")),
         address: Some(Address::from_str("0123123123012312312301231231230123123123").unwrap()),
+        creator_address: None,
         start_block: 0,
         mapping: Mapping {
             kind: String::from("ethereum/events"),
@@ -1,6 +1,9 @@
+use graph::blockchain::DataSource as _;
+use graph::blockchain::HostFn;
 use graph::data::store::scalar;
 use graph::data::subgraph::*;
 use graph::data::value::Word;
+use graph::data_source::TriggerWithHandler;
 use graph::prelude::web3::types::U256;
 use graph::runtime::gas::GasCounter;
 use graph::runtime::{AscIndexId, AscType, HostExportError};
@@ -8,10 +11,14 @@ use graph::runtime::{AscPtr, ToAscObj};
 use graph::schema::InputSchema;
 use graph::{components::store::*, ipfs_client::IpfsClient};
 use graph::{entity, prelude::*};
+use graph_chain_ethereum::trigger::MappingTrigger;
 use graph_chain_ethereum::{Chain, DataSource};
-use graph_runtime_wasm::asc_abi::class::{Array, AscBigInt, AscEntity, AscString, Uint8Array};
+use graph_runtime_wasm::asc_abi::class::{
+    Array, AscBigInt, AscEntity, AscEnum, AscEventParam, AscString, EthereumValueKind,
+    StoreValueKind, Uint8Array,
+};
 use graph_runtime_wasm::{
-    host_exports, ExperimentalFeatures, MappingContext, ValidModule, WasmInstance,
+    host_exports, ExperimentalFeatures, MappingContext, ModuleCache, ValidModule, WasmInstance,
 };
 
 use semver::Version;
@@ -19,7 +26,7 @@ use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
 use test_store::{LOGGER, STORE};
-use web3::types::H160;
+use web3::types::{Block, Bytes as Web3Bytes, Log, Transaction, H160, H256, U64};
 
 use crate::common::{mock_context, mock_data_source};
 
@@ -63,6 +70,54 @@ async fn test_valid_module_and_store_with_timeout(
     WasmInstance<Chain>,
     Arc<impl SubgraphStore>,
     DeploymentLocator,
+) {
+    test_valid_module_and_store_with_host_fns(
+        subgraph_id,
+        data_source,
+        api_version,
+        timeout,
+        Vec::new(),
+    )
+    .await
+}
+
+async fn test_valid_module_and_store_with_host_fns(
+    subgraph_id: &str,
+    data_source: DataSource,
+    api_version: Version,
+    timeout: Option<Duration>,
+    host_fns: Vec<HostFn>,
+) -> (
+    WasmInstance<Chain>,
+    Arc<impl SubgraphStore>,
+    DeploymentLocator,
+) {
+    let experimental_features = ExperimentalFeatures {
+        allow_non_deterministic_ipfs: true,
+        allow_debug_host_exports: true,
+    };
+    test_valid_module_and_store_with_features(
+        subgraph_id,
+        data_source,
+        api_version,
+        timeout,
+        host_fns,
+        experimental_features,
+    )
+    .await
+}
+
+async fn test_valid_module_and_store_with_features(
+    subgraph_id: &str,
+    data_source: DataSource,
+    api_version: Version,
+    timeout: Option<Duration>,
+    host_fns: Vec<HostFn>,
+    experimental_features: ExperimentalFeatures,
+) -> (
+    WasmInstance<Chain>,
+    Arc<impl SubgraphStore>,
+    DeploymentLocator,
 ) {
     let logger = Logger::root(slog::Discard, o!());
     let subgraph_id_with_api_version =
@@ -98,18 +153,19 @@ async fn test_valid_module_and_store_with_timeout(
         stopwatch_metrics,
     ));
 
-    let experimental_features = ExperimentalFeatures {
-        allow_non_deterministic_ipfs: true,
-    };
+    let valid_module =
+        Arc::new(ValidModule::new(&logger, data_source.mapping.runtime.as_ref()).unwrap());
+    let mut ctx = mock_context(
+        deployment.clone(),
+        data_source,
+        store.subgraph_store(),
+        api_version,
+    );
+    ctx.host_fns = Arc::new(host_fns);
 
     let module = WasmInstance::from_valid_module_with_ctx(
-        Arc::new(ValidModule::new(&logger, data_source.mapping.runtime.as_ref()).unwrap()),
-        mock_context(
-            deployment.clone(),
-            data_source,
-            store.subgraph_store(),
-            api_version,
-        ),
+        valid_module,
+        ctx,
         host_metrics,
         timeout,
         experimental_features,
@@ -367,6 +423,32 @@ async fn json_parsing_v0_0_5() {
     test_json_parsing(API_VERSION_0_0_5, 5153540).await;
 }
 
+#[tokio::test]
+async fn json_reduce_calls_the_mapping_callback() {
+    let mut module = test_module(
+        "jsonReduce",
+        mock_data_source(
+            &wasm_file_path("json_reduce.wasm", API_VERSION_0_0_4),
+            API_VERSION_0_0_4,
+        ),
+        API_VERSION_0_0_4,
+    )
+    .await;
+
+    // `countTrue` adds the `true` elements to the count it is given
+    let array = serde_json::json!([true, false, "true", true, 1, null, true]);
+    let count: AscPtr<AscEnum<StoreValueKind>> =
+        module.invoke_export2("reduce", &array, &Value::Int(10));
+    let count: Value = module.asc_get(count).unwrap();
+    assert_eq!(Value::Int(13), count);
+
+    let empty = serde_json::json!([]);
+    let count: AscPtr<AscEnum<StoreValueKind>> =
+        module.invoke_export2("reduce", &empty, &Value::Int(10));
+    let count: Value = module.asc_get(count).unwrap();
+    assert_eq!(Value::Int(10), count);
+}
+
 async fn test_ipfs_cat(api_version: Version) {
     // Ipfs host functions use `block_on` which must be called from a sync context,
     // so we replicate what we do `spawn_module`.
@@ -453,6 +535,18 @@ async fn run_ipfs_map(
     json_string: String,
     api_version: Version,
 ) -> Result<Vec<EntityModification>, anyhow::Error> {
+    run_ipfs_map_with_gas(ipfs, subgraph_id, json_string, api_version)
+        .await
+        .map(|(mods, _)| mods)
+}
+
+/// Like `run_ipfs_map`, but also returns the gas used by the handler calling `ipfs.map`.
+async fn run_ipfs_map_with_gas(
+    ipfs: IpfsClient,
+    subgraph_id: &'static str,
+    json_string: String,
+    api_version: Version,
+) -> Result<(Vec<EntityModification>, u64), anyhow::Error> {
     let hash = if json_string == BAD_IPFS_HASH {
         "Qm".to_string()
     } else {
@@ -480,6 +574,7 @@ async fn run_ipfs_map(
         // Invoke the callback
         let func = module.get_func("ipfsMap").typed().unwrap().clone();
         func.call((value.wasm_ptr(), user_data.wasm_ptr()))?;
+        let gas_used = module.gas_used();
         let mut mods = module
             .take_ctx()
             .ctx
@@ -490,7 +585,7 @@ async fn run_ipfs_map(
 
         // Bring the modifications into a predictable order (by entity_id)
         mods.sort_by(|a, b| a.key().entity_id.partial_cmp(&b.key().entity_id).unwrap());
-        Ok(mods)
+        Ok((mods, gas_used))
     })
     .join()
     .unwrap()
@@ -590,6 +685,30 @@ async fn ipfs_map_v0_0_5() {
     test_ipfs_map(API_VERSION_0_0_5, "'id' should not be null").await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn ipfs_map_charges_the_gas_of_its_callbacks() {
+    let ipfs = IpfsClient::localhost();
+
+    // Every object makes the callback store an entity of the same size, so each one should
+    // cost the same
+    let gas_for = |n: usize| {
+        let json = (0..n)
+            .map(|i| make_thing(&format!("thing{}", i), "value").0)
+            .collect::<Vec<_>>()
+            .join("\n");
+        run_ipfs_map_with_gas(ipfs.clone(), "ipfsMapGas", json, API_VERSION_0_0_5)
+    };
+    let (mods, gas_1) = gas_for(1).await.expect("call failed");
+    assert_eq!(1, mods.len());
+    let (_, gas_2) = gas_for(2).await.expect("call failed");
+    let (mods, gas_4) = gas_for(4).await.expect("call failed");
+    assert_eq!(4, mods.len());
+
+    let gas_per_object = gas_2 - gas_1;
+    assert!(gas_per_object > 0);
+    assert_eq!(gas_4, gas_2 + 2 * gas_per_object);
+}
+
 async fn test_ipfs_fail(api_version: Version) {
     let runtime = tokio::runtime::Handle::current();
 
@@ -715,6 +834,373 @@ async fn test_big_int_size_limit() {
         .contains("BigInt is too big, total bits 435416 (max 435412)"));
 }
 
+#[tokio::test]
+async fn runtime_warn() {
+    let mut module = test_module(
+        "RuntimeWarn",
+        mock_data_source(
+            &wasm_file_path("boolean.wasm", API_VERSION_0_0_5),
+            API_VERSION_0_0_5,
+        ),
+        API_VERSION_0_0_5,
+    )
+    .await;
+    let gas = GasCounter::default();
+
+    module.instance_ctx_mut().ctx.state.enter_handler();
+    for message in ["price feed is stale", "negative reserve"] {
+        let message_ptr: AscPtr<AscString> = module.asc_new(message).unwrap();
+        module
+            .instance_ctx_mut()
+            .emit_warning(&gas, message_ptr)
+            .unwrap();
+    }
+    module.instance_ctx_mut().ctx.state.exit_handler();
+
+    let state = module.take_ctx().ctx.state;
+    assert_eq!(
+        state.warnings,
+        vec![
+            "price feed is stale".to_string(),
+            "negative reserve".to_string()
+        ]
+    );
+    assert!(!state.has_errors());
+}
+
+fn log_trigger(handler: &str) -> TriggerWithHandler<graph::data_source::MappingTrigger<Chain>> {
+    let block = Block {
+        hash: Some(H256::from_low_u64_be(1)),
+        number: Some(U64::from(1)),
+        ..Default::default()
+    };
+    let transaction = Transaction {
+        from: Some(H160::from_low_u64_be(1)),
+        transaction_index: Some(U64::from(0)),
+        ..Default::default()
+    };
+    let log = Log {
+        address: H160::from_low_u64_be(2),
+        topics: vec![],
+        data: Web3Bytes(vec![]),
+        block_hash: block.hash,
+        block_number: block.number,
+        transaction_hash: Some(transaction.hash),
+        transaction_index: Some(U64::from(0)),
+        log_index: Some(U256::zero()),
+        transaction_log_index: Some(U256::zero()),
+        log_type: None,
+        removed: Some(false),
+    };
+    let block_ptr = BlockPtr::from(&block);
+
+    TriggerWithHandler::new(
+        graph::data_source::MappingTrigger::Onchain(MappingTrigger::Log {
+            block: Arc::new(block),
+            transaction: Arc::new(transaction),
+            log: Arc::new(log),
+            params: vec![],
+            receipt: None,
+            signature_ordinal: None,
+        }),
+        handler.to_owned(),
+        block_ptr,
+    )
+}
+
+#[tokio::test]
+async fn runtime_warn_is_discarded_with_a_failed_handler() {
+    let data_source = || {
+        mock_data_source(
+            &wasm_file_path("runtime_warn.wasm", API_VERSION_0_0_4),
+            API_VERSION_0_0_4,
+        )
+    };
+
+    // Both handlers warn, but `handleLogAndFail` then traps
+    let module = test_module("RuntimeWarnKept", data_source(), API_VERSION_0_0_4).await;
+    let (state, _) = module.handle_trigger(log_trigger("handleLog")).unwrap();
+    assert_eq!(state.warnings, vec!["stale".to_string()]);
+    assert!(!state.has_errors());
+
+    let module = test_module("RuntimeWarnDiscarded", data_source(), API_VERSION_0_0_4).await;
+    let (state, _) = module
+        .handle_trigger(log_trigger("handleLogAndFail"))
+        .unwrap();
+    assert!(state.warnings.is_empty());
+    assert!(state.has_errors());
+}
+
+#[tokio::test]
+async fn block_confirmations() {
+    let mut module = test_module(
+        "BlockConfirmations",
+        mock_data_source(
+            &wasm_file_path("block_confirmations.wasm", API_VERSION_0_0_4),
+            API_VERSION_0_0_4,
+        ),
+        API_VERSION_0_0_4,
+    )
+    .await;
+    module.instance_ctx_mut().ctx.block_ptr = BlockPtr::from((H256::zero(), 100u64));
+
+    let mut confirmations = |head: Option<u64>| -> BigInt {
+        module.instance_ctx_mut().ctx.chain_head_ptr =
+            head.map(|number| BlockPtr::from((H256::from_low_u64_be(number), number)));
+        let ptr: AscPtr<AscBigInt> = module.invoke_export0("confirmations");
+        module.asc_get(ptr).unwrap()
+    };
+
+    assert_eq!(BigInt::from(10), confirmations(Some(110)));
+    // Without a known chain head, there are no confirmations
+    assert_eq!(BigInt::from(0), confirmations(None));
+}
+
+#[tokio::test]
+async fn chain_head_timestamp() {
+    let mut module = test_module(
+        "ChainHeadTimestamp",
+        mock_data_source(
+            &wasm_file_path("head_timestamp.wasm", API_VERSION_0_0_4),
+            API_VERSION_0_0_4,
+        ),
+        API_VERSION_0_0_4,
+    )
+    .await;
+
+    let mut head_timestamp = |timestamp: Option<u64>| -> BigInt {
+        module.instance_ctx_mut().ctx.chain_head_timestamp = timestamp;
+        let ptr: AscPtr<AscBigInt> = module.invoke_export0("headTimestamp");
+        module.asc_get(ptr).unwrap()
+    };
+
+    assert_eq!(
+        BigInt::from(1_700_000_000u64),
+        head_timestamp(Some(1_700_000_000))
+    );
+    // Without a known chain head, the timestamp is zero
+    assert_eq!(BigInt::from(0), head_timestamp(None));
+}
+
+#[tokio::test]
+async fn call_input() {
+    let mut module = test_module(
+        "CallInput",
+        mock_data_source(
+            &wasm_file_path("boolean.wasm", API_VERSION_0_0_5),
+            API_VERSION_0_0_5,
+        ),
+        API_VERSION_0_0_5,
+    )
+    .await;
+    let gas = GasCounter::default();
+
+    let to = H160::from_low_u64_be(7);
+    let inputs = vec![
+        ethabi::LogParam {
+            name: "to".to_string(),
+            value: ethabi::Token::Address(to),
+        },
+        ethabi::LogParam {
+            name: "value".to_string(),
+            value: ethabi::Token::Uint(U256::from(42)),
+        },
+    ];
+    let inputs_ptr: AscPtr<Array<AscPtr<AscEventParam>>> =
+        module.asc_new(inputs.as_slice()).unwrap();
+
+    let mut call_input = |name: &str| -> Option<ethabi::Token> {
+        let name_ptr: AscPtr<AscString> = module.asc_new(name).unwrap();
+        let value_ptr: AscPtr<AscEnum<EthereumValueKind>> = module
+            .instance_ctx_mut()
+            .call_input(&gas, inputs_ptr, name_ptr)
+            .unwrap();
+        if value_ptr.is_null() {
+            None
+        } else {
+            Some(module.asc_get(value_ptr).unwrap())
+        }
+    };
+
+    assert_eq!(
+        Some(ethabi::Token::Uint(U256::from(42))),
+        call_input("value")
+    );
+    assert_eq!(Some(ethabi::Token::Address(to)), call_input("to"));
+    assert_eq!(None, call_input("from"));
+}
+
+#[tokio::test]
+async fn has_capability_follows_linked_host_exports() {
+    async fn capabilities(
+        subgraph_id: &str,
+        experimental_features: ExperimentalFeatures,
+        names: &[&str],
+    ) -> Vec<bool> {
+        let call_batch = HostFn {
+            name: "ethereum.callBatch",
+            func: Arc::new(|_, _| Ok(0)),
+        };
+        let (mut module, _, _) = test_valid_module_and_store_with_features(
+            subgraph_id,
+            mock_data_source(
+                &wasm_file_path("boolean.wasm", API_VERSION_0_0_5),
+                API_VERSION_0_0_5,
+            ),
+            API_VERSION_0_0_5,
+            None,
+            vec![call_batch],
+            experimental_features,
+        )
+        .await;
+        let gas = GasCounter::default();
+
+        names
+            .iter()
+            .map(|name| {
+                let name_ptr: AscPtr<AscString> = module.asc_new(*name).unwrap();
+                module
+                    .instance_ctx_mut()
+                    .host_has_capability(&gas, name_ptr)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    let names = [
+        "log.logKv",
+        "ipfs.catWithTimeout",
+        "ethereum.callBatch",
+        "ipfs.getBlock",
+        "store.cacheBytes",
+        "runtime.headTimestamp",
+        "block.confirmations",
+        "runtime.isBackfilling",
+        "runtime.totalEntityWrites",
+        "http.get",
+    ];
+
+    let features = ExperimentalFeatures {
+        allow_non_deterministic_ipfs: true,
+        allow_debug_host_exports: false,
+    };
+    assert_eq!(
+        vec![true, true, true, true, false, false, false, false, false, false],
+        capabilities("hasCapabilityIpfs", features, &names).await
+    );
+
+    let features = ExperimentalFeatures {
+        allow_non_deterministic_ipfs: false,
+        allow_debug_host_exports: true,
+    };
+    assert_eq!(
+        vec![true, true, true, false, true, true, true, true, true, false],
+        capabilities("hasCapabilityDebug", features, &names).await
+    );
+}
+
+#[tokio::test]
+async fn chain_reads_near_the_head_are_reorg_sensitive() {
+    async fn is_reorg_sensitive(subgraph_id: &str, head: BlockNumber) -> bool {
+        let ethereum_call = HostFn {
+            name: "ethereum.call",
+            func: Arc::new(|_, _| Ok(0)),
+        };
+        let (mut module, _, _) = test_valid_module_and_store_with_host_fns(
+            subgraph_id,
+            mock_data_source(
+                &wasm_file_path("chain_read.wasm", API_VERSION_0_0_4),
+                API_VERSION_0_0_4,
+            ),
+            API_VERSION_0_0_4,
+            None,
+            vec![ethereum_call],
+        )
+        .await;
+        let head = BlockPtr::from((H256::from_low_u64_be(1), head));
+        module.instance_ctx_mut().ctx.block_ptr = BlockPtr::from((H256::zero(), 1_000u64));
+        module.instance_ctx_mut().ctx.chain_head_ptr = Some(head);
+
+        let (state, _) = module.handle_trigger(log_trigger("handleLog")).unwrap();
+        state.is_reorg_sensitive()
+    }
+
+    assert!(is_reorg_sensitive("ChainReadNearHead", 1_005).await);
+    let finalized_head = 1_000 + ENV_VARS.reorg_threshold + 1;
+    assert!(!is_reorg_sensitive("ChainReadFinalized", finalized_head).await);
+}
+
+#[tokio::test]
+async fn block_triggered_handler() {
+    let data_source = || {
+        mock_data_source(
+            &wasm_file_path("triggered_handler.wasm", API_VERSION_0_0_4),
+            API_VERSION_0_0_4,
+        )
+    };
+
+    let module = test_module("TriggeredHandlerFirst", data_source(), API_VERSION_0_0_4).await;
+    let (state, _) = module.handle_trigger(log_trigger("handleFirst")).unwrap();
+    assert!(!state.has_errors());
+
+    // The next trigger of the block gets a new instance but the same block state
+    let mut module = test_module("TriggeredHandlerAfter", data_source(), API_VERSION_0_0_4).await;
+    module.instance_ctx_mut().ctx.state = state;
+    let (state, _) = module
+        .handle_trigger(log_trigger("handleIfFirstTriggered"))
+        .unwrap();
+    assert!(!state.has_errors());
+
+    // A data source with another name doesn't see that `handleFirst` ran
+    let other_data_source = DataSource {
+        name: String::from("other data source"),
+        ..data_source()
+    };
+    let mut module = test_module(
+        "TriggeredHandlerOther",
+        other_data_source,
+        API_VERSION_0_0_4,
+    )
+    .await;
+    module.instance_ctx_mut().ctx.state = state;
+    let (state, _) = module
+        .handle_trigger(log_trigger("handleIfFirstTriggered"))
+        .unwrap();
+    assert!(state.has_errors());
+
+    // Without `handleFirst` the handler fails
+    let module = test_module("TriggeredHandlerAlone", data_source(), API_VERSION_0_0_4).await;
+    let (state, _) = module
+        .handle_trigger(log_trigger("handleIfFirstTriggered"))
+        .unwrap();
+    assert!(state.has_errors());
+}
+
+#[test]
+fn module_cache_skips_compilation_of_known_modules() {
+    let dir = std::env::temp_dir().join(format!("graph-module-cache-{}", std::process::id()));
+    let cache = ModuleCache::new(dir.clone());
+    let raw_module = std::fs::read(wasm_file_path("handle_log.wasm", API_VERSION_0_0_4)).unwrap();
+    let deployment = DeploymentHash::new("moduleCache").unwrap();
+
+    let compiled = ValidModule::with_cache(&LOGGER, &raw_module, &cache, &deployment).unwrap();
+    assert_eq!(0, cache.hits());
+
+    let cached = ValidModule::with_cache(&LOGGER, &raw_module, &cache, &deployment).unwrap();
+    assert_eq!(1, cache.hits());
+    assert_eq!(
+        compiled.import_name_to_modules,
+        cached.import_name_to_modules
+    );
+
+    // Entries are not shared between deployments
+    let other = DeploymentHash::new("otherModuleCache").unwrap();
+    ValidModule::with_cache(&LOGGER, &raw_module, &cache, &other).unwrap();
+    assert_eq!(1, cache.hits());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
 #[tokio::test]
 async fn big_int_to_hex_v0_0_4() {
     test_big_int_to_hex(API_VERSION_0_0_4, 53113760).await;
@@ -867,6 +1353,20 @@ async fn test_data_source_create(api_version: Version, gas_used: u64) {
     assert_eq!(result[0].params, params.clone());
     assert_eq!(result[0].template.name(), template);
 
+    // The created data source knows the address of the data source that created it, also after
+    // being restored from the store
+    let creator = H160::from_str("0123123123012312312301231231230123123123").unwrap();
+    assert_eq!(result[0].creator_address, Some(creator.as_bytes().to_vec()));
+    let child = DataSource::from_template_info(result[0].clone()).unwrap();
+    assert_eq!(child.creator_address(), Some(creator.as_bytes()));
+    let restored = DataSource::from_stored_dynamic_data_source(
+        result[0].template.as_onchain().unwrap(),
+        child.as_stored_dynamic_data_source(),
+    )
+    .unwrap();
+    assert_eq!(restored.address, child.address);
+    assert_eq!(restored.creator_address(), Some(creator.as_bytes()));
+
     // Test with a template that doesn't exist
     let template = String::from("nonexistent template");
     let params = vec![String::from("0xc000000000000000000000000000000000000000")];
@@ -905,6 +1405,57 @@ async fn run_data_source_create(
     Ok(module.take_ctx().ctx.state.drain_created_data_sources())
 }
 
+#[tokio::test]
+async fn data_source_reset_created() {
+    let api_version = API_VERSION_0_0_5;
+    let (mut module, store, deployment) = test_valid_module_and_store(
+        "DataSourceResetCreated",
+        mock_data_source(
+            &wasm_file_path("data_source_create.wasm", api_version.clone()),
+            api_version.clone(),
+        ),
+        api_version,
+    )
+    .await;
+    let gas = GasCounter::default();
+    let schema = store.input_schema(&deployment.hash).unwrap();
+
+    let template = String::from("example template");
+    let params = vec![String::from("0xc0a47dFe034B400B47bDaD5FecDa2621de6c4d95")];
+    module.instance_ctx_mut().ctx.state.enter_handler();
+    module
+        .instance_ctx_mut()
+        .ctx
+        .state
+        .entity_cache
+        .set(
+            EntityKey::data("User".to_string(), "u1"),
+            entity! { schema => id: "u1", name: "user1" },
+        )
+        .unwrap();
+    module
+        .invoke_export2_void("dataSourceCreate", &template, &params)
+        .unwrap();
+    module
+        .invoke_export2_void("dataSourceCreate", &template, &params)
+        .unwrap();
+    module
+        .instance_ctx_mut()
+        .data_source_reset_created(&gas)
+        .unwrap();
+    module.instance_ctx_mut().ctx.state.exit_handler();
+
+    let mut state = module.take_ctx().ctx.state;
+    assert!(state.drain_created_data_sources().is_empty());
+    let mods = state
+        .entity_cache
+        .as_modifications(0)
+        .unwrap()
+        .modifications;
+    assert_eq!(1, mods.len());
+    assert_eq!("u1", mods[0].key().entity_id.as_str());
+}
+
 #[tokio::test]
 async fn data_source_create_v0_0_4() {
     test_data_source_create(API_VERSION_0_0_4, 152102833).await;
@@ -948,6 +1499,58 @@ async fn ens_name_by_hash_v0_0_5() {
     test_ens_name_by_hash(API_VERSION_0_0_5).await;
 }
 
+#[tokio::test]
+async fn store_get_in_block_ignores_the_store() {
+    let api_version = API_VERSION_0_0_5;
+    let (mut module, store, deployment) = test_valid_module_and_store(
+        "storeGetInBlock",
+        mock_data_source(
+            &wasm_file_path("store.wasm", api_version.clone()),
+            api_version.clone(),
+        ),
+        api_version,
+    )
+    .await;
+    let gas = GasCounter::default();
+    let schema = store.input_schema(&deployment.hash).unwrap();
+
+    let steve = entity! { schema => id: "steve", name: "Steve" };
+    test_store::insert_entities(&deployment, vec![(EntityType::from("User"), steve)])
+        .await
+        .unwrap();
+
+    let get_in_block = |module: &mut WasmInstance<Chain>, id: &str| -> Option<String> {
+        let entity = module.asc_new::<AscString, _>("User").unwrap();
+        let id = module.asc_new::<AscString, _>(id).unwrap();
+        let ptr = module
+            .instance_ctx_mut()
+            .store_get_in_block(&gas, entity, id)
+            .unwrap();
+        if ptr.is_null() {
+            return None;
+        }
+        let data: HashMap<Word, Value> = module.asc_get(ptr).unwrap();
+        data.get("name").map(|name| name.to_string())
+    };
+
+    module.instance_ctx_mut().ctx.state.enter_handler();
+    module
+        .instance_ctx_mut()
+        .ctx
+        .state
+        .entity_cache
+        .set(
+            EntityKey::data("User".to_string(), "alex"),
+            entity! { schema => id: "alex", name: "Alex" },
+        )
+        .unwrap();
+
+    // An entity written in this block is returned, one that is only in the store is not
+    assert_eq!(Some("Alex".to_string()), get_in_block(&mut module, "alex"));
+    assert_eq!(None, get_in_block(&mut module, "steve"));
+    module.instance_ctx_mut().ctx.state.exit_handler();
+}
+
 async fn test_entity_store(api_version: Version) {
     let (mut module, store, deployment) = test_valid_module_and_store(
         "entityStore",
@@ -1114,6 +1717,39 @@ async fn allocate_global_v0_0_5() {
     test_allocate_global(API_VERSION_0_0_5).await;
 }
 
+#[tokio::test]
+async fn allocations_do_not_overlap() {
+    let mut module = test_module(
+        "AllocationsDoNotOverlap",
+        mock_data_source(
+            &wasm_file_path("boolean.wasm", API_VERSION_0_0_5),
+            API_VERSION_0_0_5,
+        ),
+        API_VERSION_0_0_5,
+    )
+    .await;
+
+    // Mix small allocations with ones that are larger than an arena so that
+    // some strings share an arena and others force a new one
+    let strings: Vec<String> = [1, 17, 300, 4_000, 12_000, 3, 25_000, 64]
+        .iter()
+        .enumerate()
+        .map(|(i, len)| {
+            let c = char::from(b'a' + i as u8);
+            c.to_string().repeat(*len)
+        })
+        .collect();
+    let ptrs: Vec<AscPtr<AscString>> = strings
+        .iter()
+        .map(|s| module.asc_new(s.as_str()).unwrap())
+        .collect();
+
+    for (s, ptr) in strings.iter().zip(ptrs) {
+        let read: String = module.asc_get(ptr).unwrap();
+        assert_eq!(s.as_bytes(), read.as_bytes());
+    }
+}
+
 async fn test_null_ptr_read(api_version: Version) {
     let module = test_module(
         "NullPtrRead",
@@ -1199,6 +1835,52 @@ async fn test_boolean() {
     }
 }
 
+#[tokio::test]
+async fn runtime_memory_pages_grows_with_allocations() {
+    let mut module = test_module_latest("runtimeMemoryPages", "boolean.wasm").await;
+    let gas = GasCounter::default();
+
+    let before = module
+        .instance_ctx_mut()
+        .runtime_memory_pages(&gas)
+        .unwrap();
+    assert!(before > 0);
+
+    // Four pages worth of bytes can't fit in the memory the module started with
+    let bytes = vec![0u8; 4 * 64 * 1024];
+    module.asc_new::<Uint8Array, _>(bytes.as_slice()).unwrap();
+
+    let after = module
+        .instance_ctx_mut()
+        .runtime_memory_pages(&gas)
+        .unwrap();
+    assert!(
+        after >= before + 4,
+        "{} pages after {} pages",
+        after,
+        before
+    );
+}
+
+#[tokio::test]
+async fn context_block_matches_the_handler_block() {
+    let mut module = test_module_latest("contextBlock", "boolean.wasm").await;
+    let gas = GasCounter::default();
+
+    // The instance manager passes the block pointer of the trigger into the
+    // mapping context
+    let trigger = log_trigger("handleLog");
+    module.instance_ctx_mut().ctx.block_ptr = trigger.block_ptr();
+
+    let number = module.instance_ctx_mut().block_number(&gas).unwrap();
+    let number: BigInt = module.asc_get(number).unwrap();
+    assert_eq!(BigInt::from(1), number);
+
+    let hash = module.instance_ctx_mut().block_hash(&gas).unwrap();
+    let hash: H256 = module.asc_get(hash).unwrap();
+    assert_eq!(H256::from_low_u64_be(1), hash);
+}
+
 #[tokio::test]
 async fn recursion_limit() {
     let module = test_module_latest("RecursionLimit", "recursion_limit.wasm").await;
@@ -1217,6 +1899,140 @@ async fn recursion_limit() {
         .contains("recursion limit reached"));
 }
 
+#[tokio::test]
+async fn crypto_sha256_and_sha3_match_nist_vectors() {
+    let mut module = test_module(
+        "cryptoNistVectors",
+        mock_data_source(
+            &wasm_file_path("crypto.wasm", API_VERSION_0_0_5),
+            API_VERSION_0_0_5,
+        ),
+        API_VERSION_0_0_5,
+    )
+    .await;
+    let vectors = [
+        (
+            "",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+        ),
+        (
+            "abc",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+        ),
+        (
+            "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            "41c0dba2a9d6240849100376a8235e2c82e1b9998a999e21db32dd97496d3376",
+        ),
+    ];
+
+    let mut hash = |message: &str, sha3: bool| {
+        let gas = GasCounter::default();
+        let input_ptr = module.asc_new(message.as_bytes()).unwrap();
+        let digest_ptr = if sha3 {
+            module.instance_ctx_mut().crypto_sha3(&gas, input_ptr)
+        } else {
+            module.instance_ctx_mut().crypto_sha256(&gas, input_ptr)
+        }
+        .unwrap();
+        let digest: Vec<u8> = module.asc_get(digest_ptr).unwrap();
+        (hex::encode(digest), gas.get().value())
+    };
+
+    let mut last_gas = (0, 0);
+    for (message, sha256, sha3) in vectors {
+        let (sha256_digest, sha256_gas) = hash(message, false);
+        let (sha3_digest, sha3_gas) = hash(message, true);
+        assert_eq!(sha256, sha256_digest);
+        assert_eq!(sha3, sha3_digest);
+
+        // Longer messages cost more gas
+        assert!(sha256_gas > last_gas.0);
+        assert!(sha3_gas > last_gas.1);
+        last_gas = (sha256_gas, sha3_gas);
+    }
+}
+
+#[tokio::test]
+async fn store_set_many_matches_individual_sets() {
+    let api_version = API_VERSION_0_0_5;
+    let module = |name: &str| {
+        test_module(
+            name,
+            mock_data_source(
+                &wasm_file_path("store.wasm", api_version.clone()),
+                api_version.clone(),
+            ),
+            api_version.clone(),
+        )
+    };
+    let mut batched = module("storeSetManyBatched").await;
+    let mut individual = module("storeSetManyIndividual").await;
+    let gas = GasCounter::default();
+
+    let entities = vec!["User".to_string(), "Thing".to_string(), "User".to_string()];
+    let ids = vec!["u1".to_string(), "t1".to_string(), "u2".to_string()];
+    let data: Vec<Vec<(Word, Value)>> = vec![
+        vec![("name".into(), Value::from("One"))],
+        vec![("value".into(), Value::from("thing"))],
+        vec![
+            ("name".into(), Value::from("Two")),
+            ("count".into(), Value::BigInt(BigInt::from(2))),
+        ],
+    ];
+
+    batched.instance_ctx_mut().ctx.state.enter_handler();
+    let entities_ptr = batched.asc_new(entities.as_slice()).unwrap();
+    let ids_ptr = batched.asc_new(ids.as_slice()).unwrap();
+    let data_ptr = batched.asc_new(data.as_slice()).unwrap();
+    batched
+        .instance_ctx_mut()
+        .store_set_many(&gas, entities_ptr, ids_ptr, data_ptr)
+        .unwrap();
+    batched.instance_ctx_mut().ctx.state.exit_handler();
+
+    individual.instance_ctx_mut().ctx.state.enter_handler();
+    for ((entity, id), data) in entities.iter().zip(&ids).zip(&data) {
+        let entity_ptr = individual.asc_new(entity.as_str()).unwrap();
+        let id_ptr = individual.asc_new(id.as_str()).unwrap();
+        let data_ptr = individual.asc_new(data).unwrap();
+        individual
+            .instance_ctx_mut()
+            .store_set(&gas, entity_ptr, id_ptr, data_ptr)
+            .unwrap();
+    }
+    individual.instance_ctx_mut().ctx.state.exit_handler();
+
+    let mods = |mut module: WasmInstance<Chain>| {
+        module
+            .take_ctx()
+            .ctx
+            .state
+            .entity_cache
+            .as_modifications(0)
+            .unwrap()
+            .modifications
+    };
+    let batched_mods = mods(batched);
+    assert_eq!(3, batched_mods.len());
+    assert_eq!(mods(individual), batched_mods);
+
+    // Arrays of different lengths trap without setting anything
+    let mut module = module("storeSetManyMismatch").await;
+    module.instance_ctx_mut().ctx.state.enter_handler();
+    let entities_ptr = module.asc_new(entities.as_slice()).unwrap();
+    let ids_ptr = module.asc_new(&ids[..2]).unwrap();
+    let data_ptr = module.asc_new(data.as_slice()).unwrap();
+    assert!(module
+        .instance_ctx_mut()
+        .store_set_many(&gas, entities_ptr, ids_ptr, data_ptr)
+        .is_err());
+    module.instance_ctx_mut().ctx.state.exit_handler();
+    assert!(mods(module).is_empty());
+}
+
 /// Test the various ways in which `store_set` sets the `id` of entities and
 /// errors when there are issues
 #[tokio::test]
@@ -0,0 +1,22 @@
+;; A minimal mapping with a no-op event handler, for testing the module cache. It is written by
+;; hand since it only needs a bump allocator and no AssemblyScript runtime.
+(module
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 8))
+
+  (func (export "memory.allocate") (param $size i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $next))
+    (global.set $next (i32.add (local.get $ptr) (local.get $size)))
+    ;; Grow the memory if the allocation doesn't fit
+    (if (i32.gt_u (global.get $next) (i32.mul (memory.size) (i32.const 65536)))
+      (then
+        (drop (memory.grow
+          (i32.add
+            (i32.shr_u
+              (i32.sub (global.get $next) (i32.mul (memory.size) (i32.const 65536)))
+              (i32.const 16))
+            (i32.const 1))))))
+    (local.get $ptr))
+
+  (func (export "handleLog") (param $event i32)))
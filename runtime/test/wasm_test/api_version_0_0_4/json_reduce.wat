@@ -0,0 +1,46 @@
+;; A mapping that folds a JSON array with `json.reduce`, for testing that the callback runs in
+;; the mapping. `countTrue` counts the `true` elements of the array. It is written by hand since
+;; it only needs a bump allocator and no AssemblyScript runtime.
+(module
+  (import "env" "json.reduce" (func $reduce (param i32 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 64))
+
+  ;; The string "countTrue"
+  (data (i32.const 16) "\09\00\00\00c\00o\00u\00n\00t\00T\00r\00u\00e\00")
+
+  (func $allocate (export "memory.allocate") (param $size i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $next))
+    (global.set $next (i32.add (local.get $ptr) (local.get $size)))
+    ;; Grow the memory if the allocation doesn't fit
+    (if (i32.gt_u (global.get $next) (i32.mul (memory.size) (i32.const 65536)))
+      (then
+        (drop (memory.grow
+          (i32.add
+            (i32.shr_u
+              (i32.sub (global.get $next) (i32.mul (memory.size) (i32.const 65536)))
+              (i32.const 16))
+            (i32.const 1))))))
+    (local.get $ptr))
+
+  ;; reduce(array: JSONValue, initial: Value): Value
+  (func (export "reduce") (param $array i32) (param $initial i32) (result i32)
+    (call $reduce (local.get $array) (i32.const 16) (local.get $initial)))
+
+  ;; countTrue(count: Value, value: JSONValue): Value
+  ;;
+  ;; Both are `AscEnum`s: the kind at offset 0 and the payload at offset 8. `count` is an
+  ;; `Int` value, kind 1, and `true` is a `Bool` JSON value, kind 1, with payload 1.
+  (func (export "countTrue") (param $count i32) (param $value i32) (result i32)
+    (local $result i32)
+    (local.set $result (call $allocate (i32.const 16)))
+    (i32.store (local.get $result) (i32.const 1))
+    (i64.store offset=8 (local.get $result)
+      (i64.add
+        (i64.load offset=8 (local.get $count))
+        (i64.extend_i32_u
+          (i32.and
+            (i32.eq (i32.load (local.get $value)) (i32.const 1))
+            (i32.load offset=8 (local.get $value))))))
+    (local.get $result)))
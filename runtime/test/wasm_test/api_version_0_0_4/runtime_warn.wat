@@ -0,0 +1,32 @@
+;; A mapping whose handlers emit a warning, one of them failing afterwards, for testing that the
+;; warnings of a failed handler are discarded. It is written by hand since it only needs a bump
+;; allocator and no AssemblyScript runtime.
+(module
+  (import "env" "runtime.warn" (func $warn (param i32)))
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 64))
+
+  ;; The string "stale"
+  (data (i32.const 16) "\05\00\00\00s\00t\00a\00l\00e\00")
+
+  (func (export "memory.allocate") (param $size i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $next))
+    (global.set $next (i32.add (local.get $ptr) (local.get $size)))
+    ;; Grow the memory if the allocation doesn't fit
+    (if (i32.gt_u (global.get $next) (i32.mul (memory.size) (i32.const 65536)))
+      (then
+        (drop (memory.grow
+          (i32.add
+            (i32.shr_u
+              (i32.sub (global.get $next) (i32.mul (memory.size) (i32.const 65536)))
+              (i32.const 16))
+            (i32.const 1))))))
+    (local.get $ptr))
+
+  (func (export "handleLog") (param $event i32)
+    (call $warn (i32.const 16)))
+
+  (func (export "handleLogAndFail") (param $event i32)
+    (call $warn (i32.const 16))
+    (unreachable)))
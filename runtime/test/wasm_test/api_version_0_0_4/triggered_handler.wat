@@ -0,0 +1,31 @@
+;; A mapping with a handler that fails unless `handleFirst` already ran for the block, for
+;; testing `block.triggeredHandler`. It is written by hand since it only needs a bump allocator
+;; and no AssemblyScript runtime.
+(module
+  (import "env" "block.triggeredHandler" (func $triggered (param i32) (result i32)))
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 64))
+
+  ;; The string "handleFirst"
+  (data (i32.const 16) "\0b\00\00\00h\00a\00n\00d\00l\00e\00F\00i\00r\00s\00t\00")
+
+  (func (export "memory.allocate") (param $size i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $next))
+    (global.set $next (i32.add (local.get $ptr) (local.get $size)))
+    ;; Grow the memory if the allocation doesn't fit
+    (if (i32.gt_u (global.get $next) (i32.mul (memory.size) (i32.const 65536)))
+      (then
+        (drop (memory.grow
+          (i32.add
+            (i32.shr_u
+              (i32.sub (global.get $next) (i32.mul (memory.size) (i32.const 65536)))
+              (i32.const 16))
+            (i32.const 1))))))
+    (local.get $ptr))
+
+  (func (export "handleFirst") (param $event i32))
+
+  (func (export "handleIfFirstTriggered") (param $event i32)
+    (if (i32.eqz (call $triggered (i32.const 16)))
+      (then (unreachable)))))
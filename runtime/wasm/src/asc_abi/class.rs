@@ -328,6 +328,10 @@ impl AscIndexId for Array<Uint8Array> {
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArrayUint8Array;
 }
 
+impl AscIndexId for Array<AscPtr<Uint8Array>> {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArrayUint8Array;
+}
+
 impl AscIndexId for Array<AscPtr<AscEnum<EthereumValueKind>>> {
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArrayEthereumValue;
 }
@@ -354,6 +358,15 @@ impl AscIndexId for Array<AscPtr<AscTypedMapEntry<AscString, AscEnum<StoreValueK
         IndexForAscTypeId::ArrayTypedMapEntryStringStoreValue;
 }
 
+impl AscIndexId for Array<AscPtr<AscTypedMapEntry<AscString, AscEnum<EthereumValueKind>>>> {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId =
+        IndexForAscTypeId::ArrayTypedMapEntryStringEthereumValue;
+}
+
+impl AscIndexId for Array<AscPtr<AscTypedMapEntry<AscString, AscString>>> {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArrayTypedMapEntryStringString;
+}
+
 impl AscIndexId for Array<u8> {
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArrayU8;
 }
@@ -398,6 +411,10 @@ impl AscIndexId for Array<AscPtr<AscBigDecimal>> {
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArrayBigDecimal;
 }
 
+impl AscIndexId for Array<AscPtr<AscBigInt>> {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArrayBigInt;
+}
+
 /// Represents any `AscValue` since they all fit in 64 bits.
 #[repr(C)]
 #[derive(Copy, Clone, Default)]
@@ -541,6 +558,23 @@ impl Default for EthereumValueKind {
 
 impl AscValue for EthereumValueKind {}
 
+/// A named Ethereum value, used for the parameters of events and the inputs
+/// and outputs of calls.
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscEventParam {
+    pub name: AscPtr<AscString>,
+    pub value: AscPtr<AscEnum<EthereumValueKind>>,
+}
+
+impl AscIndexId for AscEventParam {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EventParam;
+}
+
+impl AscIndexId for Array<AscPtr<AscEventParam>> {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArrayEventParam;
+}
+
 #[repr(u32)]
 #[derive(AscType, Copy, Clone)]
 pub enum StoreValueKind {
@@ -605,6 +639,15 @@ impl AscIndexId for AscTypedMapEntry<AscString, AscEnum<JsonValueKind>> {
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::TypedMapEntryStringJsonValue;
 }
 
+impl AscIndexId for AscTypedMapEntry<AscString, AscEnum<EthereumValueKind>> {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId =
+        IndexForAscTypeId::TypedMapEntryStringEthereumValue;
+}
+
+impl AscIndexId for AscTypedMapEntry<AscString, AscString> {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::TypedMapEntryStringString;
+}
+
 pub(crate) type AscTypedMapEntryArray<K, V> = Array<AscPtr<AscTypedMapEntry<K, V>>>;
 
 #[repr(C)]
@@ -630,8 +673,17 @@ impl AscIndexId for AscTypedMap<AscString, AscTypedMap<AscString, AscEnum<JsonVa
         IndexForAscTypeId::TypedMapStringTypedMapStringJsonValue;
 }
 
+impl AscIndexId for AscTypedMap<AscString, AscEnum<EthereumValueKind>> {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::TypedMapStringEthereumValue;
+}
+
+impl AscIndexId for AscTypedMap<AscString, AscString> {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::TypedMapStringString;
+}
+
 pub type AscEntity = AscTypedMap<AscString, AscEnum<StoreValueKind>>;
 pub(crate) type AscJson = AscTypedMap<AscString, AscEnum<JsonValueKind>>;
+pub(crate) type AscEthereumValueMap = AscTypedMap<AscString, AscEnum<EthereumValueKind>>;
 
 #[repr(u32)]
 #[derive(AscType, Copy, Clone)]
@@ -717,6 +769,10 @@ impl AscIndexId for AscResult<AscPtr<AscEnum<JsonValueKind>>, bool> {
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ResultJsonValueBool;
 }
 
+impl AscIndexId for AscResult<AscPtr<Array<AscPtr<AscEnum<EthereumValueKind>>>>, bool> {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ResultArrayEthereumValueBool;
+}
+
 #[repr(C)]
 #[derive(AscType, Copy, Clone)]
 pub struct AscWrapped<V: AscValue> {
@@ -734,3 +790,7 @@ impl AscIndexId for AscWrapped<bool> {
 impl AscIndexId for AscWrapped<AscPtr<AscEnum<JsonValueKind>>> {
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::WrappedJsonValue;
 }
+
+impl AscIndexId for AscWrapped<AscPtr<Array<AscPtr<AscEnum<EthereumValueKind>>>>> {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::WrappedArrayEthereumValue;
+}
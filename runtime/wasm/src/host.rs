@@ -65,6 +65,7 @@ where
     ) -> Result<Sender<Self::Req>, Error> {
         let experimental_features = ExperimentalFeatures {
             allow_non_deterministic_ipfs: ENV_VARS.mappings.allow_non_deterministic_ipfs,
+            allow_debug_host_exports: ENV_VARS.mappings.allow_debug_host_exports,
         };
         crate::mapping::spawn_module(
             raw_module,
@@ -82,6 +83,7 @@ where
         network_name: String,
         subgraph_id: DeploymentHash,
         data_source: DataSource<C>,
+        instance_index: i32,
         templates: Arc<Vec<DataSourceTemplate<C>>>,
         mapping_request_sender: Sender<MappingRequest<C>>,
         metrics: Arc<HostMetrics>,
@@ -92,6 +94,7 @@ where
             network_name,
             subgraph_id,
             data_source,
+            instance_index,
             templates,
             mapping_request_sender,
             metrics,
@@ -118,6 +121,7 @@ where
         network_name: String,
         subgraph_id: DeploymentHash,
         data_source: DataSource<C>,
+        instance_index: i32,
         templates: Arc<Vec<DataSourceTemplate<C>>>,
         mapping_request_sender: Sender<MappingRequest<C>>,
         metrics: Arc<HostMetrics>,
@@ -128,6 +132,7 @@ where
         let host_exports = Arc::new(HostExports::new(
             subgraph_id,
             &data_source,
+            instance_index,
             network_name,
             templates,
             link_resolver,
@@ -171,6 +176,12 @@ where
             "data_source" => &self.data_source.name(),
         );
 
+        let chain_head_ptr = state.chain_head_ptr().cloned();
+        let chain_head_timestamp = state.chain_head_timestamp();
+        let onchain_trigger = trigger.trigger.as_onchain().cloned().map(Arc::new);
+
+        let block_log_addresses = state.log_addresses().cheap_clone();
+
         let (result_sender, result_receiver) = channel();
         let start_time = Instant::now();
         let metrics = self.metrics.clone();
@@ -183,6 +194,11 @@ where
                     state,
                     host_exports: self.host_exports.cheap_clone(),
                     block_ptr,
+                    chain_head_ptr,
+                    chain_head_timestamp,
+                    trigger: onchain_trigger,
+                    last_return_data: Default::default(),
+                    block_log_addresses,
                     proof_of_indexing,
                     host_fns: self.host_fns.cheap_clone(),
                     debug_fork: debug_fork.cheap_clone(),
@@ -260,6 +276,10 @@ impl<C: Blockchain> RuntimeHostTrait<C> for RuntimeHost<C> {
         self.data_source.creation_block()
     }
 
+    fn instance_index(&self) -> i32 {
+        self.host_exports.data_source_instance_index
+    }
+
     /// Offchain data sources track done_at which is set once the
     /// trigger has been processed.
     fn done_at(&self) -> Option<BlockNumber> {
@@ -1,5 +1,6 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::ops::Deref;
 use std::str::FromStr;
 use std::time::{Duration, Instant};
@@ -7,24 +8,26 @@ use std::time::{Duration, Instant};
 use graph::data::value::Word;
 use never::Never;
 use semver::Version;
+use sha2::Digest;
 use wasmtime::Trap;
 use web3::types::H160;
 
-use graph::blockchain::Blockchain;
+use graph::blockchain::{Blockchain, MappingTriggerTrait};
 use graph::components::store::{EnsLookup, GetScope, LoadRelatedRequest};
 use graph::components::store::{EntityKey, EntityType};
 use graph::components::subgraph::{
-    PoICausalityRegion, ProofOfIndexingEvent, SharedProofOfIndexing,
+    LogAddresses, PoICausalityRegion, ProofOfIndexingEvent, RawEvent, SharedProofOfIndexing,
 };
 use graph::data::store;
 use graph::data_source::{CausalityRegion, DataSource, DataSourceTemplate, EntityTypeAccess};
 use graph::ensure;
 use graph::prelude::ethabi::param_type::Reader;
-use graph::prelude::ethabi::{decode, encode, Token};
+use graph::prelude::ethabi::{decode, encode, LogParam, Token};
 use graph::prelude::serde_json;
 use graph::prelude::{slog::b, slog::record_static, *};
 use graph::runtime::gas::{self, complexity, Gas, GasCounter};
 pub use graph::runtime::{DeterministicHostError, HostExportError};
+use graph::schema::{InputSchema, SCHEMA_TYPE_NAME};
 
 use crate::module::{WasmInstance, WasmInstanceContext};
 use crate::{error::DeterminismLevel, module::IntoTrap};
@@ -61,12 +64,15 @@ impl IntoTrap for HostExportError {
 pub struct HostExports<C: Blockchain> {
     pub(crate) subgraph_id: DeploymentHash,
     pub api_version: Version,
-    data_source_name: String,
+    pub(crate) data_source_name: String,
     data_source_address: Vec<u8>,
+    data_source_creator_address: Vec<u8>,
     subgraph_network: String,
     data_source_context: Arc<Option<DataSourceContext>>,
     entity_type_access: EntityTypeAccess,
     data_source_causality_region: CausalityRegion,
+    /// The creation ordinal of the data source among the instances of its template
+    pub(crate) data_source_instance_index: i32,
 
     /// Some data sources have indeterminism or different notions of time. These
     /// need to be each be stored separately to separate causality between them,
@@ -76,12 +82,15 @@ pub struct HostExports<C: Blockchain> {
     templates: Arc<Vec<DataSourceTemplate<C>>>,
     pub(crate) link_resolver: Arc<dyn LinkResolver>,
     ens_lookup: Arc<dyn EnsLookup>,
+    /// Patterns compiled for `string.matches`, keyed by their source
+    regex_cache: std::sync::Mutex<HashMap<String, regex::Regex>>,
 }
 
 impl<C: Blockchain> HostExports<C> {
     pub fn new(
         subgraph_id: DeploymentHash,
         data_source: &DataSource<C>,
+        data_source_instance_index: i32,
         subgraph_network: String,
         templates: Arc<Vec<DataSourceTemplate<C>>>,
         link_resolver: Arc<dyn LinkResolver>,
@@ -92,14 +101,20 @@ impl<C: Blockchain> HostExports<C> {
             api_version: data_source.api_version(),
             data_source_name: data_source.name().to_owned(),
             data_source_address: data_source.address().unwrap_or_default(),
+            data_source_creator_address: data_source
+                .creator_address()
+                .or_else(|| data_source.address())
+                .unwrap_or_default(),
             data_source_context: data_source.context().cheap_clone(),
             entity_type_access: data_source.entities(),
             data_source_causality_region: data_source.causality_region(),
+            data_source_instance_index,
             poi_causality_region: PoICausalityRegion::from_network(&subgraph_network),
             subgraph_network,
             templates,
             link_resolver,
             ens_lookup,
+            regex_cache: std::sync::Mutex::new(HashMap::new()),
         }
     }
 
@@ -252,6 +267,44 @@ impl<C: Blockchain> HostExports<C> {
         Ok(())
     }
 
+    /// Append a record with the type tag `tag` and the JSON document `payload` to the
+    /// deployment's append-only raw event table. Records are numbered in the order in which they
+    /// are appended in a block. Since the numbering needs a deterministic order of handlers,
+    /// offchain data sources can't append raw events. Deployments created before raw events were
+    /// introduced get a table for them when their store is opened for writing.
+    pub(crate) fn store_append_raw(
+        &self,
+        state: &mut BlockState<C>,
+        tag: String,
+        payload: String,
+        gas: &GasCounter,
+    ) -> Result<(), HostExportError> {
+        gas.consume_host_fn(gas::STORE_SET.with_args(complexity::Linear, (&tag, &payload)))?;
+
+        if self.data_source_causality_region != CausalityRegion::ONCHAIN {
+            return Err(HostExportError::Deterministic(anyhow!(
+                "offchain data source `{}` can not append raw events",
+                self.data_source_name
+            )));
+        }
+        if !state.entity_cache.store.supports_raw_events() {
+            return Err(HostExportError::Deterministic(anyhow!(
+                "deployment `{}` has no table for raw events",
+                self.subgraph_id
+            )));
+        }
+        serde_json::from_str::<serde_json::Value>(&payload).map_err(|e| {
+            HostExportError::Deterministic(anyhow!(
+                "raw event payload with tag `{}` is not valid JSON: {}",
+                tag,
+                e
+            ))
+        })?;
+
+        state.push_raw_event(RawEvent { tag, payload });
+        Ok(())
+    }
+
     pub(crate) fn store_get<'a>(
         &self,
         state: &'a mut BlockState<C>,
@@ -277,6 +330,24 @@ impl<C: Blockchain> HostExports<C> {
         Ok(result)
     }
 
+    /// The entity as a canonical JSON string, or `None` if it does not exist. Attributes are
+    /// sorted by name and each value is tagged with its type, e.g.
+    /// `{"id":{"type":"String","data":"1"}}`.
+    pub(crate) fn store_entity_to_json(
+        &self,
+        state: &mut BlockState<C>,
+        entity_type: String,
+        entity_id: String,
+        gas: &GasCounter,
+    ) -> Result<Option<String>, anyhow::Error> {
+        let entity = self.store_get(state, entity_type, entity_id, gas, GetScope::Store)?;
+        let json = entity.map(|entity| entity_to_json(&entity)).transpose()?;
+        if let Some(json) = &json {
+            gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, json))?;
+        }
+        Ok(json)
+    }
+
     pub(crate) fn store_load_related(
         &self,
         state: &mut BlockState<C>,
@@ -299,6 +370,88 @@ impl<C: Blockchain> HostExports<C> {
         Ok(result)
     }
 
+    /// An estimate in bytes of the memory used by the entity changes the current block has made
+    /// so far. The estimate depends on how the node represents and caches entities, so it is not
+    /// deterministic; that is why `store.cacheBytes` is only linked for debugging, with
+    /// `GRAPH_ALLOW_DEBUG_HOST_EXPORTS`.
+    pub(crate) fn store_cache_bytes(
+        &self,
+        state: &BlockState<C>,
+        gas: &GasCounter,
+    ) -> Result<BigInt, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        Ok(BigInt::from(state.entity_cache.pending_weight() as u64))
+    }
+
+    /// A keccak256 commitment over the entity operations of the current block so far. The
+    /// operations are sorted first, so the commitment does not depend on the order of the writes.
+    pub(crate) fn block_entity_commitment(
+        &self,
+        state: &BlockState<C>,
+        gas: &GasCounter,
+    ) -> Result<[u8; 32], DeterministicHostError> {
+        let changes = state.entity_cache.encode_pending_changes()?;
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &changes))?;
+        Ok(tiny_keccak::keccak256(&changes))
+    }
+
+    /// The `type:id` keys of the entities the current block has changed so far, in sorted order.
+    pub(crate) fn store_changed_keys(
+        &self,
+        state: &BlockState<C>,
+        gas: &GasCounter,
+    ) -> Result<Vec<String>, DeterministicHostError> {
+        let keys = state.entity_cache.changed_keys();
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &keys))?;
+        Ok(keys)
+    }
+
+    pub(crate) fn store_is_new_in_block(
+        &self,
+        state: &mut BlockState<C>,
+        entity_type: String,
+        entity_id: String,
+        gas: &GasCounter,
+    ) -> Result<bool, anyhow::Error> {
+        let store_key = EntityKey {
+            entity_type: EntityType::new(entity_type),
+            entity_id: entity_id.into(),
+            causality_region: self.data_source_causality_region,
+        };
+        self.check_entity_type_access(&store_key.entity_type)?;
+
+        gas.consume_host_fn(gas::STORE_GET.with_args(complexity::Size, &store_key))?;
+
+        Ok(state.entity_cache.is_new_in_block(&store_key)?)
+    }
+
+    /// The block at which the entity was last written, or `None` if the
+    /// entity does not exist. Entities written in the current block report
+    /// `block`.
+    pub(crate) fn store_last_modified_block(
+        &self,
+        state: &BlockState<C>,
+        entity_type: String,
+        entity_id: String,
+        block: BlockNumber,
+        gas: &GasCounter,
+    ) -> Result<Option<BigInt>, anyhow::Error> {
+        let store_key = EntityKey {
+            entity_type: EntityType::new(entity_type),
+            entity_id: entity_id.into(),
+            causality_region: self.data_source_causality_region,
+        };
+        self.check_entity_type_access(&store_key.entity_type)?;
+
+        gas.consume_host_fn(gas::STORE_GET.with_args(complexity::Size, &store_key))?;
+
+        let result = state
+            .entity_cache
+            .last_modified_block(&store_key, block)?
+            .map(BigInt::from);
+        Ok(result)
+    }
+
     /// Prints the module of `n` in hex.
     /// Integers are encoded using the least amount of digits (no leading zero digits).
     /// Their encoding may be of uneven length. The number zero encodes as "0x0".
@@ -322,11 +475,23 @@ impl<C: Blockchain> HostExports<C> {
         ))
     }
 
-    pub(crate) fn ipfs_cat(&self, logger: &Logger, link: String) -> Result<Vec<u8>, anyhow::Error> {
+    /// Fetches `link`, giving up after `timeout` if one is given. Without a timeout, the link
+    /// resolver's own timeout applies.
+    pub(crate) fn ipfs_cat(
+        &self,
+        logger: &Logger,
+        link: String,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<u8>, anyhow::Error> {
         // Does not consume gas because this is not a part of the deterministic feature set.
         // Ideally this would first consume gas for fetching the file stats, and then again
         // for the bytes of the file.
-        graph::block_on(self.link_resolver.cat(logger, &Link { link }))
+        graph::block_on(cat_with_timeout(
+            self.link_resolver.as_ref(),
+            logger,
+            &Link { link },
+            timeout,
+        ))
     }
 
     pub(crate) fn ipfs_get_block(
@@ -354,10 +519,12 @@ impl<C: Blockchain> HostExports<C> {
         callback: &str,
         user_data: store::Value,
         flags: Vec<String>,
+        gas: &GasCounter,
     ) -> Result<Vec<BlockState<C>>, anyhow::Error> {
-        // Does not consume gas because this is not a part of deterministic APIs.
-        // Ideally we would consume gas the same as ipfs_cat and then share
-        // gas across the spawned modules for callbacks.
+        // Fetching the file does not consume gas because this is not a part of deterministic
+        // APIs, but the callbacks are handler work: their gas is charged to `gas`, and their time
+        // counts towards the timeout of `module`, which is paused while waiting on IPFS. Once the
+        // timeout runs out, no further callbacks are started.
 
         const JSON_FLAG: &str = "json";
         ensure!(
@@ -367,6 +534,7 @@ impl<C: Blockchain> HostExports<C> {
 
         let host_metrics = module.host_metrics.clone();
         let valid_module = module.valid_module.clone();
+        let timeout_stopwatch = module.timeout_stopwatch.clone();
         let ctx = module.ctx.derive_with_empty_block_state();
         let callback = callback.to_owned();
         // Create a base error message to avoid borrowing headaches
@@ -385,14 +553,36 @@ impl<C: Blockchain> HostExports<C> {
             let mut v = Vec::new();
             while let Some(sv) = graph::block_on(stream.next()) {
                 let sv = sv?;
-                let module = WasmInstance::from_valid_module_with_ctx(
+
+                // The callbacks share the timeout of `module`, so each one only gets what is
+                // left of it.
+                let timeout = match module.timeout {
+                    Some(timeout) => {
+                        let elapsed = timeout_stopwatch.lock().unwrap().elapsed();
+                        match timeout.checked_sub(elapsed) {
+                            Some(time_left) if !time_left.is_zero() => Some(time_left),
+                            _ => {
+                                return Err(anyhow!(
+                                    "the callbacks hit the timeout of '{}' seconds",
+                                    timeout.as_secs()
+                                ))
+                            }
+                        }
+                    }
+                    None => None,
+                };
+                let instance = WasmInstance::from_valid_module_with_ctx(
                     valid_module.clone(),
                     ctx.derive_with_empty_block_state(),
                     host_metrics.clone(),
-                    module.timeout,
+                    timeout,
                     module.experimental_features,
                 )?;
-                let result = module.handle_json_callback(&callback, &sv.value, &user_data)?;
+                timeout_stopwatch.lock().unwrap().start();
+                let result = instance.handle_json_callback(&callback, &sv.value, &user_data);
+                timeout_stopwatch.lock().unwrap().stop();
+                let (state, gas_used) = result?;
+                gas.consume_host_fn(gas_used)?;
                 // Log progress every 15s
                 if last_log.elapsed() > Duration::from_secs(15) {
                     debug!(
@@ -403,13 +593,60 @@ impl<C: Blockchain> HostExports<C> {
                     );
                     last_log = Instant::now();
                 }
-                v.push(result)
+                v.push(state)
             }
             Ok(v)
         };
         result.map_err(move |e: Error| anyhow::anyhow!("{}: {}", errmsg, e.to_string()))
     }
 
+    /// Folds the elements of the JSON array `array` into an accumulator, starting from `initial`.
+    /// Like the callbacks of `ipfs_map`, each call of `callback` runs in a fresh instance; the gas
+    /// the calls use is charged to `gas`, and their block states are returned to the caller.
+    pub(crate) fn json_reduce(
+        module: &mut WasmInstanceContext<C>,
+        array: serde_json::Value,
+        callback: &str,
+        initial: store::Value,
+        gas: &GasCounter,
+    ) -> Result<(store::Value, Vec<BlockState<C>>), HostExportError> {
+        let values = match array {
+            serde_json::Value::Array(values) => values,
+            other => {
+                return Err(DeterministicHostError::from(anyhow!(
+                    "json.reduce expects a JSON array but got `{}`",
+                    other
+                ))
+                .into())
+            }
+        };
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &values.len()))?;
+
+        let host_metrics = module.host_metrics.clone();
+        let valid_module = module.valid_module.clone();
+        let ctx = module.ctx.derive_with_empty_block_state();
+        let timeout = module.timeout;
+        let experimental_features = module.experimental_features;
+
+        let mut states = Vec::with_capacity(values.len());
+        let result = json_reduce(&values, initial, |accumulator, value| {
+            let module = WasmInstance::from_valid_module_with_ctx(
+                valid_module.clone(),
+                ctx.derive_with_empty_block_state(),
+                host_metrics.clone(),
+                timeout,
+                experimental_features,
+            )?;
+            let (accumulator, state, gas_used) =
+                module.handle_json_reduce_callback(callback, &accumulator, value)?;
+            gas.consume_host_fn(gas_used)?;
+            states.push(state);
+            Ok(accumulator)
+        })
+        .map_err(|e| anyhow!("json.reduce: callback '{}' failed: {:#}", callback, e))?;
+        Ok((result, states))
+    }
+
     /// Expects a decimal string.
     pub(crate) fn json_to_i64(
         &self,
@@ -435,7 +672,7 @@ impl<C: Blockchain> HostExports<C> {
             .map_err(DeterministicHostError::from)
     }
 
-    /// Expects a decimal string.
+    /// Expects a decimal string. Values that are NaN or infinite are rejected.
     pub(crate) fn json_to_f64(
         &self,
         json: String,
@@ -443,9 +680,7 @@ impl<C: Blockchain> HostExports<C> {
     ) -> Result<f64, DeterministicHostError> {
         gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &json))?;
 
-        f64::from_str(&json)
-            .with_context(|| format!("JSON `{}` cannot be parsed as f64", json))
-            .map_err(DeterministicHostError::from)
+        json_to_finite_f64(&json)
     }
 
     /// Expects a decimal string.
@@ -462,6 +697,42 @@ impl<C: Blockchain> HostExports<C> {
         Ok(big_int.to_signed_bytes_le())
     }
 
+    pub(crate) fn json_to_array(
+        &self,
+        json: serde_json::Value,
+        gas: &GasCounter,
+    ) -> Result<Vec<serde_json::Value>, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        json_as_array(json)
+    }
+
+    pub(crate) fn json_to_object(
+        &self,
+        json: serde_json::Value,
+        gas: &GasCounter,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        json_as_object(json)
+    }
+
+    pub(crate) fn json_to_bool(
+        &self,
+        json: serde_json::Value,
+        gas: &GasCounter,
+    ) -> Result<bool, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        json_as_bool(json)
+    }
+
+    pub(crate) fn json_to_string(
+        &self,
+        json: serde_json::Value,
+        gas: &GasCounter,
+    ) -> Result<String, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        json_as_string(json)
+    }
+
     pub(crate) fn crypto_keccak_256(
         &self,
         input: Vec<u8>,
@@ -472,6 +743,55 @@ impl<C: Blockchain> HostExports<C> {
         Ok(tiny_keccak::keccak256(data))
     }
 
+    pub(crate) fn crypto_sha256(
+        &self,
+        input: Vec<u8>,
+        gas: &GasCounter,
+    ) -> Result<[u8; 32], DeterministicHostError> {
+        let data = &input[..];
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, data))?;
+        Ok(sha2::Sha256::digest(data).into())
+    }
+
+    /// SHA3-256 as standardized in FIPS 202; this differs from `crypto_keccak_256` in the padding.
+    pub(crate) fn crypto_sha3(
+        &self,
+        input: Vec<u8>,
+        gas: &GasCounter,
+    ) -> Result<[u8; 32], DeterministicHostError> {
+        let data = &input[..];
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, data))?;
+        Ok(sha3::Sha3_256::digest(data).into())
+    }
+
+    /// The EIP-712 `hashStruct` of a struct, i.e. `keccak256(typeHash || field0 || ...)`, given
+    /// its type hash and the encodings of its fields. Each field must already be encoded as 32
+    /// bytes.
+    pub(crate) fn crypto_struct_hash(
+        &self,
+        type_hash: Vec<u8>,
+        fields: Vec<Vec<u8>>,
+        gas: &GasCounter,
+    ) -> Result<[u8; 32], DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &fields))?;
+        struct_hash(&type_hash, &fields)
+    }
+
+    /// The address that signed `hash` with the signature `(v, r, s)`, or `None` if the
+    /// signature is invalid. Like OpenZeppelin's `ECDSA.recover`, `v` must be 27 or 28 and
+    /// malleable signatures with an `s` in the upper half of the curve order are rejected.
+    pub(crate) fn crypto_ecrecover(
+        &self,
+        hash: Vec<u8>,
+        v: i32,
+        r: Vec<u8>,
+        s: Vec<u8>,
+        gas: &GasCounter,
+    ) -> Result<Option<H160>, DeterministicHostError> {
+        gas.consume_host_fn(gas::ECRECOVER)?;
+        ecrecover(&hash, v, &r, &s)
+    }
+
     pub(crate) fn big_int_plus(
         &self,
         x: BigInt,
@@ -579,6 +899,16 @@ impl<C: Blockchain> HostExports<C> {
         Ok(x & y)
     }
 
+    pub(crate) fn big_int_bit_xor(
+        &self,
+        x: BigInt,
+        y: BigInt,
+        gas: &GasCounter,
+    ) -> Result<BigInt, DeterministicHostError> {
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Max, (&x, &y)))?;
+        Ok(x ^ y)
+    }
+
     pub(crate) fn big_int_left_shift(
         &self,
         x: BigInt,
@@ -599,6 +929,67 @@ impl<C: Blockchain> HostExports<C> {
         Ok(x >> bits)
     }
 
+    pub(crate) fn big_int_next_power_of_two(
+        &self,
+        x: BigInt,
+        gas: &GasCounter,
+    ) -> Result<BigInt, DeterministicHostError> {
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Size, &x))?;
+        big_int_next_power_of_two(x)
+    }
+
+    /// Returns `|x - y|`.
+    pub(crate) fn big_int_abs_diff(
+        &self,
+        x: BigInt,
+        y: BigInt,
+        gas: &GasCounter,
+    ) -> Result<BigInt, DeterministicHostError> {
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Max, (&x, &y)))?;
+        Ok(big_int_abs_diff(x, y))
+    }
+
+    /// The `n`th term of the linear recurrence `a(k) = coefficients[0] * a(k - 1) + ... +
+    /// coefficients[d - 1] * a(k - d)` whose first `d` terms are `seeds`. For example, the
+    /// coefficients `[1, 1]` and seeds `[0, 1]` give the Fibonacci numbers.
+    pub(crate) fn big_int_linear_recurrence(
+        &self,
+        coefficients: Vec<BigInt>,
+        seeds: Vec<BigInt>,
+        n: u32,
+        gas: &GasCounter,
+    ) -> Result<BigInt, DeterministicHostError> {
+        gas.consume_host_fn(
+            gas::BIG_MATH_GAS_OP.with_args(complexity::Size, (&coefficients, &seeds)),
+        )?;
+        big_int_linear_recurrence(coefficients, seeds, n, gas)
+    }
+
+    /// Converts `amount` from a token with `from_decimals` decimals to one with `to_decimals`
+    /// decimals. Scaling up is exact, scaling down rounds half away from zero.
+    pub(crate) fn rescale_amount(
+        &self,
+        amount: BigInt,
+        from_decimals: u8,
+        to_decimals: u8,
+        gas: &GasCounter,
+    ) -> Result<BigInt, DeterministicHostError> {
+        let shift = from_decimals.abs_diff(to_decimals);
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Linear, (&amount, &shift)))?;
+        rescale_amount(amount, from_decimals, to_decimals)
+    }
+
+    /// Rounds `timestamp` down to the start of its bucket of `bucket_size` seconds.
+    pub(crate) fn timestamp_to_bucket(
+        &self,
+        timestamp: BigInt,
+        bucket_size: u32,
+        gas: &GasCounter,
+    ) -> Result<BigInt, DeterministicHostError> {
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Size, &timestamp))?;
+        timestamp_bucket(timestamp, bucket_size)
+    }
+
     /// Useful for IPFS hashes stored as bytes
     pub(crate) fn bytes_to_base58(
         &self,
@@ -656,275 +1047,2027 @@ impl<C: Blockchain> HostExports<C> {
         Ok(x / y)
     }
 
-    pub(crate) fn big_decimal_equals(
+    /// The remainder of truncating division, which has the sign of `x`. The remainder is exact,
+    /// it is not affected by the precision of `big_decimal_divided_by`.
+    pub(crate) fn big_decimal_mod(
         &self,
         x: BigDecimal,
         y: BigDecimal,
         gas: &GasCounter,
-    ) -> Result<bool, HostExportError> {
-        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Min, (&x, &y)))?;
-        Ok(x == y)
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Mul, (&x, &y)))?;
+        big_decimal_mod(x, y)
     }
 
-    pub(crate) fn big_decimal_to_string(
+    /// Like `big_decimal_divided_by`, but returns `default` instead of failing when `y` is zero.
+    pub(crate) fn big_decimal_div_or(
         &self,
         x: BigDecimal,
+        y: BigDecimal,
+        default: BigDecimal,
         gas: &GasCounter,
-    ) -> Result<String, DeterministicHostError> {
-        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Mul, (&x, &x)))?;
-        Ok(x.to_string())
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Mul, (&x, &y)))?;
+        Ok(big_decimal_div_or(x, y, default))
     }
 
-    pub(crate) fn big_decimal_from_string(
+    /// Computes `1 / x` at the same precision as `big_decimal_divided_by`.
+    pub(crate) fn big_decimal_inverse(
         &self,
-        s: String,
+        x: BigDecimal,
         gas: &GasCounter,
     ) -> Result<BigDecimal, DeterministicHostError> {
-        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &s))?;
-        BigDecimal::from_str(&s)
-            .with_context(|| format!("string  is not a BigDecimal: '{}'", s))
-            .map_err(DeterministicHostError::from)
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Mul, (&x, &x)))?;
+        big_decimal_inverse(x)
     }
 
-    pub(crate) fn data_source_create(
+    /// Linearly interpolates between `a` and `b`, i.e., computes `a + (b - a) * t`. The fraction
+    /// `t` is usually in `[0, 1]`, but values outside that range are allowed and extrapolate
+    /// beyond `a` or `b`.
+    pub(crate) fn big_decimal_lerp(
         &self,
-        logger: &Logger,
-        state: &mut BlockState<C>,
-        name: String,
-        params: Vec<String>,
-        context: Option<DataSourceContext>,
-        creation_block: BlockNumber,
+        a: BigDecimal,
+        b: BigDecimal,
+        t: BigDecimal,
         gas: &GasCounter,
-    ) -> Result<(), HostExportError> {
-        gas.consume_host_fn(gas::CREATE_DATA_SOURCE)?;
-        info!(
-            logger,
-            "Create data source";
-            "name" => &name,
-            "params" => format!("{}", params.join(","))
-        );
-
-        // Resolve the name into the right template
-        let template = self
-            .templates
-            .iter()
-            .find(|template| template.name() == name)
-            .with_context(|| {
-                format!(
-                    "Failed to create data source from name `{}`: \
-                     No template with this name in parent data source `{}`. \
-                     Available names: {}.",
-                    name,
-                    self.data_source_name,
-                    self.templates
-                        .iter()
-                        .map(|template| template.name())
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                )
-            })
-            .map_err(DeterministicHostError::from)?
-            .clone();
-
-        // Remember that we need to create this data source
-        state.push_created_data_source(DataSourceTemplateInfo {
-            template,
-            params,
-            context,
-            creation_block,
-        });
-
-        Ok(())
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Mul, (&b, &t)))?;
+        Ok(big_decimal_lerp(a, b, t))
     }
 
-    pub(crate) fn ens_name_by_hash(
+    /// Advances a TWAP accumulator by `price` held for `elapsed` seconds, i.e., computes
+    /// `accumulator + price * elapsed`. A negative `elapsed` is an error.
+    pub(crate) fn big_decimal_twap_update(
         &self,
-        hash: &str,
+        accumulator: BigDecimal,
+        price: BigDecimal,
+        elapsed: BigInt,
         gas: &GasCounter,
-    ) -> Result<Option<String>, anyhow::Error> {
-        gas.consume_host_fn(gas::ENS_NAME_BY_HASH)?;
-        Ok(self.ens_lookup.find_name(hash)?)
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Mul, (&price, &elapsed)))?;
+        twap_update(accumulator, price, elapsed)
     }
 
-    pub(crate) fn is_ens_data_empty(&self) -> Result<bool, anyhow::Error> {
-        Ok(self.ens_lookup.is_table_empty()?)
+    /// Compounds `principal` at `rate` per period over `periods` periods, i.e.,
+    /// `principal * (1 + rate)^periods`.
+    pub(crate) fn big_decimal_compound(
+        &self,
+        principal: BigDecimal,
+        rate: BigDecimal,
+        periods: u32,
+        gas: &GasCounter,
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        big_decimal_compound(principal, rate, periods, gas)
     }
 
-    pub(crate) fn log_log(
+    /// The fractional price impact of swapping `amount_in` into a constant-product pool with the
+    /// given reserves, i.e., how much worse the execution price is than the spot price. Without
+    /// fees this is `amount_in / (reserve_in + amount_in)`.
+    pub(crate) fn big_decimal_price_impact(
         &self,
-        logger: &Logger,
-        level: slog::Level,
-        msg: String,
+        reserve_in: BigDecimal,
+        reserve_out: BigDecimal,
+        amount_in: BigDecimal,
         gas: &GasCounter,
-    ) -> Result<(), DeterministicHostError> {
-        gas.consume_host_fn(gas::LOG_OP.with_args(complexity::Size, &msg))?;
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        gas.consume_host_fn(
+            gas::BIG_MATH_GAS_OP.with_args(complexity::Mul, (&reserve_in, &amount_in)),
+        )?;
+        big_decimal_price_impact(reserve_in, reserve_out, amount_in)
+    }
 
-        let rs = record_static!(level, self.data_source_name.as_str());
+    /// Sums `values`. The sum is computed exactly and only rounded to the usual precision at the
+    /// end so that the result does not depend on the order of the values.
+    pub(crate) fn big_decimal_sum(
+        &self,
+        values: Vec<BigDecimal>,
+        gas: &GasCounter,
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Size, &values))?;
+        Ok(big_decimal_sum(values))
+    }
 
-        logger.log(&slog::Record::new(
-            &rs,
-            &format_args!("{}", msg),
-            b!("data_source" => &self.data_source_name),
-        ));
+    /// The geometric mean of `values`, i.e., the `n`-th root of their product for `n` values. The
+    /// product is computed exactly and the root is rounded to the usual precision.
+    pub(crate) fn big_decimal_geomean(
+        &self,
+        values: Vec<BigDecimal>,
+        gas: &GasCounter,
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Size, &values))?;
+        big_decimal_geomean(values, gas)
+    }
 
-        if level == slog::Level::Critical {
-            return Err(DeterministicHostError::from(anyhow!(
-                "Critical error logged in mapping"
-            )));
-        }
-        Ok(())
+    /// The population variance of `values`, i.e., the mean of the squared differences from
+    /// their mean.
+    pub(crate) fn big_decimal_variance(
+        &self,
+        values: Vec<BigDecimal>,
+        gas: &GasCounter,
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Size, &values))?;
+        Ok(BigDecimal::from(population_variance(values)?))
     }
 
-    pub(crate) fn data_source_address(
+    /// The population standard deviation of `values`, the square root of their variance.
+    pub(crate) fn big_decimal_stddev(
         &self,
+        values: Vec<BigDecimal>,
         gas: &GasCounter,
-    ) -> Result<Vec<u8>, DeterministicHostError> {
-        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
-        Ok(self.data_source_address.clone())
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Size, &values))?;
+        Ok(big_decimal_nth_root(&population_variance(values)?, 2))
     }
 
-    pub(crate) fn data_source_network(
+    /// The weighted median of `values`, where `weights[i]` is the weight of `values[i]`: the
+    /// value at which the cumulative weight of the sorted values reaches half of the total weight.
+    /// When the cumulative weight is exactly half at a value, the median is the midpoint between
+    /// it and the next value, as for the median of an even number of equally weighted values.
+    pub(crate) fn big_decimal_weighted_median(
+        &self,
+        values: Vec<BigDecimal>,
+        weights: Vec<BigDecimal>,
+        gas: &GasCounter,
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        gas.consume_host_fn(
+            gas::BIG_MATH_GAS_OP.with_args(complexity::Linear, (&values, &weights)),
+        )?;
+        big_decimal_weighted_median(values, weights)
+    }
+
+    /// Scales `x` to a fixed-point integer with `decimals` decimal places, i.e., computes
+    /// `round(x * 10^decimals)`. Rounding is half away from zero, so `0.5` becomes `1` and `-0.5`
+    /// becomes `-1`.
+    pub(crate) fn big_decimal_to_fixed(
+        &self,
+        x: BigDecimal,
+        decimals: u32,
+        gas: &GasCounter,
+    ) -> Result<BigInt, DeterministicHostError> {
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Size, &x))?;
+        let decimals = u8::try_from(decimals).map_err(|_| {
+            DeterministicHostError::from(anyhow!(
+                "attempted to convert BigDecimal to fixed point with {} decimals, \
+                 but at most 255 are allowed",
+                decimals
+            ))
+        })?;
+        big_decimal_to_fixed(&x, decimals)
+    }
+
+    pub(crate) fn big_decimal_equals(
+        &self,
+        x: BigDecimal,
+        y: BigDecimal,
+        gas: &GasCounter,
+    ) -> Result<bool, HostExportError> {
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Min, (&x, &y)))?;
+        Ok(x == y)
+    }
+
+    pub(crate) fn big_decimal_approx_equals(
+        &self,
+        x: BigDecimal,
+        y: BigDecimal,
+        tolerance: BigDecimal,
+        gas: &GasCounter,
+    ) -> Result<bool, DeterministicHostError> {
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Linear, (&x, &y)))?;
+        big_decimal_approx_equals(x, y, tolerance)
+    }
+
+    pub(crate) fn big_decimal_to_string(
         &self,
+        x: BigDecimal,
         gas: &GasCounter,
     ) -> Result<String, DeterministicHostError> {
-        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
-        Ok(self.subgraph_network.clone())
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Mul, (&x, &x)))?;
+        Ok(x.to_string())
     }
 
-    pub(crate) fn data_source_context(
+    /// Like `big_decimal_to_string`, but without insignificant trailing zeros in the fraction,
+    /// and without a decimal point for integers.
+    pub(crate) fn big_decimal_to_string_trimmed(
         &self,
+        x: BigDecimal,
         gas: &GasCounter,
-    ) -> Result<Option<DataSourceContext>, DeterministicHostError> {
-        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
-        Ok(self.data_source_context.as_ref().clone())
+    ) -> Result<String, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Mul, (&x, &x)))?;
+        Ok(big_decimal_to_string_trimmed(&x))
     }
 
-    pub(crate) fn json_from_bytes(
+    pub(crate) fn big_int_to_big_decimal(
         &self,
-        bytes: &Vec<u8>,
+        x: BigInt,
         gas: &GasCounter,
-    ) -> Result<serde_json::Value, DeterministicHostError> {
-        // Max JSON size is 10MB.
-        const MAX_JSON_SIZE: usize = 10_000_000;
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &x))?;
+        Ok(big_int_to_big_decimal(x))
+    }
 
-        gas.consume_host_fn(gas::JSON_FROM_BYTES.with_args(gas::complexity::Size, &bytes))?;
+    pub(crate) fn big_decimal_from_string(
+        &self,
+        s: String,
+        gas: &GasCounter,
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &s))?;
+        BigDecimal::from_str(&s)
+            .with_context(|| format!("string  is not a BigDecimal: '{}'", s))
+            .map_err(DeterministicHostError::from)
+    }
 
-        if bytes.len() > MAX_JSON_SIZE {
-            return Err(DeterministicHostError::Other(
-                anyhow!("JSON size exceeds max size of {}", MAX_JSON_SIZE).into(),
-            ));
-        }
+    /// Accepts decimal strings with an optional exponent such as `1.5e18` or `2E-3`.
+    pub(crate) fn big_decimal_from_scientific(
+        &self,
+        s: String,
+        gas: &GasCounter,
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &s))?;
+        big_decimal_from_scientific(&s)
+    }
 
-        serde_json::from_slice(bytes.as_slice())
-            .map_err(|e| DeterministicHostError::from(Error::from(e)))
+    /// Marks the results of the current block as reorg-sensitive if a read of chain state, like an
+    /// `ethereum.call`, at `block_number` happened within the reorg threshold of the chain head.
+    /// When the chain head is unknown, the read is assumed to be reorg-sensitive.
+    pub(crate) fn record_chain_read(
+        &self,
+        state: &mut BlockState<C>,
+        block_number: BlockNumber,
+        chain_head_ptr: Option<&BlockPtr>,
+    ) {
+        if is_unfinalized(
+            block_number,
+            chain_head_ptr.map(|ptr| ptr.number),
+            ENV_VARS.reorg_threshold,
+        ) {
+            state.mark_reorg_sensitive();
+        }
     }
 
-    pub(crate) fn string_to_h160(
+    /// Records a non-fatal warning, e.g., about the quality of the data a handler processed. The
+    /// warnings of a block are logged when the block is processed and do not affect indexing.
+    pub(crate) fn emit_warning(
         &self,
-        string: &str,
+        state: &mut BlockState<C>,
+        message: String,
         gas: &GasCounter,
-    ) -> Result<H160, DeterministicHostError> {
-        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &string))?;
-        string_to_h160(string)
+    ) -> Result<(), DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &message))?;
+        state.push_warning(message);
+        Ok(())
     }
 
-    pub(crate) fn bytes_to_string(
+    /// Increments the mapping-defined metric `name` by `value`. Only metrics listed in
+    /// `GRAPH_MAPPING_METRICS_ALLOWLIST` are recorded, which bounds the number of label values;
+    /// increments of other metrics are ignored rather than failing so that indexing does not
+    /// depend on how the indexer is configured.
+    pub(crate) fn metric_increment(
         &self,
         logger: &Logger,
-        bytes: Vec<u8>,
+        host_metrics: &HostMetrics,
+        name: String,
+        value: BigInt,
         gas: &GasCounter,
-    ) -> Result<String, DeterministicHostError> {
-        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &bytes))?;
+    ) -> Result<(), DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &name))?;
 
-        Ok(bytes_to_string(logger, bytes))
+        let value = u64::try_from(&value).map_err(|e| {
+            DeterministicHostError::from(anyhow!(
+                "invalid increment `{}` for metric `{}`: {}",
+                value,
+                name,
+                e
+            ))
+        })?;
+
+        if ENV_VARS.mappings.metrics_allowlist.contains(&name) {
+            host_metrics.increment_mapping_metric(&name, value as f64);
+        } else {
+            debug!(logger, "Ignoring increment of a metric that is not on the allowlist";
+                   "metric" => &name);
+        }
+        Ok(())
     }
 
-    pub(crate) fn ethereum_encode(
+    /// Whether the handler named `handler` of the current data source or template already ran
+    /// successfully for the current block. Handlers that failed with a deterministic error are not
+    /// considered to have run.
+    pub(crate) fn block_triggered_handler(
         &self,
-        token: Token,
+        state: &BlockState<C>,
+        handler: &str,
         gas: &GasCounter,
-    ) -> Result<Vec<u8>, DeterministicHostError> {
-        let encoded = encode(&[token]);
+    ) -> Result<bool, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &handler))?;
+        Ok(state.has_triggered_handler(&self.data_source_name, handler))
+    }
 
-        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &encoded))?;
+    /// Whether `value`, typically an address or a topic, may be contained in the 2048-bit logs
+    /// bloom of the current block, see `MappingTriggerTrait::block_logs_bloom`. False positives
+    /// are possible, false negatives are not.
+    pub(crate) fn block_bloom_contains(
+        &self,
+        trigger: Option<&C::MappingTrigger>,
+        value: &[u8],
+        gas: &GasCounter,
+    ) -> Result<bool, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &value))?;
+        let bloom = trigger
+            .and_then(|trigger| trigger.block_logs_bloom())
+            .ok_or_else(|| {
+                DeterministicHostError::from(anyhow!("the current block has no logs bloom"))
+            })?;
+        bloom_contains(bloom, value)
+    }
 
-        Ok(encoded)
+    /// The Keccak-256 hash of the RLP-encoded header of the current block, see
+    /// `MappingTriggerTrait::block_header_rlp`, or `None` if the header can't be reconstructed.
+    pub(crate) fn block_header_hash(
+        &self,
+        trigger: Option<&C::MappingTrigger>,
+        gas: &GasCounter,
+    ) -> Result<Option<[u8; 32]>, DeterministicHostError> {
+        // A header of the usual size is charged for before it is encoded; only headers with a
+        // large `extraData` pay for the rest afterwards.
+        gas.consume_host_fn(gas::BLOCK_HEADER_HASH)?;
+        let header_rlp = match trigger.and_then(|trigger| trigger.block_header_rlp()) {
+            Some(header_rlp) => header_rlp,
+            None => return Ok(None),
+        };
+        if let Some(excess) = header_rlp.get(gas::BLOCK_HEADER_SIZE..) {
+            gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &excess))?;
+        }
+        Ok(Some(tiny_keccak::keccak256(&header_rlp)))
     }
 
-    pub(crate) fn ethereum_decode(
+    /// The timestamp of the chain head in seconds, or zero if it is unknown. This is
+    /// non-deterministic since the chain head differs between indexers and over time.
+    pub(crate) fn chain_head_timestamp(
         &self,
-        types: String,
-        data: Vec<u8>,
+        chain_head_timestamp: Option<u64>,
         gas: &GasCounter,
-    ) -> Result<Token, anyhow::Error> {
-        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &data))?;
+    ) -> Result<BigInt, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        Ok(BigInt::from(chain_head_timestamp.unwrap_or(0)))
+    }
 
-        let param_types =
-            Reader::read(&types).map_err(|e| anyhow::anyhow!("Failed to read types: {}", e))?;
+    /// The number of entity operations ever transacted for the deployment. The count survives
+    /// restarts, but it is non-deterministic since reverted blocks still count and copied or
+    /// grafted deployments start over.
+    pub(crate) fn total_entity_writes(
+        &self,
+        entity_writes: u64,
+        gas: &GasCounter,
+    ) -> Result<BigInt, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        Ok(BigInt::from(entity_writes))
+    }
 
-        decode(&[param_types], &data)
-            // The `.pop().unwrap()` here is ok because we're always only passing one
-            // `param_types` to `decode`, so the returned `Vec` has always size of one.
-            // We can't do `tokens[0]` because the value can't be moved out of the `Vec`.
-            .map(|mut tokens| tokens.pop().unwrap())
-            .context("Failed to decode")
+    /// The names of the entity types that the subgraph schema declares, in the order in which
+    /// they are declared. Interfaces are not included.
+    pub(crate) fn schema_entity_types(
+        &self,
+        state: &BlockState<C>,
+        gas: &GasCounter,
+    ) -> Result<Vec<String>, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        Ok(schema_entity_types(&state.entity_cache.schema))
     }
-}
 
-fn string_to_h160(string: &str) -> Result<H160, DeterministicHostError> {
-    // `H160::from_str` takes a hex string with no leading `0x`.
-    let s = string.trim_start_matches("0x");
-    H160::from_str(s)
-        .with_context(|| format!("Failed to convert string to Address/H160: '{}'", s))
-        .map_err(DeterministicHostError::from)
-}
+    /// The type of the field `field` of `entity_type` as it is written in the subgraph schema, for
+    /// example `BigInt` or `[String!]!`.
+    pub(crate) fn schema_field_type(
+        &self,
+        state: &BlockState<C>,
+        entity_type: &str,
+        field: &str,
+        gas: &GasCounter,
+    ) -> Result<String, DeterministicHostError> {
+        gas.consume_host_fn(
+            gas::DEFAULT_GAS_OP.with_args(complexity::Linear, (entity_type, field)),
+        )?;
+        schema_field_type(&state.entity_cache.schema, entity_type, field)
+    }
 
-fn bytes_to_string(logger: &Logger, bytes: Vec<u8>) -> String {
-    let s = String::from_utf8_lossy(&bytes);
+    /// Whether `block_number` is more than `ENV_VARS.mappings.backfill_threshold` blocks behind the
+    /// chain head, i.e., whether the subgraph is still catching up with the chain. This is `false`
+    /// if the chain head is unknown, and non-deterministic since the chain head differs between
+    /// indexers and over time.
+    pub(crate) fn is_backfilling(
+        &self,
+        block_number: BlockNumber,
+        chain_head_ptr: Option<&BlockPtr>,
+        gas: &GasCounter,
+    ) -> Result<bool, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        Ok(is_backfilling(
+            block_number,
+            chain_head_ptr.map(|ptr| ptr.number),
+            ENV_VARS.mappings.backfill_threshold,
+        ))
+    }
 
-    // If the string was re-allocated, that means it was not UTF8.
-    if matches!(s, std::borrow::Cow::Owned(_)) {
-        warn!(
-            logger,
-            "Bytes contain invalid UTF8. This may be caused by attempting \
-            to convert a value such as an address that cannot be parsed to a unicode string. \
-            You may want to use 'toHexString()' instead. String (truncated to 1024 chars): '{}'",
-            &s.chars().take(1024).collect::<String>(),
-        )
+    /// Whether the host export `name`, for example `ipfs.getBlock`, is among the
+    /// `linked_host_exports` of the instance.
+    pub(crate) fn has_capability(
+        &self,
+        name: &str,
+        linked_host_exports: &BTreeSet<&'static str>,
+        gas: &GasCounter,
+    ) -> Result<bool, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &name))?;
+        Ok(linked_host_exports.contains(name))
     }
 
-    // The string may have been encoded in a fixed length buffer and padded with null
-    // characters, so trim trailing nulls.
-    s.trim_end_matches('\u{0000}').to_string()
-}
+    /// The number of blocks between `block_number` and the chain head, or zero if the chain head
+    /// is unknown or behind. This is non-deterministic since the chain head differs between
+    /// indexers and over time.
+    pub(crate) fn block_confirmations(
+        &self,
+        block_number: BlockNumber,
+        chain_head_ptr: Option<&BlockPtr>,
+        gas: &GasCounter,
+    ) -> Result<BigInt, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        Ok(block_confirmations(
+            block_number,
+            chain_head_ptr.map(|ptr| ptr.number),
+        ))
+    }
 
-/// Expose some host functions for testing only
-#[cfg(debug_assertions)]
-pub mod test_support {
-    use std::{borrow::Cow, collections::HashMap, sync::Arc};
+    /// The position of the current log among the logs that the same contract emitted in its
+    /// transaction with the same signature, or -1 outside of event handlers and on chains that
+    /// can't determine it.
+    pub(crate) fn log_signature_ordinal(
+        &self,
+        ordinal: Option<u32>,
+        gas: &GasCounter,
+    ) -> Result<i32, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        match ordinal {
+            Some(ordinal) => i32::try_from(ordinal).map_err(|e| anyhow!(e).into()),
+            None => Ok(-1),
+        }
+    }
 
-    use graph::{
-        blockchain::Blockchain,
-        components::{store::GetScope, subgraph::SharedProofOfIndexing},
-        data::value::Word,
-        prelude::{BlockState, Entity, StopwatchMetrics, Value},
-        runtime::{gas::GasCounter, HostExportError},
-        slog::Logger,
-    };
+    /// The position of the call input called `name` among the call inputs with the given `names`,
+    /// or `None` if the call has no such input.
+    pub(crate) fn call_input(
+        &self,
+        names: &[String],
+        name: &str,
+        gas: &GasCounter,
+    ) -> Result<Option<usize>, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &names))?;
+        Ok(names.iter().position(|input| input == name))
+    }
 
-    use crate::MappingContext;
+    /// The parameters of the current event keyed by name; parameters without a name are keyed by
+    /// their position. Only available in event handlers.
+    pub(crate) fn event_params_map(
+        &self,
+        params: Option<&[LogParam]>,
+        gas: &GasCounter,
+    ) -> Result<Vec<(String, Token)>, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        let params = params.ok_or_else(|| {
+            DeterministicHostError::from(anyhow!(
+                "event.paramsMap can only be called from an event handler"
+            ))
+        })?;
+        Ok(event_params_map(params))
+    }
 
-    pub struct HostExports<C: Blockchain>(Arc<super::HostExports<C>>);
+    /// The selector of the function called by the current transaction, i.e. the first 4 bytes of
+    /// its input, or `None` if the input is shorter than that or the trigger has no transaction.
+    pub(crate) fn transaction_selector(
+        &self,
+        selector: Option<[u8; 4]>,
+        gas: &GasCounter,
+    ) -> Result<Option<[u8; 4]>, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        Ok(selector)
+    }
 
-    impl<C: Blockchain> HostExports<C> {
-        pub fn new(ctx: &MappingContext<C>) -> Self {
-            HostExports(ctx.host_exports.clone())
-        }
+    /// The gas used by the current transaction according to its receipt. The receipt, and hence
+    /// the result, is only available in event handlers that declare `receipt: true`; otherwise
+    /// this returns `None`.
+    pub(crate) fn transaction_gas_used(
+        &self,
+        gas_used: Option<BigInt>,
+        gas: &GasCounter,
+    ) -> Result<Option<BigInt>, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        Ok(gas_used)
+    }
 
-        pub fn store_set(
-            &self,
-            logger: &Logger,
-            state: &mut BlockState<C>,
+    /// Whether the current log was emitted by the data source's own contract. This is always
+    /// `false` outside of event handlers and for data sources without an address.
+    pub(crate) fn log_is_own_address(
+        &self,
+        log_address: Option<&[u8]>,
+        gas: &GasCounter,
+    ) -> Result<bool, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        Ok(log_address.map_or(false, |log_address| {
+            is_own_address(&self.data_source_address, log_address)
+        }))
+    }
+
+    /// How many distinct contracts emitted logs in the current block.
+    pub(crate) fn block_unique_log_addresses(
+        &self,
+        log_addresses: &LogAddresses,
+        gas: &GasCounter,
+    ) -> Result<i32, HostExportError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        let log_addresses = log_addresses.get().map_err(HostExportError::Unknown)?;
+        Ok(log_addresses.len() as i32)
+    }
+
+    /// Removes duplicate strings, keeping the first occurrence of each.
+    pub(crate) fn array_dedup(
+        &self,
+        values: Vec<String>,
+        gas: &GasCounter,
+    ) -> Result<Vec<String>, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &values))?;
+        Ok(dedup_preserving_order(values))
+    }
+
+    /// Sorts `values` numerically and removes duplicates.
+    pub(crate) fn array_big_int_sorted_unique(
+        &self,
+        values: Vec<BigInt>,
+        gas: &GasCounter,
+    ) -> Result<Vec<BigInt>, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &values))?;
+        Ok(big_int_sorted_unique(values))
+    }
+
+    /// Drops the data sources that the current handler created so far, so that a handler that is
+    /// retried does not create them twice. Entity operations of the handler are kept.
+    pub(crate) fn data_source_reset_created(
+        &self,
+        state: &mut BlockState<C>,
+        gas: &GasCounter,
+    ) -> Result<(), DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        state.reset_handler_created_data_sources();
+        Ok(())
+    }
+
+    pub(crate) fn data_source_create(
+        &self,
+        logger: &Logger,
+        state: &mut BlockState<C>,
+        name: String,
+        params: Vec<String>,
+        context: Option<DataSourceContext>,
+        creation_block: BlockNumber,
+        gas: &GasCounter,
+    ) -> Result<(), HostExportError> {
+        gas.consume_host_fn(gas::CREATE_DATA_SOURCE)?;
+        info!(
+            logger,
+            "Create data source";
+            "name" => &name,
+            "params" => format!("{}", params.join(","))
+        );
+
+        // Resolve the name into the right template
+        let template = self
+            .templates
+            .iter()
+            .find(|template| template.name() == name)
+            .with_context(|| {
+                format!(
+                    "Failed to create data source from name `{}`: \
+                     No template with this name in parent data source `{}`. \
+                     Available names: {}.",
+                    name,
+                    self.data_source_name,
+                    self.templates
+                        .iter()
+                        .map(|template| template.name())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+            .map_err(DeterministicHostError::from)?
+            .clone();
+
+        // Remember that we need to create this data source
+        state.push_created_data_source(DataSourceTemplateInfo {
+            template,
+            params,
+            context,
+            creation_block,
+            creator_address: Some(self.data_source_address.clone())
+                .filter(|address| !address.is_empty()),
+        });
+
+        Ok(())
+    }
+
+    pub(crate) fn ens_name_by_hash(
+        &self,
+        hash: &str,
+        gas: &GasCounter,
+    ) -> Result<Option<String>, anyhow::Error> {
+        gas.consume_host_fn(gas::ENS_NAME_BY_HASH)?;
+        Ok(self.ens_lookup.find_name(hash)?)
+    }
+
+    pub(crate) fn is_ens_data_empty(&self) -> Result<bool, anyhow::Error> {
+        Ok(self.ens_lookup.is_table_empty()?)
+    }
+
+    pub(crate) fn log_log(
+        &self,
+        logger: &Logger,
+        level: slog::Level,
+        msg: String,
+        gas: &GasCounter,
+    ) -> Result<(), DeterministicHostError> {
+        gas.consume_host_fn(gas::LOG_OP.with_args(complexity::Size, &msg))?;
+
+        let rs = record_static!(level, self.data_source_name.as_str());
+
+        logger.log(&slog::Record::new(
+            &rs,
+            &format_args!("{}", msg),
+            b!("data_source" => &self.data_source_name),
+        ));
+
+        if level == slog::Level::Critical {
+            return Err(DeterministicHostError::from(anyhow!(
+                "Critical error logged in mapping"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Like `log_log`, but attaches `fields` to the log record so that log aggregation can
+    /// filter on them.
+    pub(crate) fn log_log_kv(
+        &self,
+        logger: &Logger,
+        level: slog::Level,
+        msg: String,
+        fields: HashMap<String, String>,
+        gas: &GasCounter,
+    ) -> Result<(), DeterministicHostError> {
+        gas.consume_host_fn(gas::LOG_OP.with_args(complexity::Size, &msg))?;
+        gas.consume_host_fn(gas::LOG_OP.with_args(complexity::Size, &fields))?;
+
+        let fields: BTreeMap<_, _> = fields.into_iter().collect();
+        log_kv(logger, level, &self.data_source_name, &msg, &fields);
+
+        if level == slog::Level::Critical {
+            return Err(DeterministicHostError::from(anyhow!(
+                "Critical error logged in mapping"
+            )));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn data_source_address(
+        &self,
+        gas: &GasCounter,
+    ) -> Result<Vec<u8>, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        Ok(self.data_source_address.clone())
+    }
+
+    /// The address of the data source that created this one from a template, or the data
+    /// source's own address if it was declared in the manifest.
+    pub(crate) fn data_source_creator_address(
+        &self,
+        gas: &GasCounter,
+    ) -> Result<Vec<u8>, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        Ok(self.data_source_creator_address.clone())
+    }
+
+    /// The creation ordinal of the data source among the instances of its template, or 0 for
+    /// static data sources.
+    pub(crate) fn data_source_instance_index(
+        &self,
+        gas: &GasCounter,
+    ) -> Result<i32, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        Ok(self.data_source_instance_index)
+    }
+
+    pub(crate) fn data_source_network(
+        &self,
+        gas: &GasCounter,
+    ) -> Result<String, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        Ok(self.subgraph_network.clone())
+    }
+
+    pub(crate) fn data_source_context(
+        &self,
+        gas: &GasCounter,
+    ) -> Result<Option<DataSourceContext>, DeterministicHostError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        Ok(self.data_source_context.as_ref().clone())
+    }
+
+    pub(crate) fn json_from_bytes(
+        &self,
+        bytes: &Vec<u8>,
+        gas: &GasCounter,
+    ) -> Result<serde_json::Value, DeterministicHostError> {
+        // Max JSON size is 10MB.
+        const MAX_JSON_SIZE: usize = 10_000_000;
+
+        gas.consume_host_fn(gas::JSON_FROM_BYTES.with_args(gas::complexity::Size, &bytes))?;
+
+        if bytes.len() > MAX_JSON_SIZE {
+            return Err(DeterministicHostError::Other(
+                anyhow!("JSON size exceeds max size of {}", MAX_JSON_SIZE).into(),
+            ));
+        }
+
+        serde_json::from_slice(bytes.as_slice())
+            .map_err(|e| DeterministicHostError::from(Error::from(e)))
+    }
+
+    pub(crate) fn string_to_h160(
+        &self,
+        string: &str,
+        gas: &GasCounter,
+    ) -> Result<H160, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &string))?;
+        string_to_h160(string)
+    }
+
+    pub(crate) fn hex_to_signed_big_int(
+        &self,
+        hex: &str,
+        width: u32,
+        gas: &GasCounter,
+    ) -> Result<BigInt, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &hex))?;
+        hex_to_signed_big_int(hex, width)
+    }
+
+    /// Formats `amount` of a token with `decimals` decimals, rounded to at most `precision`
+    /// fractional digits. Halves are rounded away from zero and trailing zeros are removed, so
+    /// `1_234_500` with 6 decimals is `"1.235"` at precision 3 and `"1.2345"` at precision 6.
+    pub(crate) fn format_token_amount(
+        &self,
+        amount: BigInt,
+        decimals: u8,
+        precision: u8,
+        gas: &GasCounter,
+    ) -> Result<String, DeterministicHostError> {
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(
+            complexity::Exponential,
+            (&amount, (decimals as f32).log2() as u8),
+        ))?;
+        format_token_amount(amount, decimals, precision)
+    }
+
+    pub(crate) fn address_normalize(
+        &self,
+        address: &str,
+        gas: &GasCounter,
+    ) -> Result<String, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &address))?;
+        normalize_address(address)
+    }
+
+    pub(crate) fn is_zero_address(
+        &self,
+        address: &[u8],
+        gas: &GasCounter,
+    ) -> Result<bool, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &address))?;
+        is_zero_address(address)
+    }
+
+    /// Checks whether `pattern` matches anywhere in `string`. Patterns use the syntax of the
+    /// `regex` crate, which has no backreferences or lookaround and matches in linear time; in
+    /// addition, patterns that are too long or compile to too large an automaton are rejected.
+    pub(crate) fn string_matches(
+        &self,
+        string: String,
+        pattern: String,
+        gas: &GasCounter,
+    ) -> Result<bool, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Mul, (&string, &pattern)))?;
+
+        let mut cache = self.regex_cache.lock().unwrap();
+        let regex = match cache.get(&pattern) {
+            Some(regex) => regex.clone(),
+            None => {
+                let regex = compile_safe_pattern(&pattern)?;
+                if cache.len() >= STRING_MATCHES_CACHE_SIZE {
+                    cache.clear();
+                }
+                cache.insert(pattern, regex.clone());
+                regex
+            }
+        };
+        Ok(regex.is_match(&string))
+    }
+
+    /// Compares two semantic versions by precedence, returning -1, 0 or 1. Build metadata is
+    /// ignored as the semver spec requires.
+    pub(crate) fn semver_compare(
+        &self,
+        x: String,
+        y: String,
+        gas: &GasCounter,
+    ) -> Result<i32, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Linear, (&x, &y)))?;
+        semver_compare(&x, &y)
+    }
+
+    pub(crate) fn bytes_to_string(
+        &self,
+        logger: &Logger,
+        bytes: Vec<u8>,
+        gas: &GasCounter,
+    ) -> Result<String, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &bytes))?;
+
+        Ok(bytes_to_string(logger, bytes))
+    }
+
+    /// Decodes `bytes` as UTF-16 with the given endianness. Unpaired surrogates are replaced
+    /// with U+FFFD, as invalid UTF-8 is in `bytes_to_string`, but an odd number of bytes can't be
+    /// UTF-16 and is an error.
+    pub(crate) fn bytes_to_string_utf16(
+        &self,
+        bytes: Vec<u8>,
+        little_endian: bool,
+        gas: &GasCounter,
+    ) -> Result<String, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &bytes))?;
+
+        bytes_to_string_utf16(&bytes, little_endian)
+    }
+
+    /// Decodes `bytes` as Latin-1 (ISO-8859-1). Every byte is a valid Latin-1 character, so this
+    /// never fails.
+    pub(crate) fn bytes_to_string_latin1(
+        &self,
+        bytes: Vec<u8>,
+        gas: &GasCounter,
+    ) -> Result<String, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &bytes))?;
+
+        Ok(bytes_to_string_latin1(&bytes))
+    }
+
+    /// Whether `bytes` is valid UTF-8, i.e., whether `bytes_to_string` would return it unchanged
+    /// instead of replacing invalid sequences.
+    pub(crate) fn bytes_is_valid_utf8(
+        &self,
+        bytes: Vec<u8>,
+        gas: &GasCounter,
+    ) -> Result<bool, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &bytes))?;
+
+        Ok(is_valid_utf8(&bytes))
+    }
+
+    /// The UTF-8 encoding of `string`, the inverse of `bytes_to_string`.
+    pub(crate) fn string_to_bytes(
+        &self,
+        string: String,
+        gas: &GasCounter,
+    ) -> Result<Vec<u8>, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &string))?;
+
+        Ok(string.into_bytes())
+    }
+
+    pub(crate) fn bytes_to_u32(
+        &self,
+        bytes: Vec<u8>,
+        little_endian: bool,
+        gas: &GasCounter,
+    ) -> Result<u32, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &bytes))?;
+
+        let word = four_bytes(&bytes)?;
+        Ok(if little_endian {
+            u32::from_le_bytes(word)
+        } else {
+            u32::from_be_bytes(word)
+        })
+    }
+
+    pub(crate) fn bytes_to_i32(
+        &self,
+        bytes: Vec<u8>,
+        little_endian: bool,
+        gas: &GasCounter,
+    ) -> Result<i32, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &bytes))?;
+
+        let word = four_bytes(&bytes)?;
+        Ok(if little_endian {
+            i32::from_le_bytes(word)
+        } else {
+            i32::from_be_bytes(word)
+        })
+    }
+
+    pub(crate) fn ethereum_encode(
+        &self,
+        token: Token,
+        gas: &GasCounter,
+    ) -> Result<Vec<u8>, DeterministicHostError> {
+        let encoded = encode(&[token]);
+
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &encoded))?;
+
+        Ok(encoded)
+    }
+
+    /// The raw output of the most recent contract call in this handler, or `None` if there
+    /// was no call or it reverted.
+    pub(crate) fn ethereum_last_return_data(
+        &self,
+        last_return_data: Option<Vec<u8>>,
+        gas: &GasCounter,
+    ) -> Result<Option<Vec<u8>>, DeterministicHostError> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &last_return_data))?;
+        Ok(last_return_data)
+    }
+
+    pub(crate) fn ethereum_encode_packed(
+        &self,
+        tokens: Vec<Token>,
+        gas: &GasCounter,
+    ) -> Result<Vec<u8>, DeterministicHostError> {
+        let encoded = encode_packed(&tokens)?;
+
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &encoded))?;
+
+        Ok(encoded)
+    }
+
+    pub(crate) fn ethereum_decode(
+        &self,
+        types: String,
+        data: Vec<u8>,
+        gas: &GasCounter,
+    ) -> Result<Token, anyhow::Error> {
+        gas.consume_host_fn(gas::DEFAULT_GAS_OP.with_args(complexity::Size, &data))?;
+
+        let param_types =
+            Reader::read(&types).map_err(|e| anyhow::anyhow!("Failed to read types: {}", e))?;
+
+        decode(&[param_types], &data)
+            // The `.pop().unwrap()` here is ok because we're always only passing one
+            // `param_types` to `decode`, so the returned `Vec` has always size of one.
+            // We can't do `tokens[0]` because the value can't be moved out of the `Vec`.
+            .map(|mut tokens| tokens.pop().unwrap())
+            .context("Failed to decode")
+    }
+}
+
+fn struct_hash(type_hash: &[u8], fields: &[Vec<u8>]) -> Result<[u8; 32], DeterministicHostError> {
+    if type_hash.len() != 32 {
+        return Err(DeterministicHostError::from(anyhow!(
+            "structHash expects the type hash to be 32 bytes long, but it is {} bytes long",
+            type_hash.len()
+        )));
+    }
+    if let Some((i, field)) = fields
+        .iter()
+        .enumerate()
+        .find(|(_, field)| field.len() != 32)
+    {
+        return Err(DeterministicHostError::from(anyhow!(
+            "structHash expects field {} to be 32 bytes long, but it is {} bytes long",
+            i,
+            field.len()
+        )));
+    }
+
+    let mut encoded = Vec::with_capacity(32 * (fields.len() + 1));
+    encoded.extend_from_slice(type_hash);
+    for field in fields {
+        encoded.extend_from_slice(field);
+    }
+    Ok(tiny_keccak::keccak256(&encoded))
+}
+
+fn ecrecover(
+    hash: &[u8],
+    v: i32,
+    r: &[u8],
+    s: &[u8],
+) -> Result<Option<H160>, DeterministicHostError> {
+    // Half of the order of the secp256k1 curve, big-endian
+    const HALF_ORDER: [u8; 32] = [
+        0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b,
+        0x20, 0xa0,
+    ];
+
+    for (name, value) in [("hash", hash), ("r", r), ("s", s)] {
+        if value.len() != 32 {
+            return Err(DeterministicHostError::from(anyhow!(
+                "ecrecover expects `{}` to be 32 bytes long, but it is {} bytes long",
+                name,
+                value.len()
+            )));
+        }
+    }
+
+    if !(v == 27 || v == 28) || s > &HALF_ORDER[..] {
+        return Ok(None);
+    }
+
+    let signature = [r, s].concat();
+    Ok(web3::signing::recover(hash, &signature, v - 27).ok())
+}
+
+/// Encodes `tokens` like Solidity's `abi.encodePacked`. Since a `Token` does not carry the bit
+/// width of integers, all integers are encoded as 32 bytes, i.e., as `uint256` or `int256`.
+/// Elements of arrays are padded to 32 bytes. Arrays of dynamic types, nested arrays and tuples
+/// have no packed encoding and are rejected.
+fn encode_packed(tokens: &[Token]) -> Result<Vec<u8>, DeterministicHostError> {
+    let mut encoded = Vec::new();
+    for token in tokens {
+        match token {
+            Token::Address(address) => encoded.extend_from_slice(address.as_bytes()),
+            Token::FixedBytes(bytes) | Token::Bytes(bytes) => encoded.extend_from_slice(bytes),
+            Token::String(s) => encoded.extend_from_slice(s.as_bytes()),
+            Token::Bool(b) => encoded.push(*b as u8),
+            Token::Int(_) | Token::Uint(_) => encoded.extend(encode(&[token.clone()])),
+            Token::Array(elements) | Token::FixedArray(elements) => {
+                for element in elements {
+                    match element {
+                        Token::Address(_)
+                        | Token::FixedBytes(_)
+                        | Token::Bool(_)
+                        | Token::Int(_)
+                        | Token::Uint(_) => encoded.extend(encode(&[element.clone()])),
+                        _ => {
+                            return Err(DeterministicHostError::from(anyhow!(
+                                "encodePacked does not support arrays of dynamic types or nested arrays"
+                            )))
+                        }
+                    }
+                }
+            }
+            Token::Tuple(_) => {
+                return Err(DeterministicHostError::from(anyhow!(
+                    "encodePacked does not support tuples"
+                )))
+            }
+        }
+    }
+    Ok(encoded)
+}
+
+fn json_as_array(
+    json: serde_json::Value,
+) -> Result<Vec<serde_json::Value>, DeterministicHostError> {
+    match json {
+        serde_json::Value::Array(values) => Ok(values),
+        other => Err(json_kind_mismatch("array", &other)),
+    }
+}
+
+fn json_as_object(
+    json: serde_json::Value,
+) -> Result<serde_json::Map<String, serde_json::Value>, DeterministicHostError> {
+    match json {
+        serde_json::Value::Object(object) => Ok(object),
+        other => Err(json_kind_mismatch("object", &other)),
+    }
+}
+
+fn json_as_bool(json: serde_json::Value) -> Result<bool, DeterministicHostError> {
+    match json {
+        serde_json::Value::Bool(b) => Ok(b),
+        other => Err(json_kind_mismatch("bool", &other)),
+    }
+}
+
+fn json_as_string(json: serde_json::Value) -> Result<String, DeterministicHostError> {
+    match json {
+        serde_json::Value::String(s) => Ok(s),
+        other => Err(json_kind_mismatch("string", &other)),
+    }
+}
+
+fn json_kind_mismatch(expected: &str, value: &serde_json::Value) -> DeterministicHostError {
+    let kind = match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "bool",
+        serde_json::Value::Number(_) => "number",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    };
+    DeterministicHostError::from(anyhow!(
+        "expected JSON {}, got {}: `{}`",
+        expected,
+        kind,
+        value
+    ))
+}
+
+/// Serializes the attributes of `entity` as a JSON object with its keys in sorted order.
+fn entity_to_json(entity: &Entity) -> Result<String, Error> {
+    let attributes: BTreeMap<&str, &store::Value> = entity.sorted_ref().into_iter().collect();
+    Ok(serde_json::to_string(&attributes)?)
+}
+
+fn json_reduce(
+    values: &[serde_json::Value],
+    initial: store::Value,
+    mut callback: impl FnMut(store::Value, &serde_json::Value) -> Result<store::Value, Error>,
+) -> Result<store::Value, Error> {
+    values
+        .iter()
+        .try_fold(initial, |accumulator, value| callback(accumulator, value))
+}
+
+fn json_to_finite_f64(json: &str) -> Result<f64, DeterministicHostError> {
+    let value = f64::from_str(json)
+        .with_context(|| format!("JSON `{}` cannot be parsed as f64", json))
+        .map_err(DeterministicHostError::from)?;
+    if !value.is_finite() {
+        return Err(DeterministicHostError::from(anyhow!(
+            "JSON `{}` is not a finite f64",
+            json
+        )));
+    }
+    Ok(value)
+}
+
+/// Parses the mantissa and the exponent separately so that the exponent can be checked against the
+/// limits of `BigDecimal` before the value is constructed.
+fn big_decimal_from_scientific(s: &str) -> Result<BigDecimal, DeterministicHostError> {
+    let invalid = || {
+        DeterministicHostError::from(anyhow!(
+            "string is not a BigDecimal in scientific notation: '{}'",
+            s
+        ))
+    };
+
+    let (mantissa, exp) = s.split_once(['e', 'E']).unwrap_or((s, "0"));
+    let mantissa = BigDecimal::from_str(mantissa).map_err(|_| invalid())?;
+    let exp = i32::from_str(exp)
+        .ok()
+        .filter(|exp| (BigDecimal::MIN_EXP..=BigDecimal::MAX_EXP).contains(exp))
+        .ok_or_else(invalid)?;
+
+    Ok(mantissa * BigDecimal::new(BigInt::from(1), exp as i64))
+}
+
+/// The longest pattern `string.matches` accepts
+const STRING_MATCHES_MAX_PATTERN_LEN: usize = 256;
+/// The maximum size in bytes of a pattern compiled for `string.matches`
+const STRING_MATCHES_SIZE_LIMIT: usize = 1 << 16;
+/// The number of compiled patterns each data source keeps around
+const STRING_MATCHES_CACHE_SIZE: usize = 64;
+
+fn compile_safe_pattern(pattern: &str) -> Result<regex::Regex, DeterministicHostError> {
+    if pattern.len() > STRING_MATCHES_MAX_PATTERN_LEN {
+        return Err(DeterministicHostError::from(anyhow!(
+            "pattern is {} bytes long but at most {} bytes are allowed",
+            pattern.len(),
+            STRING_MATCHES_MAX_PATTERN_LEN
+        )));
+    }
+    regex::RegexBuilder::new(pattern)
+        .size_limit(STRING_MATCHES_SIZE_LIMIT)
+        .dfa_size_limit(STRING_MATCHES_SIZE_LIMIT)
+        .build()
+        .map_err(|e| DeterministicHostError::from(anyhow!("invalid pattern `{}`: {}", pattern, e)))
+}
+
+fn semver_compare(x: &str, y: &str) -> Result<i32, DeterministicHostError> {
+    let parse = |version: &str| {
+        Version::parse(version).map_err(|e| {
+            DeterministicHostError::from(anyhow!("invalid semantic version `{}`: {}", version, e))
+        })
+    };
+    let (x, y) = (parse(x)?, parse(y)?);
+
+    // `Version` also orders by build metadata, which does not affect precedence
+    let ordering = (x.major, x.minor, x.patch, &x.pre).cmp(&(y.major, y.minor, y.patch, &y.pre));
+    Ok(ordering as i32)
+}
+
+fn format_token_amount(
+    amount: BigInt,
+    decimals: u8,
+    precision: u8,
+) -> Result<String, DeterministicHostError> {
+    // Digits past `decimals` would all be zero and get trimmed anyway
+    let precision = precision.min(decimals);
+    let zero = BigInt::from(0);
+    let negative = amount < zero;
+    let magnitude = if negative {
+        zero.clone() - amount
+    } else {
+        amount
+    };
+
+    let divisor = BigInt::from(10).pow(decimals - precision)?;
+    let rounded = (magnitude + divisor.clone() / BigInt::from(2)) / divisor;
+
+    let digits = format!("{:0>1$}", rounded.to_string(), precision as usize + 1);
+    let (int, frac) = digits.split_at(digits.len() - precision as usize);
+    let frac = frac.trim_end_matches('0');
+    // Amounts that round to zero are never shown as `-0`
+    let sign = if negative && rounded != zero { "-" } else { "" };
+    Ok(if frac.is_empty() {
+        format!("{}{}", sign, int)
+    } else {
+        format!("{}{}.{}", sign, int, frac)
+    })
+}
+
+/// Interprets a hex string, with or without the `0x` prefix, as a two's complement integer that
+/// is `width` bits wide, like the `int<width>` types of Solidity. Shorter strings are padded with
+/// zeros on the left; `width` must be a multiple of 8 of at most 256.
+fn hex_to_signed_big_int(hex: &str, width: u32) -> Result<BigInt, DeterministicHostError> {
+    if width == 0 || width % 8 != 0 || width > 256 {
+        return Err(DeterministicHostError::from(anyhow!(
+            "Invalid width {} for a signed integer: must be a multiple of 8 between 8 and 256",
+            width
+        )));
+    }
+
+    let digits = hex.strip_prefix("0x").unwrap_or(hex);
+    let width_in_digits = (width / 4) as usize;
+    if digits.len() > width_in_digits {
+        return Err(DeterministicHostError::from(anyhow!(
+            "Hex string `{}` does not fit into {} bits",
+            hex,
+            width
+        )));
+    }
+
+    let padded = format!("{:0>1$}", digits, width_in_digits);
+    let mut bytes = ::hex::decode(&padded)
+        .with_context(|| format!("Invalid hex string `{}`", hex))
+        .map_err(DeterministicHostError::from)?;
+    bytes.reverse();
+    Ok(BigInt::from_signed_bytes_le(&bytes)?)
+}
+
+fn string_to_h160(string: &str) -> Result<H160, DeterministicHostError> {
+    // `H160::from_str` takes a hex string with no leading `0x`.
+    let s = string.trim_start_matches("0x");
+    H160::from_str(s)
+        .with_context(|| format!("Failed to convert string to Address/H160: '{}'", s))
+        .map_err(DeterministicHostError::from)
+}
+
+/// Validates a hex address, with or without the `0x` prefix, and returns its lowercase form
+/// prefixed with `0x`. Mixed-case input is treated as checksummed and must pass the EIP-55 check.
+fn normalize_address(address: &str) -> Result<String, DeterministicHostError> {
+    let hex = address.strip_prefix("0x").unwrap_or(address);
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(DeterministicHostError::from(anyhow!(
+            "Invalid address `{}`: expected 20 hex-encoded bytes",
+            address
+        )));
+    }
+
+    let lowercase = hex.to_ascii_lowercase();
+    if hex != lowercase && hex != hex.to_ascii_uppercase() {
+        let hash = tiny_keccak::keccak256(lowercase.as_bytes());
+        for (i, c) in hex.chars().enumerate() {
+            let nibble = match i % 2 {
+                0 => hash[i / 2] >> 4,
+                _ => hash[i / 2] & 0x0f,
+            };
+            if c.is_ascii_alphabetic() && c.is_ascii_uppercase() != (nibble >= 8) {
+                return Err(DeterministicHostError::from(anyhow!(
+                    "Invalid address checksum: `{}`",
+                    address
+                )));
+            }
+        }
+    }
+
+    Ok(format!("0x{}", lowercase))
+}
+
+fn is_zero_address(address: &[u8]) -> Result<bool, DeterministicHostError> {
+    if address.len() != H160::len_bytes() {
+        return Err(DeterministicHostError::from(anyhow!(
+            "expected an address of {} bytes but got {} bytes",
+            H160::len_bytes(),
+            address.len()
+        )));
+    }
+    Ok(address.iter().all(|byte| *byte == 0))
+}
+
+/// The standard Ethereum bloom membership test: the low 11 bits of each of the first three
+/// byte pairs of `keccak256(value)` select a bit that must be set in the bloom.
+fn bloom_contains(bloom: &[u8], value: &[u8]) -> Result<bool, DeterministicHostError> {
+    const BLOOM_SIZE: usize = 256;
+
+    if bloom.len() != BLOOM_SIZE {
+        return Err(DeterministicHostError::from(anyhow!(
+            "logs bloom must be {} bytes long, but got {} bytes",
+            BLOOM_SIZE,
+            bloom.len()
+        )));
+    }
+
+    let hash = tiny_keccak::keccak256(value);
+    Ok(hash[..6].chunks(2).all(|pair| {
+        let bit = (((pair[0] as usize) << 8) | pair[1] as usize) & (BLOOM_SIZE * 8 - 1);
+        bloom[BLOOM_SIZE - 1 - bit / 8] & (1 << (bit % 8)) != 0
+    }))
+}
+
+fn schema_entity_types(schema: &InputSchema) -> Vec<String> {
+    schema
+        .get_object_type_definitions()
+        .into_iter()
+        .filter(|obj_type| obj_type.name != SCHEMA_TYPE_NAME)
+        .map(|obj_type| obj_type.name.clone())
+        .collect()
+}
+
+fn schema_field_type(
+    schema: &InputSchema,
+    entity_type: &str,
+    field: &str,
+) -> Result<String, DeterministicHostError> {
+    let object_type = schema
+        .find_object_type(&EntityType::new(entity_type.to_string()))
+        .ok_or_else(|| {
+            DeterministicHostError::from(anyhow!(
+                "entity type `{}` is not declared in the schema",
+                entity_type
+            ))
+        })?;
+    let field = object_type
+        .fields
+        .iter()
+        .find(|f| f.name == field)
+        .ok_or_else(|| {
+            DeterministicHostError::from(anyhow!(
+                "entity type `{}` has no field `{}`",
+                entity_type,
+                field
+            ))
+        })?;
+    Ok(field.field_type.to_string())
+}
+
+fn is_unfinalized(
+    block_number: BlockNumber,
+    chain_head: Option<BlockNumber>,
+    reorg_threshold: BlockNumber,
+) -> bool {
+    chain_head.map_or(true, |head| {
+        block_number > head.saturating_sub(reorg_threshold)
+    })
+}
+
+fn is_backfilling(
+    block_number: BlockNumber,
+    chain_head: Option<BlockNumber>,
+    threshold: BlockNumber,
+) -> bool {
+    chain_head.map_or(false, |head| head.saturating_sub(block_number) > threshold)
+}
+
+fn block_confirmations(block_number: BlockNumber, chain_head: Option<BlockNumber>) -> BigInt {
+    let confirmations = chain_head.map_or(0, |head| head.saturating_sub(block_number).max(0));
+    BigInt::from(confirmations)
+}
+
+async fn cat_with_timeout(
+    link_resolver: &dyn LinkResolver,
+    logger: &Logger,
+    link: &Link,
+    timeout: Option<Duration>,
+) -> Result<Vec<u8>, anyhow::Error> {
+    match timeout {
+        None => link_resolver.cat(logger, link).await,
+        Some(timeout) => tokio::time::timeout(timeout, link_resolver.cat(logger, link))
+            .await
+            .map_err(|_| anyhow!("timed out after {}ms", timeout.as_millis()))?,
+    }
+}
+
+/// Log `msg` with the `fields` attached to the record as a JSON object under the `fields` key.
+/// slog only accepts `&'static str` keys, so mapping-defined keys can't be record keys.
+fn log_kv(
+    logger: &Logger,
+    level: slog::Level,
+    data_source: &str,
+    msg: &str,
+    fields: &BTreeMap<String, String>,
+) {
+    let rs = record_static!(level, data_source);
+    let fields = serde_json::json!(fields).to_string();
+
+    logger.log(&slog::Record::new(
+        &rs,
+        &format_args!("{}", msg),
+        b!("data_source" => data_source, "fields" => fields),
+    ));
+}
+
+fn is_own_address(data_source_address: &[u8], log_address: &[u8]) -> bool {
+    !data_source_address.is_empty() && data_source_address == log_address
+}
+
+fn event_params_map(params: &[LogParam]) -> Vec<(String, Token)> {
+    params
+        .iter()
+        .enumerate()
+        .map(|(i, param)| {
+            let name = if param.name.is_empty() {
+                i.to_string()
+            } else {
+                param.name.clone()
+            };
+            (name, param.value.clone())
+        })
+        .collect()
+}
+
+fn dedup_preserving_order(values: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    values
+        .into_iter()
+        .filter(|value| seen.insert(value.clone()))
+        .collect()
+}
+
+/// The largest term `bigInt.linearRecurrence` computes
+const LINEAR_RECURRENCE_MAX_TERM: u32 = 10_000;
+
+fn big_int_linear_recurrence(
+    coefficients: Vec<BigInt>,
+    seeds: Vec<BigInt>,
+    n: u32,
+    gas: &GasCounter,
+) -> Result<BigInt, DeterministicHostError> {
+    if coefficients.is_empty() || coefficients.len() != seeds.len() {
+        return Err(DeterministicHostError::from(anyhow!(
+            "linearRecurrence expects as many coefficients as seeds and at least one of each, \
+             but got {} coefficients and {} seeds",
+            coefficients.len(),
+            seeds.len()
+        )));
+    }
+    if n > LINEAR_RECURRENCE_MAX_TERM {
+        return Err(DeterministicHostError::from(anyhow!(
+            "linearRecurrence computes at most term {}, but term {} was requested",
+            LINEAR_RECURRENCE_MAX_TERM,
+            n
+        )));
+    }
+
+    let n = n as usize;
+    if let Some(seed) = seeds.get(n) {
+        return Ok(seed.clone());
+    }
+
+    // The last `d` terms, oldest first
+    let mut terms = VecDeque::from(seeds);
+    for _ in terms.len()..=n {
+        // The terms can grow with every step, so each one is charged for the size of the terms
+        // it is computed from
+        gas.consume_host_fn(
+            gas::BIG_MATH_GAS_OP
+                .with_args(complexity::Mul, (&coefficients, &*terms.make_contiguous())),
+        )?;
+        let next = coefficients
+            .iter()
+            .zip(terms.iter().rev())
+            .fold(BigInt::from(0), |sum, (coefficient, term)| {
+                sum + coefficient.clone() * term.clone()
+            });
+        // Stop as soon as the terms get too big to be used anywhere else
+        if next.bits() >= BigInt::MAX_BITS as usize {
+            return Err(DeterministicHostError::from(anyhow!(
+                "linearRecurrence terms exceed the maximum BigInt size of {} bits",
+                BigInt::MAX_BITS
+            )));
+        }
+        terms.pop_front();
+        terms.push_back(next);
+    }
+    Ok(terms.pop_back().unwrap())
+}
+
+fn big_int_sorted_unique(mut values: Vec<BigInt>) -> Vec<BigInt> {
+    values.sort();
+    values.dedup();
+    values
+}
+
+fn big_int_abs_diff(x: BigInt, y: BigInt) -> BigInt {
+    if x >= y {
+        x - y
+    } else {
+        y - x
+    }
+}
+
+fn big_int_next_power_of_two(x: BigInt) -> Result<BigInt, DeterministicHostError> {
+    if x <= BigInt::from(0) {
+        return Err(DeterministicHostError::from(anyhow!(
+            "attempted to compute the next power of two of non-positive BigInt `{}`",
+            x
+        )));
+    }
+
+    // A power of two has no bits in common with its predecessor.
+    if x.clone() & (x.clone() - BigInt::from(1)) == BigInt::from(0) {
+        return Ok(x);
+    }
+
+    let bits = x.bits();
+    let mut bytes = vec![0u8; bits / 8 + 1];
+    bytes[bits / 8] = 1 << (bits % 8);
+    BigInt::from_unsigned_bytes_le(&bytes).map_err(DeterministicHostError::from)
+}
+
+fn rescale_amount(
+    amount: BigInt,
+    from_decimals: u8,
+    to_decimals: u8,
+) -> Result<BigInt, DeterministicHostError> {
+    if to_decimals >= from_decimals {
+        let factor = BigInt::from(10).pow(to_decimals - from_decimals)?;
+        return Ok(amount * factor);
+    }
+
+    let factor = BigInt::from(10).pow(from_decimals - to_decimals)?;
+    // `/` and `%` truncate towards zero, the remainder has the sign of `amount`.
+    let quotient = amount.clone() / factor.clone();
+    let remainder = amount % factor.clone();
+    let twice = remainder * BigInt::from(2);
+    if twice >= factor {
+        Ok(quotient + BigInt::from(1))
+    } else if twice <= BigInt::from(0) - factor {
+        Ok(quotient - BigInt::from(1))
+    } else {
+        Ok(quotient)
+    }
+}
+
+fn timestamp_bucket(timestamp: BigInt, bucket_size: u32) -> Result<BigInt, DeterministicHostError> {
+    if bucket_size == 0 {
+        return Err(DeterministicHostError::from(anyhow!(
+            "attempted to compute the bucket of timestamp `{}` with a bucket size of zero",
+            timestamp
+        )));
+    }
+
+    let bucket_size = BigInt::from(bucket_size as u64);
+    let mut offset = timestamp.clone() % bucket_size.clone();
+    // `%` truncates towards zero, round timestamps before the epoch down as well.
+    if offset < 0.into() {
+        offset = offset + bucket_size;
+    }
+    Ok(timestamp - offset)
+}
+
+fn big_decimal_div_or(x: BigDecimal, y: BigDecimal, default: BigDecimal) -> BigDecimal {
+    if y == BigDecimal::zero() {
+        default
+    } else {
+        x / y
+    }
+}
+
+fn big_decimal_to_string_trimmed(x: &BigDecimal) -> String {
+    let s = x.to_string();
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        s
+    }
+}
+
+fn big_int_to_big_decimal(x: BigInt) -> BigDecimal {
+    BigDecimal::new(x, 0)
+}
+
+fn big_decimal_mod(x: BigDecimal, y: BigDecimal) -> Result<BigDecimal, DeterministicHostError> {
+    if y == BigDecimal::zero() {
+        return Err(DeterministicHostError::from(anyhow!(
+            "attempted to calculate the remainder of BigDecimal `{}` with a divisor of zero",
+            x
+        )));
+    }
+
+    // Bring both operands to the same scale, the remainder of their digits is then the digits
+    // of the remainder at that scale
+    let (x_digits, x_scale) = x.as_bigint_and_exponent();
+    let (y_digits, y_scale) = y.as_bigint_and_exponent();
+    let scale = x_scale.max(y_scale);
+    let (x_digits, _) = bigdecimal::BigDecimal::new(x_digits, x_scale)
+        .with_scale(scale)
+        .as_bigint_and_exponent();
+    let (y_digits, _) = bigdecimal::BigDecimal::new(y_digits, y_scale)
+        .with_scale(scale)
+        .as_bigint_and_exponent();
+    Ok(BigDecimal::from(bigdecimal::BigDecimal::new(
+        x_digits % y_digits,
+        scale,
+    )))
+}
+
+fn big_decimal_inverse(x: BigDecimal) -> Result<BigDecimal, DeterministicHostError> {
+    if x == BigDecimal::zero() {
+        return Err(DeterministicHostError::from(anyhow!(
+            "attempted to compute the inverse of BigDecimal zero"
+        )));
+    }
+    Ok(BigDecimal::from(1) / x)
+}
+
+fn big_decimal_price_impact(
+    reserve_in: BigDecimal,
+    reserve_out: BigDecimal,
+    amount_in: BigDecimal,
+) -> Result<BigDecimal, DeterministicHostError> {
+    if reserve_in <= BigDecimal::zero() || reserve_out <= BigDecimal::zero() {
+        return Err(DeterministicHostError::from(anyhow!(
+            "attempted to compute the price impact with reserves `{}` and `{}`, \
+             but reserves must be positive",
+            reserve_in,
+            reserve_out
+        )));
+    }
+    if amount_in < BigDecimal::zero() {
+        return Err(DeterministicHostError::from(anyhow!(
+            "attempted to compute the price impact of negative amount `{}`",
+            amount_in
+        )));
+    }
+
+    // The spot price is `reserve_out / reserve_in` and the execution price is
+    // `amount_out / amount_in` with `amount_out = reserve_out * amount_in / (reserve_in +
+    // amount_in)`. The impact `1 - execution / spot` therefore does not depend on `reserve_out`
+    let total_in = reserve_in + amount_in.clone();
+    Ok(amount_in / total_in)
+}
+
+fn big_decimal_to_fixed(x: &BigDecimal, decimals: u8) -> Result<BigInt, DeterministicHostError> {
+    // Work with the exact value since `BigDecimal` operations round to 34 digits
+    let (digits, scale) = x.as_bigint_and_exponent();
+    let scaled = bigdecimal::BigDecimal::new(digits, scale - decimals as i64);
+    let half = bigdecimal::BigDecimal::new(5.into(), 1);
+    let rounded = if scaled < bigdecimal::BigDecimal::from(0) {
+        scaled - half
+    } else {
+        scaled + half
+    };
+    // Reducing the scale truncates towards zero
+    let (int, _) = rounded.with_scale(0).as_bigint_and_exponent();
+    BigInt::new(int).map_err(DeterministicHostError::from)
+}
+
+fn big_decimal_sum(values: Vec<BigDecimal>) -> BigDecimal {
+    // Adding `BigDecimal`s rounds after every step; accumulate the exact values instead
+    let total = values
+        .iter()
+        .map(|value| {
+            let (digits, scale) = value.as_bigint_and_exponent();
+            bigdecimal::BigDecimal::new(digits, scale)
+        })
+        .fold(bigdecimal::BigDecimal::from(0), |total, value| {
+            total + value
+        });
+    BigDecimal::from(total)
+}
+
+/// The largest number of periods `bigDecimal.compound` compounds over
+const COMPOUND_MAX_PERIODS: u32 = 1_000_000_000;
+
+fn big_decimal_compound(
+    principal: BigDecimal,
+    rate: BigDecimal,
+    periods: u32,
+    gas: &GasCounter,
+) -> Result<BigDecimal, DeterministicHostError> {
+    if periods > COMPOUND_MAX_PERIODS {
+        return Err(DeterministicHostError::from(anyhow!(
+            "compound takes at most {} periods, but got {}",
+            COMPOUND_MAX_PERIODS,
+            periods
+        )));
+    }
+
+    // Every multiplication is charged for the size of its operands
+    let mul = |x: BigDecimal, y: BigDecimal| -> Result<BigDecimal, DeterministicHostError> {
+        gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Mul, (&x, &y)))?;
+        Ok(x * y)
+    };
+
+    // Exponentiation by squaring, so that this takes at most 60 multiplications. Every
+    // multiplication rounds to the standard precision, which keeps the operands small
+    let mut base = BigDecimal::from(1) + rate;
+    let mut factor = BigDecimal::from(1);
+    let mut periods = periods;
+    while periods > 0 {
+        if periods & 1 == 1 {
+            factor = mul(factor, base.clone())?;
+        }
+        periods >>= 1;
+        if periods > 0 {
+            base = mul(base.clone(), base)?;
+        }
+    }
+    mul(principal, factor)
+}
+
+fn big_decimal_geomean(
+    values: Vec<BigDecimal>,
+    gas: &GasCounter,
+) -> Result<BigDecimal, DeterministicHostError> {
+    let n = u32::try_from(values.len())
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| {
+            DeterministicHostError::from(anyhow!(
+                "Geometric mean needs between 1 and {} values, but got {}",
+                u32::MAX,
+                values.len()
+            ))
+        })?;
+    let zero = BigDecimal::zero();
+    let mut product = bigdecimal::BigDecimal::from(1);
+    for (i, value) in values.iter().enumerate() {
+        if *value <= zero {
+            return Err(DeterministicHostError::from(anyhow!(
+                "Geometric mean needs positive values, but got {}",
+                value
+            )));
+        }
+        // The product is at most as large as the values multiplied into it so far, and grows
+        // with each of them, so every multiplication is charged for that size
+        gas.consume_host_fn(
+            gas::BIG_MATH_GAS_OP.with_args(complexity::Mul, (&values[..i], value)),
+        )?;
+        let (digits, scale) = value.as_bigint_and_exponent();
+        product = product * bigdecimal::BigDecimal::new(digits, scale);
+    }
+    // The root of the product costs about as much as squaring it
+    gas.consume_host_fn(gas::BIG_MATH_GAS_OP.with_args(complexity::Mul, (&values, &values)))?;
+    Ok(big_decimal_nth_root(&product, n))
+}
+
+/// The `n`-th root of the positive `x`, rounded to the usual precision.
+fn big_decimal_nth_root(x: &bigdecimal::BigDecimal, n: u32) -> BigDecimal {
+    // Take the integer root of `x` scaled up so that the root has a few digits more than the
+    // precision we keep; truncating the integer root then doesn't affect the rounded result
+    let n = i64::from(n);
+    let (digits, scale) = x.as_bigint_and_exponent();
+    let root_digits = i64::from(BigDecimal::MAX_SIGNFICANT_DIGITS) + 6;
+    let mut shift = (root_digits * n - x.digits() as i64).max(0);
+    // The scale of the radicand has to be a multiple of `n`
+    shift += (-(scale + shift)).rem_euclid(n);
+    let (radicand, _) = bigdecimal::BigDecimal::new(digits, scale)
+        .with_scale(scale + shift)
+        .as_bigint_and_exponent();
+    BigDecimal::from(bigdecimal::BigDecimal::new(
+        radicand.nth_root(n as u32),
+        (scale + shift) / n,
+    ))
+}
+
+/// The population variance of `values`, not yet rounded so that taking its square root
+/// doesn't compound the rounding error.
+fn population_variance(
+    values: Vec<BigDecimal>,
+) -> Result<bigdecimal::BigDecimal, DeterministicHostError> {
+    if values.is_empty() {
+        return Err(DeterministicHostError::from(anyhow!(
+            "Variance needs at least one value"
+        )));
+    }
+
+    // Every step rounds to standard precision like the other `BigDecimal` operations, so that
+    // values of very different magnitudes can't blow up the size of the sums. Summing squared
+    // deviations from the mean keeps the variance from going negative through that rounding
+    let n = BigDecimal::from(values.len() as u64);
+    let mean = values
+        .iter()
+        .cloned()
+        .fold(BigDecimal::zero(), |sum, value| sum + value)
+        / n.clone();
+    let sum_of_squares = values.into_iter().fold(BigDecimal::zero(), |sum, value| {
+        let deviation = value - mean.clone();
+        sum + deviation.clone() * deviation
+    });
+
+    let exact = |x: BigDecimal| {
+        let (digits, scale) = x.as_bigint_and_exponent();
+        bigdecimal::BigDecimal::new(digits, scale)
+    };
+    Ok(exact(sum_of_squares) / exact(n))
+}
+
+fn big_decimal_weighted_median(
+    values: Vec<BigDecimal>,
+    weights: Vec<BigDecimal>,
+) -> Result<BigDecimal, DeterministicHostError> {
+    if values.len() != weights.len() {
+        return Err(DeterministicHostError::from(anyhow!(
+            "Weighted median needs one weight per value, but got {} values and {} weights",
+            values.len(),
+            weights.len()
+        )));
+    }
+
+    // Weights are summed up exactly so that comparing against half the
+    // total weight is not thrown off by rounding
+    let exact = |x: &BigDecimal| {
+        let (digits, scale) = x.as_bigint_and_exponent();
+        bigdecimal::BigDecimal::new(digits, scale)
+    };
+    let zero = bigdecimal::BigDecimal::from(0);
+    let mut samples = Vec::with_capacity(values.len());
+    for (value, weight) in values.into_iter().zip(weights) {
+        let weight = exact(&weight);
+        if weight < zero {
+            return Err(DeterministicHostError::from(anyhow!(
+                "Weighted median needs non-negative weights, but got {}",
+                weight
+            )));
+        }
+        // Values without weight can never be the median
+        if weight > zero {
+            samples.push((value, weight));
+        }
+    }
+    if samples.is_empty() {
+        return Err(DeterministicHostError::from(anyhow!(
+            "Weighted median needs a positive total weight"
+        )));
+    }
+
+    samples.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let total = samples
+        .iter()
+        .fold(zero.clone(), |total, (_, weight)| total + weight);
+    let two = bigdecimal::BigDecimal::from(2);
+    let mut cumulative = zero;
+    for (i, (value, weight)) in samples.iter().enumerate() {
+        cumulative = cumulative + weight;
+        match (&cumulative * &two).cmp(&total) {
+            Ordering::Less => {}
+            Ordering::Equal => {
+                // Since the total weight is positive, this is never the last sample
+                let next = samples[i + 1].0.clone();
+                return Ok((value.clone() + next) / BigDecimal::from(2));
+            }
+            Ordering::Greater => return Ok(value.clone()),
+        }
+    }
+    unreachable!("the cumulative weight of all samples exceeds half the total weight")
+}
+
+fn big_decimal_approx_equals(
+    x: BigDecimal,
+    y: BigDecimal,
+    tolerance: BigDecimal,
+) -> Result<bool, DeterministicHostError> {
+    if tolerance < BigDecimal::zero() {
+        return Err(DeterministicHostError::from(anyhow!(
+            "attempted to compare BigDecimals with a negative tolerance `{}`",
+            tolerance
+        )));
+    }
+
+    let diff = if x >= y { x - y } else { y - x };
+    Ok(diff <= tolerance)
+}
+
+fn big_decimal_lerp(a: BigDecimal, b: BigDecimal, t: BigDecimal) -> BigDecimal {
+    a.clone() + (b - a) * t
+}
+
+fn twap_update(
+    accumulator: BigDecimal,
+    price: BigDecimal,
+    elapsed: BigInt,
+) -> Result<BigDecimal, DeterministicHostError> {
+    if elapsed < BigInt::from(0) {
+        return Err(DeterministicHostError::from(anyhow!(
+            "elapsed time for a TWAP update must not be negative, but is {}",
+            elapsed
+        )));
+    }
+    Ok(accumulator + price * BigDecimal::new(elapsed, 0))
+}
+
+fn bytes_to_string(logger: &Logger, bytes: Vec<u8>) -> String {
+    let s = String::from_utf8_lossy(&bytes);
+
+    // If the string was re-allocated, that means it was not UTF8.
+    if matches!(s, std::borrow::Cow::Owned(_)) {
+        warn!(
+            logger,
+            "Bytes contain invalid UTF8. This may be caused by attempting \
+            to convert a value such as an address that cannot be parsed to a unicode string. \
+            You may want to use 'toHexString()' instead. String (truncated to 1024 chars): '{}'",
+            &s.chars().take(1024).collect::<String>(),
+        )
+    }
+
+    // The string may have been encoded in a fixed length buffer and padded with null
+    // characters, so trim trailing nulls.
+    s.trim_end_matches('\u{0000}').to_string()
+}
+
+fn bytes_to_string_utf16(
+    bytes: &[u8],
+    little_endian: bool,
+) -> Result<String, DeterministicHostError> {
+    if bytes.len() % 2 != 0 {
+        return Err(DeterministicHostError::from(anyhow!(
+            "UTF-16 needs an even number of bytes, but got {}",
+            bytes.len()
+        )));
+    }
+    let units = bytes.chunks_exact(2).map(|unit| {
+        let unit = [unit[0], unit[1]];
+        if little_endian {
+            u16::from_le_bytes(unit)
+        } else {
+            u16::from_be_bytes(unit)
+        }
+    });
+    let s: String = char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect();
+
+    // Trim null padding, like `bytes_to_string` does
+    Ok(s.trim_end_matches('\u{0000}').to_string())
+}
+
+fn bytes_to_string_latin1(bytes: &[u8]) -> String {
+    // Latin-1 is exactly the first 256 Unicode code points
+    let s: String = bytes.iter().map(|b| char::from(*b)).collect();
+    s.trim_end_matches('\u{0000}').to_string()
+}
+
+fn is_valid_utf8(bytes: &[u8]) -> bool {
+    std::str::from_utf8(bytes).is_ok()
+}
+
+fn four_bytes(bytes: &[u8]) -> Result<[u8; 4], DeterministicHostError> {
+    <[u8; 4]>::try_from(bytes).map_err(|_| {
+        DeterministicHostError::from(anyhow!(
+            "expected exactly 4 bytes for a 32-bit integer, but got {}",
+            bytes.len()
+        ))
+    })
+}
+
+/// Expose some host functions for testing only
+#[cfg(debug_assertions)]
+pub mod test_support {
+    use std::{borrow::Cow, collections::HashMap, sync::Arc};
+
+    use graph::{
+        blockchain::Blockchain,
+        components::{store::GetScope, subgraph::SharedProofOfIndexing},
+        data::value::Word,
+        prelude::{BlockState, Entity, StopwatchMetrics, Value},
+        runtime::{gas::GasCounter, HostExportError},
+        slog::Logger,
+    };
+
+    use crate::MappingContext;
+
+    pub struct HostExports<C: Blockchain>(Arc<super::HostExports<C>>);
+
+    impl<C: Blockchain> HostExports<C> {
+        pub fn new(ctx: &MappingContext<C>) -> Self {
+            HostExports(ctx.host_exports.clone())
+        }
+
+        pub fn store_set(
+            &self,
+            logger: &Logger,
+            state: &mut BlockState<C>,
             proof_of_indexing: &SharedProofOfIndexing,
             entity_type: String,
             entity_id: String,
@@ -944,44 +3087,1231 @@ pub mod test_support {
             )
         }
 
-        pub fn store_get<'a>(
-            &self,
-            state: &'a mut BlockState<C>,
-            entity_type: String,
-            entity_id: String,
-            gas: &GasCounter,
-        ) -> Result<Option<Cow<'a, Entity>>, anyhow::Error> {
-            self.0
-                .store_get(state, entity_type, entity_id, gas, GetScope::Store)
-        }
+        pub fn store_get<'a>(
+            &self,
+            state: &'a mut BlockState<C>,
+            entity_type: String,
+            entity_id: String,
+            gas: &GasCounter,
+        ) -> Result<Option<Cow<'a, Entity>>, anyhow::Error> {
+            self.0
+                .store_get(state, entity_type, entity_id, gas, GetScope::Store)
+        }
+    }
+}
+
+#[test]
+fn struct_hash_matches_eip712_example() {
+    // The `Mail` struct from the example in EIP-712
+    let type_hash =
+        ::hex::decode("a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2").unwrap();
+    let from =
+        ::hex::decode("fc71e5fa27ff56c350aa531bc129ebdf613b772b6604664f5d8dbe21b85eb0c8").unwrap();
+    let to =
+        ::hex::decode("cd54f074a4af31b4411ff6a60c9719dbd559c221c8ac3492d9d872b041d703d1").unwrap();
+    let contents = tiny_keccak::keccak256(b"Hello, Bob!").to_vec();
+
+    let hash = struct_hash(&type_hash, &[from.clone(), to, contents]).unwrap();
+    assert_eq!(
+        "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e",
+        ::hex::encode(hash)
+    );
+
+    // A struct without fields hashes just the type hash
+    assert_eq!(
+        tiny_keccak::keccak256(&type_hash),
+        struct_hash(&type_hash, &[]).unwrap()
+    );
+
+    assert!(struct_hash(&type_hash, &[from[1..].to_vec()]).is_err());
+    assert!(struct_hash(&type_hash[1..], &[from]).is_err());
+}
+
+#[test]
+fn ecrecover_recovers_signer() {
+    let hash =
+        ::hex::decode("1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8").unwrap();
+    let r =
+        ::hex::decode("a0de78b10a2aaf474a5e41d74aab7b0b93f474c4828f763e46acbf5ead43160d").unwrap();
+    let s =
+        ::hex::decode("2fae942c522828f0024fdc4d64329c268c71150330533613fe253150cfd07e49").unwrap();
+    let signer = string_to_h160("0x2c7536E3605D9C16a7a3D7b1898e529396a65c23").unwrap();
+
+    assert_eq!(Some(signer), ecrecover(&hash, 27, &r, &s).unwrap());
+    // The wrong recovery id yields a different address
+    assert_ne!(Some(signer), ecrecover(&hash, 28, &r, &s).unwrap());
+
+    // Invalid `v`
+    for v in [0, 1, 26, 29, -27] {
+        assert_eq!(None, ecrecover(&hash, v, &r, &s).unwrap());
+    }
+
+    // The malleable twin `(28, r, n - s)` of the signature is rejected
+    let high_s =
+        ::hex::decode("d0516bd3add7d70ffdb023b29bcd63d82e3dc7e37ef56a27c1ad2d3c0065c2f8").unwrap();
+    assert_eq!(None, ecrecover(&hash, 28, &r, &high_s).unwrap());
+
+    // A zero signature does not recover anything
+    assert_eq!(None, ecrecover(&hash, 27, &[0; 32], &[0; 32]).unwrap());
+
+    assert!(ecrecover(&hash[1..], 27, &r, &s).is_err());
+}
+
+#[test]
+fn test_string_to_h160_with_0x() {
+    assert_eq!(
+        H160::from_str("A16081F360e3847006dB660bae1c6d1b2e17eC2A").unwrap(),
+        string_to_h160("0xA16081F360e3847006dB660bae1c6d1b2e17eC2A").unwrap()
+    )
+}
+
+#[test]
+fn bytes_to_string_is_lossy() {
+    assert_eq!(
+        "Downcoin WETH-USDT",
+        bytes_to_string(
+            &graph::log::logger(true),
+            vec![68, 111, 119, 110, 99, 111, 105, 110, 32, 87, 69, 84, 72, 45, 85, 83, 68, 84],
+        )
+    );
+
+    assert_eq!(
+        "Downcoin WETH-USDT�",
+        bytes_to_string(
+            &graph::log::logger(true),
+            vec![
+                68, 111, 119, 110, 99, 111, 105, 110, 32, 87, 69, 84, 72, 45, 85, 83, 68, 84, 160,
+                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
+            ],
+        )
+    )
+}
+
+#[test]
+fn bytes_to_string_utf16_and_latin1() {
+    // "Grüße €" in UTF-16LE and UTF-16BE, with null padding
+    let le = ::hex::decode("47007200fc00df0065002000ac2000000000").unwrap();
+    let be = ::hex::decode("0047007200fc00df0065002020ac00000000").unwrap();
+    assert_eq!("Grüße €", bytes_to_string_utf16(&le, true).unwrap());
+    assert_eq!("Grüße €", bytes_to_string_utf16(&be, false).unwrap());
+    // A surrogate pair and an unpaired surrogate
+    assert_eq!(
+        "😀",
+        bytes_to_string_utf16(&::hex::decode("3dd800de").unwrap(), true).unwrap()
+    );
+    assert_eq!(
+        "a\u{FFFD}",
+        bytes_to_string_utf16(&::hex::decode("61003dd8").unwrap(), true).unwrap()
+    );
+    assert!(bytes_to_string_utf16(&[0x61, 0x00, 0x62], true).is_err());
+
+    // "Grüße" and "café ©" in Latin-1
+    assert_eq!(
+        "Grüße",
+        bytes_to_string_latin1(&::hex::decode("4772fcdf65").unwrap())
+    );
+    assert_eq!(
+        "café ©",
+        bytes_to_string_latin1(&::hex::decode("636166e920a90000").unwrap())
+    );
+}
+
+#[test]
+fn string_to_bytes_round_trips_multibyte_characters() {
+    let s = "Grüße €😀".to_string();
+    let bytes = s.clone().into_bytes();
+    assert_eq!(
+        ::hex::decode("4772c3bcc39f6520e282acf09f9880").unwrap(),
+        bytes
+    );
+    assert_eq!(s, bytes_to_string(&graph::log::logger(true), bytes));
+}
+
+#[test]
+fn is_valid_utf8_rejects_bad_continuation_bytes() {
+    assert!(is_valid_utf8("Grüße €😀".as_bytes()));
+    assert!(is_valid_utf8(&[]));
+    // `ü` is c3 bc; c3 followed by a byte that is not 10xxxxxx is invalid
+    assert!(!is_valid_utf8(&[0x47, 0x72, 0xc3, 0x28]));
+    // A truncated multibyte sequence
+    assert!(!is_valid_utf8(&[0xe2, 0x82]));
+    // A lone continuation byte
+    assert!(!is_valid_utf8(&[0x80]));
+}
+
+#[test]
+fn four_bytes_requires_exactly_four_bytes() {
+    let word = four_bytes(&[0xff, 0xff, 0xff, 0xfe]).unwrap();
+    assert_eq!(0xfeffffff, u32::from_le_bytes(word));
+    assert_eq!(0xfffffffe, u32::from_be_bytes(word));
+    assert_eq!(-16777217, i32::from_le_bytes(word));
+    assert_eq!(-2, i32::from_be_bytes(word));
+
+    assert!(four_bytes(&[]).is_err());
+    assert!(four_bytes(&[1, 2, 3]).is_err());
+    assert!(four_bytes(&[1, 2, 3, 4, 5]).is_err());
+}
+
+#[test]
+fn normalize_address_accepts_lowercase_and_checksummed() {
+    assert_eq!(
+        "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+        normalize_address("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap()
+    );
+    assert_eq!(
+        "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+        normalize_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").unwrap()
+    );
+}
+
+#[test]
+fn normalize_address_rejects_invalid_input() {
+    // Wrong length
+    assert!(normalize_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA").is_err());
+    // Bad checksum, the first `A` was lowercased
+    assert!(normalize_address("0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed").is_err());
+    // Only one `0x` prefix is stripped
+    assert!(normalize_address("0x0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").is_err());
+}
+
+#[test]
+fn is_zero_address_checks_all_bytes() {
+    assert!(is_zero_address(&[0; 20]).unwrap());
+
+    let address = H160::from_str("5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").unwrap();
+    assert!(!is_zero_address(address.as_bytes()).unwrap());
+    let mut address = [0; 20];
+    address[19] = 1;
+    assert!(!is_zero_address(&address).unwrap());
+
+    assert!(is_zero_address(&[0; 19]).is_err());
+    assert!(is_zero_address(&[0; 32]).is_err());
+}
+
+#[test]
+fn cat_with_timeout_gives_up_on_slow_resolvers() {
+    #[derive(Debug)]
+    struct SlowLinkResolver(Duration);
+
+    #[async_trait]
+    impl LinkResolver for SlowLinkResolver {
+        fn with_timeout(&self, _timeout: Duration) -> Box<dyn LinkResolver> {
+            unimplemented!()
+        }
+
+        fn with_retries(&self) -> Box<dyn LinkResolver> {
+            unimplemented!()
+        }
+
+        async fn cat(&self, _logger: &Logger, _link: &Link) -> Result<Vec<u8>, Error> {
+            tokio::time::sleep(self.0).await;
+            Ok(vec![1, 2, 3])
+        }
+
+        async fn get_block(&self, _logger: &Logger, _link: &Link) -> Result<Vec<u8>, Error> {
+            unimplemented!()
+        }
+
+        async fn json_stream(
+            &self,
+            _logger: &Logger,
+            _link: &Link,
+        ) -> Result<JsonValueStream, Error> {
+            unimplemented!()
+        }
+    }
+
+    let logger = graph::log::logger(true);
+    let link = Link {
+        link: "/ipfs/QmSlow".to_string(),
+    };
+    let resolver = SlowLinkResolver(Duration::from_millis(200));
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    let result = runtime.block_on(cat_with_timeout(
+        &resolver,
+        &logger,
+        &link,
+        Some(Duration::from_millis(10)),
+    ));
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("timed out after 10ms"));
+
+    let result = runtime.block_on(cat_with_timeout(
+        &resolver,
+        &logger,
+        &link,
+        Some(Duration::from_secs(10)),
+    ));
+    assert_eq!(vec![1, 2, 3], result.unwrap());
+    assert_eq!(
+        vec![1, 2, 3],
+        runtime
+            .block_on(cat_with_timeout(&resolver, &logger, &link, None))
+            .unwrap()
+    );
+}
+
+#[test]
+fn event_params_map_keys_unnamed_params_by_position() {
+    let param = |name: &str, value: Token| LogParam {
+        name: name.to_string(),
+        value,
+    };
+    let params = vec![
+        param("from", Token::Address(H160::from_low_u64_be(1))),
+        param("", Token::Uint(web3::types::U256::from(7))),
+        param("approved", Token::Bool(true)),
+    ];
+
+    assert_eq!(
+        vec![
+            ("from".to_string(), Token::Address(H160::from_low_u64_be(1))),
+            ("1".to_string(), Token::Uint(web3::types::U256::from(7))),
+            ("approved".to_string(), Token::Bool(true)),
+        ],
+        event_params_map(&params)
+    );
+    assert!(event_params_map(&[]).is_empty());
+}
+
+#[test]
+fn block_confirmations_are_relative_to_chain_head() {
+    assert_eq!(BigInt::from(10), block_confirmations(100, Some(110)));
+    assert_eq!(BigInt::from(0), block_confirmations(100, Some(100)));
+    // A chain head that lags behind the block is clamped at zero
+    assert_eq!(BigInt::from(0), block_confirmations(100, Some(90)));
+    assert_eq!(BigInt::from(0), block_confirmations(100, None));
+}
+
+#[test]
+fn is_backfilling_far_behind_chain_head() {
+    assert!(is_backfilling(100, Some(1101), 1000));
+    assert!(!is_backfilling(100, Some(1100), 1000));
+    assert!(!is_backfilling(100, Some(500), 1000));
+    // A chain head that lags behind the block
+    assert!(!is_backfilling(100, Some(90), 1000));
+    assert!(!is_backfilling(100, None, 1000));
+}
+
+#[test]
+fn schema_entity_types_lists_object_types() {
+    let schema = InputSchema::raw(
+        "
+        interface Named { id: ID!, name: String! }
+        type Token @entity { id: ID!, symbol: String! }
+        type Pair @entity(immutable: true) { id: Bytes!, token0: Token! }
+        type User implements Named @entity { id: ID!, name: String! }
+        enum Kind { Swap, Sync }
+        ",
+        "entityTypes",
+    );
+
+    assert_eq!(vec!["Token", "Pair", "User"], schema_entity_types(&schema));
+}
+
+#[test]
+fn entity_to_json_is_canonical() {
+    let schema = InputSchema::raw(
+        "
+        type Token @entity {
+            id: ID!,
+            name: String!,
+            supply: BigInt!,
+            price: BigDecimal!,
+            active: Boolean!,
+            decimals: Int!,
+            owner: Bytes!,
+            tags: [String!]!
+        }
+        ",
+        "entityToJson",
+    );
+    let entity = graph::entity! { schema =>
+        tags: vec!["a", "b"],
+        supply: BigInt::from(1000),
+        owner: store::scalar::Bytes::from(vec![1u8, 2]),
+        name: "Token",
+        id: "1",
+        price: BigDecimal::from_str("1.5").unwrap(),
+        active: true,
+        decimals: 18,
+    };
+
+    assert_eq!(
+        r#"{"active":{"type":"Bool","data":true},"#.to_owned()
+            + r#""decimals":{"type":"Int","data":18},"#
+            + r#""id":{"type":"String","data":"1"},"#
+            + r#""name":{"type":"String","data":"Token"},"#
+            + r#""owner":{"type":"Bytes","data":"0x0102"},"#
+            + r#""price":{"type":"BigDecimal","data":"1.5"},"#
+            + r#""supply":{"type":"BigInt","data":"1000"},"#
+            + r#""tags":{"type":"List","data":[{"type":"String","data":"a"},"#
+            + r#"{"type":"String","data":"b"}]}}"#,
+        entity_to_json(&entity).unwrap()
+    );
+}
+
+#[test]
+fn schema_field_type_describes_fields() {
+    let schema = InputSchema::raw(
+        "
+        type Token @entity {
+            id: ID!,
+            symbol: String,
+            supply: BigInt!,
+            tags: [String!]!,
+            holders: [Bytes]
+        }
+        ",
+        "fieldType",
+    );
+    let field_type =
+        |entity_type: &str, field: &str| schema_field_type(&schema, entity_type, field);
+
+    assert_eq!("ID!", field_type("Token", "id").unwrap());
+    assert_eq!("String", field_type("Token", "symbol").unwrap());
+    assert_eq!("BigInt!", field_type("Token", "supply").unwrap());
+    assert_eq!("[String!]!", field_type("Token", "tags").unwrap());
+    assert_eq!("[Bytes]", field_type("Token", "holders").unwrap());
+
+    // Unknown entity types and fields
+    assert!(field_type("Pair", "id").is_err());
+    assert!(field_type("Token", "name").is_err());
+}
+
+#[test]
+fn rescale_amount_scales_between_decimals() {
+    let n = |s: &str| BigInt::from_str(s).unwrap();
+
+    // 6 -> 18 decimals is exact
+    assert_eq!(
+        n("1234567000000000000"),
+        rescale_amount(n("1234567"), 6, 18).unwrap()
+    );
+    // Identity
+    assert_eq!(n("-42"), rescale_amount(n("-42"), 18, 18).unwrap());
+    // 18 -> 6 decimals rounds half away from zero
+    assert_eq!(
+        n("1234567"),
+        rescale_amount(n("1234567499999999999"), 18, 6).unwrap()
+    );
+    assert_eq!(
+        n("1234568"),
+        rescale_amount(n("1234567500000000000"), 18, 6).unwrap()
+    );
+    assert_eq!(
+        n("-1234568"),
+        rescale_amount(n("-1234567500000000000"), 18, 6).unwrap()
+    );
+    assert_eq!(
+        n("-1234567"),
+        rescale_amount(n("-1234567499999999999"), 18, 6).unwrap()
+    );
+    assert_eq!(n("0"), rescale_amount(n("499999999999"), 18, 6).unwrap());
+}
+
+#[test]
+fn timestamp_bucket_rounds_down_to_bucket_start() {
+    const HOUR: u32 = 3600;
+    const DAY: u32 = 86400;
+
+    let bucket = |ts: i64, size| timestamp_bucket(BigInt::from(ts), size).unwrap();
+    assert_eq!(BigInt::from(1_679_997_600), bucket(1_679_999_999, HOUR));
+    assert_eq!(BigInt::from(1_679_961_600), bucket(1_679_999_999, DAY));
+    // A timestamp on the boundary is the start of its own bucket
+    assert_eq!(BigInt::from(1_679_997_600), bucket(1_679_997_600, HOUR));
+    assert_eq!(BigInt::from(1_679_961_600), bucket(1_679_961_600, DAY));
+    assert_eq!(BigInt::from(-86400), bucket(-1, DAY));
+
+    assert!(timestamp_bucket(BigInt::from(1), 0).is_err());
+}
+
+#[test]
+fn big_int_next_power_of_two_rounds_up() {
+    let next = |x: BigInt| big_int_next_power_of_two(x).unwrap();
+    let two_pow_200 =
+        BigInt::from_str("1606938044258990275541962092341162602522202993782792835301376").unwrap();
+    let two_pow_201 = two_pow_200.clone() + two_pow_200.clone();
+
+    assert_eq!(BigInt::from(1), next(BigInt::from(1)));
+    assert_eq!(BigInt::from(8), next(BigInt::from(5)));
+    assert_eq!(BigInt::from(8), next(BigInt::from(8)));
+    assert_eq!(two_pow_200.clone(), next(two_pow_200.clone()));
+    assert_eq!(two_pow_201, next(two_pow_200 + BigInt::from(1)));
+
+    assert!(big_int_next_power_of_two(BigInt::from(0)).is_err());
+    assert!(big_int_next_power_of_two(BigInt::from(-4)).is_err());
+}
+
+#[test]
+fn big_int_abs_diff_is_non_negative() {
+    let diff = |x: i64, y: i64| big_int_abs_diff(BigInt::from(x), BigInt::from(y));
+
+    assert_eq!(BigInt::from(7), diff(10, 3));
+    assert_eq!(BigInt::from(7), diff(3, 10));
+    assert_eq!(BigInt::from(0), diff(5, 5));
+    assert_eq!(BigInt::from(8), diff(-3, 5));
+    assert_eq!(BigInt::from(8), diff(5, -3));
+}
+
+#[test]
+fn big_int_linear_recurrence_matches_known_sequences() {
+    let big_ints = |values: &[i64]| values.iter().map(|v| BigInt::from(*v)).collect::<Vec<_>>();
+    let term = |coefficients: &[i64], seeds: &[i64], n| {
+        big_int_linear_recurrence(
+            big_ints(coefficients),
+            big_ints(seeds),
+            n,
+            &GasCounter::default(),
+        )
+    };
+
+    // Fibonacci
+    let fibonacci: Vec<_> = (0..10)
+        .map(|n| term(&[1, 1], &[0, 1], n).unwrap())
+        .collect();
+    assert_eq!(big_ints(&[0, 1, 1, 2, 3, 5, 8, 13, 21, 34]), fibonacci);
+    assert_eq!(
+        BigInt::from(2880067194370816120i64),
+        term(&[1, 1], &[0, 1], 90).unwrap()
+    );
+    // Pell numbers
+    assert_eq!(BigInt::from(2378), term(&[2, 1], &[0, 1], 10).unwrap());
+    // `a(k) = 3 * a(k - 1) - 2 * a(k - 2)` gives `2^k - 1`
+    assert_eq!(BigInt::from(1048575), term(&[3, -2], &[0, 1], 20).unwrap());
+    // A first order recurrence is a geometric sequence
+    assert_eq!(
+        BigInt::from(5 * 3i64.pow(12)),
+        term(&[3], &[5], 12).unwrap()
+    );
+
+    assert!(term(&[1, 1], &[0, 1], LINEAR_RECURRENCE_MAX_TERM).is_ok());
+    assert!(term(&[1, 1], &[0, 1], LINEAR_RECURRENCE_MAX_TERM + 1).is_err());
+    assert!(term(&[1, 1], &[0], 5).is_err());
+    assert!(term(&[], &[], 5).is_err());
+}
+
+#[test]
+fn big_int_linear_recurrence_charges_for_the_growing_terms() {
+    let gas_used = |coefficients: &[i64], n| {
+        let gas = GasCounter::default();
+        let big_ints = |values: &[i64]| values.iter().map(|v| BigInt::from(*v)).collect();
+        big_int_linear_recurrence(big_ints(coefficients), big_ints(&[1, 1]), n, &gas).unwrap();
+        gas.get().value()
+    };
+
+    // The terms grow by about 63 bits with every step, so computing twice as many of them
+    // costs about four times as much, while terms that don't grow cost the same every step
+    assert!(gas_used(&[i64::MAX, 1], 400) > 3 * gas_used(&[i64::MAX, 1], 200));
+    assert!(gas_used(&[1, 0], 400) < 3 * gas_used(&[1, 0], 200));
+}
+
+#[test]
+fn big_decimal_to_string_trimmed_drops_trailing_zeros() {
+    let trimmed = |s: &str| big_decimal_to_string_trimmed(&BigDecimal::from_str(s).unwrap());
+
+    assert_eq!("1.23", trimmed("1.2300"));
+    assert_eq!("5", trimmed("5.000"));
+    assert_eq!("0", trimmed("0.0"));
+    assert_eq!("-0.5", trimmed("-0.50"));
+    assert_eq!("100", trimmed("100"));
+    assert_eq!("100", trimmed("100.00"));
+}
+
+#[test]
+fn big_int_to_big_decimal_matches_string_conversion() {
+    let mut values = vec![
+        BigInt::from(0),
+        BigInt::from(1),
+        BigInt::from(-7),
+        BigInt::from(i64::MAX),
+        BigInt::from_unsigned_u256(&web3::types::U256::MAX),
+    ];
+    // Around and far beyond the precision of BigDecimal
+    for digits in [10, 33, 34, 35, 100, 1000] {
+        let n = BigInt::from_str(&format!("1{}", "0".repeat(digits))).unwrap();
+        values.push(n.clone() - BigInt::from(1));
+        values.push(BigInt::from(0) - n);
+    }
+
+    for n in values {
+        assert_eq!(
+            BigDecimal::from_str(&n.to_string()).unwrap(),
+            big_int_to_big_decimal(n.clone()),
+            "{}",
+            n
+        );
     }
 }
+
 #[test]
-fn test_string_to_h160_with_0x() {
+fn big_decimal_div_or_defaults_on_zero_denominator() {
+    let d = |s: &str| BigDecimal::from_str(s).unwrap();
+
+    assert_eq!(d("2.5"), big_decimal_div_or(d("5"), d("2"), d("-1")));
+    assert_eq!(d("1") / d("3"), big_decimal_div_or(d("1"), d("3"), d("-1")));
+    assert_eq!(d("-1"), big_decimal_div_or(d("5"), d("0"), d("-1")));
+    assert_eq!(d("0"), big_decimal_div_or(d("0"), d("0.000"), d("0")));
+}
+
+#[test]
+fn big_decimal_mod_is_exact() {
+    let d = |s: &str| BigDecimal::from_str(s).unwrap();
+    let rem = |x: &str, y: &str| big_decimal_mod(d(x), d(y)).unwrap();
+
+    assert_eq!(d("1.5"), rem("7.5", "2"));
+    assert_eq!(d("0"), rem("6", "2"));
+    // The remainder has the sign of the dividend
+    assert_eq!(d("-1.5"), rem("-7.5", "2"));
+    assert_eq!(d("1.5"), rem("7.5", "-2"));
+    assert_eq!(d("-1.5"), rem("-7.5", "-2"));
+    // Operands with different scales
+    assert_eq!(d("0.03456"), rem("1.23456", "0.1"));
+    assert_eq!(d("0.1"), rem("10", "0.3"));
+    assert_eq!(d("2"), rem("1e20", "7"));
+    assert_eq!(d("0.0000001"), rem("0.0000001", "1000"));
+
+    assert!(big_decimal_mod(d("1"), BigDecimal::zero()).is_err());
+}
+
+#[test]
+fn big_decimal_inverse_matches_division() {
+    for x in ["2", "3", "0.0004", "-7.5", "123456789.987654321", "1e-20"] {
+        let x = BigDecimal::from_str(x).unwrap();
+        assert_eq!(
+            BigDecimal::from(1) / x.clone(),
+            big_decimal_inverse(x).unwrap()
+        );
+    }
     assert_eq!(
-        H160::from_str("A16081F360e3847006dB660bae1c6d1b2e17eC2A").unwrap(),
-        string_to_h160("0xA16081F360e3847006dB660bae1c6d1b2e17eC2A").unwrap()
-    )
+        BigDecimal::from_str("0.25").unwrap(),
+        big_decimal_inverse(BigDecimal::from(4)).unwrap()
+    );
+
+    assert!(big_decimal_inverse(BigDecimal::zero()).is_err());
 }
 
 #[test]
-fn bytes_to_string_is_lossy() {
+fn big_decimal_price_impact_uses_constant_product() {
+    let d = |s: &str| BigDecimal::from_str(s).unwrap();
+    let impact = |reserve_in: &str, reserve_out: &str, amount_in: &str| {
+        big_decimal_price_impact(d(reserve_in), d(reserve_out), d(amount_in)).unwrap()
+    };
+
+    // Small trades barely move the price: 1 / (999 + 1)
+    assert_eq!(d("0.001"), impact("999", "5000", "1"));
+    // Trading as much as is in the pool halves the price
+    assert_eq!(d("0.5"), impact("1000", "1000", "1000"));
+    // Large trades: 3000 / (1000 + 3000)
+    assert_eq!(d("0.75"), impact("1000", "250", "3000"));
+    assert_eq!(BigDecimal::zero(), impact("1000", "1000", "0"));
+
+    assert!(big_decimal_price_impact(d("0"), d("1000"), d("1")).is_err());
+    assert!(big_decimal_price_impact(d("1000"), d("0"), d("1")).is_err());
+    assert!(big_decimal_price_impact(d("1000"), d("1000"), d("-1")).is_err());
+}
+
+#[test]
+fn big_decimal_to_fixed_rounds_half_away_from_zero() {
+    let to_fixed = |x: &str, decimals| {
+        let x = BigDecimal::from_str(x).unwrap();
+        big_decimal_to_fixed(&x, decimals).unwrap()
+    };
+    let n = |s: &str| BigInt::from_str(s).unwrap();
+
+    // Exact conversions
+    assert_eq!(n("1500000000000000000"), to_fixed("1.5", 18));
+    assert_eq!(n("-1234500"), to_fixed("-1.2345", 6));
+    assert_eq!(n("12300000"), to_fixed("123e5", 0));
+    assert_eq!(n("0"), to_fixed("0", 6));
+
+    // Rounding
+    assert_eq!(n("123"), to_fixed("1.23456", 2));
+    assert_eq!(n("124"), to_fixed("1.235", 2));
+    assert_eq!(n("-124"), to_fixed("-1.235", 2));
+    assert_eq!(n("-123"), to_fixed("-1.234", 2));
+    assert_eq!(n("1"), to_fixed("0.5", 0));
+    assert_eq!(n("-1"), to_fixed("-0.5", 0));
+    assert_eq!(n("0"), to_fixed("0.4999", 0));
+    assert_eq!(n("0"), to_fixed("1e-30", 18));
+}
+
+#[test]
+fn big_decimal_sum_handles_scales_and_signs() {
+    let d = |s: &str| BigDecimal::from_str(s).unwrap();
+    let sum = |values: &[&str]| big_decimal_sum(values.iter().copied().map(d).collect());
+
+    assert_eq!(BigDecimal::zero(), sum(&[]));
+    assert_eq!(d("1.5"), sum(&["1.5"]));
+    assert_eq!(d("1000.0101"), sum(&["1000", "0.01", "0.0001"]));
+    assert_eq!(d("-2.25"), sum(&["1.25", "-3.5"]));
+    assert_eq!(BigDecimal::zero(), sum(&["1e20", "-1e20"]));
+
+    // Rounding only happens at the end, so tiny values that cancel out
+    // around a large one do not get lost
+    assert_eq!(d("1e40"), sum(&["1e40", "1e-10", "-1e-10"]));
+    assert_eq!(d("2e-10"), sum(&["1e40", "1e-10", "-1e40", "1e-10"]));
+}
+
+#[test]
+fn big_decimal_compound_grows_exponentially() {
+    let d = |s: &str| BigDecimal::from_str(s).unwrap();
+    let compound = |principal: &str, rate: &str, periods| {
+        big_decimal_compound(d(principal), d(rate), periods, &GasCounter::default())
+    };
+
+    assert_eq!(d("1000"), compound("1000", "0.05", 0).unwrap());
+    assert_eq!(d("1050"), compound("1000", "0.05", 1).unwrap());
+    assert_eq!(d("1102.5"), compound("1000", "0.05", 2).unwrap());
+    assert_eq!(d("133.1"), compound("100", "0.1", 3).unwrap());
+    assert_eq!(d("12.5"), compound("100", "-0.5", 3).unwrap());
+
+    // Large period counts
     assert_eq!(
-        "Downcoin WETH-USDT",
-        bytes_to_string(
-            &graph::log::logger(true),
-            vec![68, 111, 119, 110, 99, 111, 105, 110, 32, 87, 69, 84, 72, 45, 85, 83, 68, 84],
-        )
+        d("1267650600228229401496703205376"),
+        compound("1", "1", 100).unwrap()
+    );
+    assert_eq!(
+        d("5192296858534827628530496329220096"),
+        compound("1", "1", 112).unwrap()
     );
+    assert_eq!(d("42"), compound("42", "0", COMPOUND_MAX_PERIODS).unwrap());
+    assert!(compound("42", "0", COMPOUND_MAX_PERIODS + 1).is_err());
+}
 
+#[test]
+fn big_decimal_compound_charges_every_multiplication() {
+    let gas_used = |periods| {
+        let gas = GasCounter::default();
+        big_decimal_compound(BigDecimal::from(1), BigDecimal::from(1), periods, &gas).unwrap();
+        gas.get().value()
+    };
+
+    // With all bits set, every squaring comes with a multiplication into the factor
+    assert!(gas_used(0b1111) > gas_used(0b1000));
+    assert!(2 * gas_used(0xff_ffff) > 3 * gas_used(0xfff));
+}
+
+#[test]
+fn big_decimal_weighted_median_balances_weights() {
+    let d = |s: &str| BigDecimal::from_str(s).unwrap();
+    let ds = |values: &[&str]| values.iter().copied().map(d).collect::<Vec<_>>();
+    let median =
+        |values: &[&str], weights: &[&str]| big_decimal_weighted_median(ds(values), ds(weights));
+
+    // Odd and even sample counts with equal weights
+    assert_eq!(d("2"), median(&["3", "1", "2"], &["1", "1", "1"]).unwrap());
     assert_eq!(
-        "Downcoin WETH-USDT�",
-        bytes_to_string(
-            &graph::log::logger(true),
-            vec![
-                68, 111, 119, 110, 99, 111, 105, 110, 32, 87, 69, 84, 72, 45, 85, 83, 68, 84, 160,
-                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
-            ],
+        d("2.5"),
+        median(&["4", "1", "3", "2"], &["1", "1", "1", "1"]).unwrap()
+    );
+    // A heavy sample pulls the median towards it, and outliers don't matter
+    assert_eq!(
+        d("10"),
+        median(&["1", "10", "1000000"], &["0.2", "0.7", "0.1"]).unwrap()
+    );
+    assert_eq!(d("1"), median(&["1", "2", "3"], &["5", "2", "2"]).unwrap());
+    // Exactly half the weight on each side
+    assert_eq!(d("2"), median(&["1", "3"], &["0.5", "0.5"]).unwrap());
+    // Samples without weight are ignored
+    assert_eq!(
+        d("2"),
+        median(&["1", "3", "100"], &["1", "1", "0"]).unwrap()
+    );
+
+    // Mismatched lengths, negative and zero total weights
+    assert!(median(&["1", "2"], &["1"]).is_err());
+    assert!(median(&["1", "2"], &["1", "-1"]).is_err());
+    assert!(median(&["1", "2"], &["0", "0"]).is_err());
+    assert!(median(&[], &[]).is_err());
+}
+
+#[test]
+fn big_decimal_geomean_takes_nth_root_of_product() {
+    let d = |s: &str| BigDecimal::from_str(s).unwrap();
+    let geomean = |values: &[&str]| {
+        big_decimal_geomean(
+            values.iter().copied().map(d).collect(),
+            &GasCounter::default(),
         )
+    };
+
+    assert_eq!(d("4"), geomean(&["2", "8"]).unwrap());
+    assert_eq!(d("3"), geomean(&["1", "3", "9"]).unwrap());
+    assert_eq!(d("4.2"), geomean(&["4.2"]).unwrap());
+    assert_eq!(d("3"), geomean(&["1.5", "6"]).unwrap());
+    assert_eq!(d("1"), geomean(&["0.01", "100"]).unwrap());
+    assert_eq!(d("1"), geomean(&["1e20", "1e-20", "1"]).unwrap());
+    // Irrational roots are rounded to 34 significant digits
+    assert_eq!(
+        d("2.449489742783178098197284074705891"),
+        geomean(&["2", "3"]).unwrap()
+    );
+    assert_eq!(
+        d("0.002828427124746190097603377448419396"),
+        geomean(&["0.001", "0.008"]).unwrap()
+    );
+
+    // Empty arrays and non-positive values
+    assert!(geomean(&[]).is_err());
+    assert!(geomean(&["1", "0"]).is_err());
+    assert!(geomean(&["-2", "-8"]).is_err());
+}
+
+#[test]
+fn big_decimal_geomean_charges_for_the_growing_product() {
+    let gas_used = |n: usize| {
+        let gas = GasCounter::default();
+        let values = vec![BigDecimal::from_str("1234567890.0987654321").unwrap(); n];
+        big_decimal_geomean(values, &gas).unwrap();
+        gas.get().value()
+    };
+
+    // Twice as many values make a product twice as large, and multiplying into it twice as
+    // expensive, so the cost grows quadratically
+    assert!(gas_used(200) > 3 * gas_used(100));
+}
+
+#[test]
+fn big_decimal_approx_equals_compares_within_tolerance() {
+    let d = |s: &str| BigDecimal::from_str(s).unwrap();
+    let approx = |x: &str, y: &str| big_decimal_approx_equals(d(x), d(y), d("0.001")).unwrap();
+
+    // Exactly at the tolerance, in either order
+    assert!(approx("1.001", "1"));
+    assert!(approx("1", "1.001"));
+    // Just within the tolerance
+    assert!(approx("1.0009999", "1"));
+    assert!(approx("-1", "-1.0009999"));
+    // Just outside the tolerance
+    assert!(!approx("1.0010001", "1"));
+    assert!(!approx("1", "1.0010001"));
+
+    assert!(big_decimal_approx_equals(d("1"), d("1"), BigDecimal::zero()).unwrap());
+    assert!(big_decimal_approx_equals(d("1"), d("1"), d("-0.001")).is_err());
+}
+
+#[test]
+fn big_decimal_lerp_interpolates_and_extrapolates() {
+    let d = |s: &str| BigDecimal::from_str(s).unwrap();
+    let lerp = |t: &str| big_decimal_lerp(d("10"), d("20"), d(t));
+
+    assert_eq!(d("10"), lerp("0"));
+    assert_eq!(d("20"), lerp("1"));
+    assert_eq!(d("15"), lerp("0.5"));
+    // Fractions outside of [0, 1] extrapolate
+    assert_eq!(d("25"), lerp("1.5"));
+    assert_eq!(d("5"), lerp("-0.5"));
+    // Interpolating towards a smaller value
+    assert_eq!(d("17.5"), big_decimal_lerp(d("20"), d("10"), d("0.25")));
+}
+
+#[test]
+fn twap_update_accumulates_price_times_elapsed() {
+    let d = |s: &str| BigDecimal::from_str(s).unwrap();
+    let updates = [("1.5", 12), ("1.25", 30), ("2", 0), ("0.003", 7)];
+
+    let accumulator = updates
+        .iter()
+        .fold(BigDecimal::from(0), |acc, (price, elapsed)| {
+            twap_update(acc, d(price), BigInt::from(*elapsed)).unwrap()
+        });
+    // 1.5 * 12 + 1.25 * 30 + 2 * 0 + 0.003 * 7
+    assert_eq!(d("55.521"), accumulator);
+
+    assert!(twap_update(d("1"), d("1"), BigInt::from(-1)).is_err());
+}
+
+#[test]
+fn compile_safe_pattern_matches_and_rejects_complex_patterns() {
+    let address = compile_safe_pattern("^0x[0-9a-fA-F]{40}$").unwrap();
+    assert!(address.is_match("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"));
+    assert!(!address.is_match("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAe"));
+    assert!(!address.is_match("5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"));
+
+    // Nested repetitions blow up the compiled program
+    assert!(compile_safe_pattern("(a{100}){100}").is_err());
+    // Backreferences are not supported at all
+    assert!(compile_safe_pattern(r"(a)\1").is_err());
+    // Overly long patterns are rejected outright
+    assert!(compile_safe_pattern(&"a".repeat(STRING_MATCHES_MAX_PATTERN_LEN + 1)).is_err());
+}
+
+#[test]
+fn semver_compare_uses_precedence() {
+    let compare = |x, y| semver_compare(x, y).unwrap();
+
+    assert_eq!(-1, compare("1.2.0", "1.10.0"));
+    assert_eq!(1, compare("1.10.0", "1.2.0"));
+    assert_eq!(0, compare("1.2.0", "1.2.0"));
+
+    // Pre-releases come before the release, and are ordered by their identifiers
+    assert_eq!(-1, compare("1.0.0-alpha", "1.0.0"));
+    assert_eq!(-1, compare("1.0.0-alpha", "1.0.0-alpha.1"));
+    assert_eq!(-1, compare("1.0.0-alpha.2", "1.0.0-alpha.10"));
+    assert_eq!(-1, compare("1.0.0-beta", "1.0.0-rc.1"));
+    // Build metadata is ignored
+    assert_eq!(0, compare("1.0.0+build.1", "1.0.0+build.2"));
+
+    assert!(semver_compare("1.2", "1.2.0").is_err());
+    assert!(semver_compare("1.2.0", "not a version").is_err());
+}
+
+#[test]
+fn encode_packed_address_and_uint256() {
+    let address = H160::from_str("5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").unwrap();
+    let encoded = encode_packed(&[Token::Address(address), Token::Uint(1000.into())]).unwrap();
+    assert_eq!(
+        "5aaeb6053f3e94c9b9a09f33669435e7ef1beaed\
+         00000000000000000000000000000000000000000000000000000000000003e8",
+        ::hex::encode(encoded)
+    );
+
+    let encoded = encode_packed(&[
+        Token::String("ab".to_owned()),
+        Token::Bool(true),
+        Token::Array(vec![Token::Bool(false), Token::Bool(true)]),
+    ])
+    .unwrap();
+    assert_eq!(
+        "616201\
+         0000000000000000000000000000000000000000000000000000000000000000\
+         0000000000000000000000000000000000000000000000000000000000000001",
+        ::hex::encode(encoded)
+    );
+
+    assert!(encode_packed(&[Token::Array(vec![Token::String("a".to_owned())])]).is_err());
+    assert!(encode_packed(&[Token::Tuple(vec![Token::Bool(true)])]).is_err());
+}
+
+#[test]
+fn json_to_f64_rejects_non_finite_values() {
+    assert_eq!(1.5, json_to_finite_f64("1.5").unwrap());
+    assert_eq!(-2e300, json_to_finite_f64("-2e300").unwrap());
+
+    // Numbers that overflow `f64`
+    assert!(json_to_finite_f64("1e400").is_err());
+    assert!(json_to_finite_f64("-1e400").is_err());
+
+    // Non-finite values that `f64::from_str` accepts
+    for json in ["NaN", "nan", "inf", "-inf", "Infinity", "-Infinity"] {
+        assert!(json_to_finite_f64(json).is_err(), "`{}` was accepted", json);
+    }
+}
+
+#[test]
+fn json_destructuring_walks_nested_values() {
+    let value: serde_json::Value =
+        serde_json::from_str(r#"{"a": [1, {"b": true}], "c": "d"}"#).unwrap();
+
+    let mut object = json_as_object(value).unwrap();
+    assert_eq!(2, object.len());
+    let mut a = json_as_array(object.remove("a").unwrap()).unwrap();
+    assert_eq!(2, a.len());
+    let mut inner = json_as_object(a.pop().unwrap()).unwrap();
+    assert!(json_as_bool(inner.remove("b").unwrap()).unwrap());
+}
+
+#[test]
+fn json_destructuring_rejects_other_kinds() {
+    let value: serde_json::Value = serde_json::from_str(r#"{"a": [1, {"b": true}]}"#).unwrap();
+    let message = json_as_array(value).unwrap_err().to_string();
+    assert!(message.contains("expected JSON array, got object"));
+    assert!(message.contains(r#"{"a":[1,{"b":true}]}"#));
+
+    assert!(json_as_object(serde_json::json!([1])).is_err());
+    assert!(json_as_bool(serde_json::Value::Null).is_err());
+    // Strings are not coerced
+    assert!(json_as_bool(serde_json::json!("true")).is_err());
+}
+
+#[test]
+fn json_as_string_unescapes_strings_only() {
+    let value: serde_json::Value = serde_json::from_str(r#""a \"quoted\" \u00fc\n""#).unwrap();
+    assert_eq!("a \"quoted\" ü\n", json_as_string(value).unwrap());
+    assert_eq!("", json_as_string(serde_json::json!("")).unwrap());
+
+    assert!(json_as_string(serde_json::json!(1)).is_err());
+    assert!(json_as_string(serde_json::json!({ "a": "b" })).is_err());
+    assert!(json_as_string(serde_json::Value::Null).is_err());
+}
+
+#[test]
+fn json_reduce_threads_accumulator() {
+    let values: Vec<serde_json::Value> = serde_json::from_str("[1, 2, 3.5, -4]").unwrap();
+    let sum = |accumulator: store::Value, value: &serde_json::Value| -> Result<_, Error> {
+        let value = BigDecimal::from_str(&value.to_string())?;
+        match accumulator {
+            store::Value::BigDecimal(sum) => Ok(store::Value::BigDecimal(sum + value)),
+            other => Err(anyhow!("unexpected accumulator {}", other)),
+        }
+    };
+
+    let initial = store::Value::BigDecimal(BigDecimal::from(10));
+    assert_eq!(
+        store::Value::BigDecimal(BigDecimal::from_str("12.5").unwrap()),
+        json_reduce(&values, initial.clone(), sum).unwrap()
+    );
+    // An empty array returns the initial accumulator
+    assert_eq!(initial.clone(), json_reduce(&[], initial, sum).unwrap());
+    // Errors from the callback stop the fold
+    assert!(json_reduce(&values, store::Value::Null, sum).is_err());
+}
+
+#[test]
+fn big_decimal_from_scientific_applies_exponent() {
+    let parse = |s| big_decimal_from_scientific(s).unwrap();
+    let decimal = |s| BigDecimal::from_str(s).unwrap();
+
+    assert_eq!(decimal("1500000000000000000"), parse("1.5e18"));
+    assert_eq!(decimal("1500000000000000000"), parse("1.5E+18"));
+    assert_eq!(decimal("0.0025"), parse("2.5e-3"));
+    assert_eq!(decimal("-400"), parse("-4e2"));
+    assert_eq!(decimal("42.5"), parse("42.5"));
+
+    for s in [
+        "1.5e",
+        "e5",
+        "1.5e1.5",
+        "1e99999",
+        "1e-9223372036854775808",
+        "abc",
+    ] {
+        let err = big_decimal_from_scientific(s).unwrap_err();
+        assert!(
+            err.to_string().contains(s),
+            "unexpected error for `{}`: {}",
+            s,
+            err
+        );
+    }
+}
+
+#[test]
+fn dedup_preserving_order_keeps_first_occurrence() {
+    let strings = |values: &[&str]| values.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    assert_eq!(
+        strings(&["b", "a", "c"]),
+        dedup_preserving_order(strings(&["b", "a", "b", "c", "a", "b"]))
+    );
+    assert_eq!(
+        strings(&["x", "y", "z"]),
+        dedup_preserving_order(strings(&["x", "y", "z"]))
+    );
+    assert!(dedup_preserving_order(vec![]).is_empty());
+}
+
+#[test]
+fn big_int_sorted_unique_sorts_numerically() {
+    let big_ints = |values: &[i64]| values.iter().map(|v| BigInt::from(*v)).collect::<Vec<_>>();
+
+    assert_eq!(
+        big_ints(&[-20, -3, 0, 2, 10, 100]),
+        big_int_sorted_unique(big_ints(&[10, -3, 100, 2, 10, 0, -20, -3, 2]))
+    );
+    assert_eq!(big_ints(&[7]), big_int_sorted_unique(big_ints(&[7, 7, 7])));
+    assert!(big_int_sorted_unique(vec![]).is_empty());
+}
+
+#[test]
+fn bloom_contains_uses_ethereum_bloom_bits() {
+    // The bloom of a log emitted by `address` with the single topic `topic`
+    let bloom = ::hex::decode(
+        "00000000000000000000000000000000\
+         00000000100000000000000000000000\
+         00000000000000000000000000000000\
+         00000000000000000000000000000000\
+         00000000000000000000000000000000\
+         00000000000000000000000000000000\
+         00000002020000000000000000000000\
+         00000000000000000000000800000000\
+         10000000000000000000000000000000\
+         00000000000000000000001000000000\
+         00000000000000000000000000000000\
+         00000000000000000000000000000000\
+         00000000000000000000000000000000\
+         00000000000000000000000000000000\
+         00000000000000000000000000000000\
+         00000000000000000000000000000000",
     )
+    .unwrap();
+    let address = ::hex::decode("ef2d6d194084c2de36e0dabfce45d046b37d1106").unwrap();
+    let topic =
+        ::hex::decode("02c69be41d0b7e40352fc85be1cd65eb03d40ef8427a0ca4596b1ead9a00e9fc").unwrap();
+    let other = ::hex::decode("0000000000000000000000000000000000000001").unwrap();
+
+    assert!(bloom_contains(&bloom, &address).unwrap());
+    assert!(bloom_contains(&bloom, &topic).unwrap());
+    assert!(!bloom_contains(&bloom, &other).unwrap());
+    assert!(!bloom_contains(&[0; 256], &address).unwrap());
+    assert!(bloom_contains(&bloom[1..], &address).is_err());
+}
+
+#[test]
+fn is_unfinalized_within_reorg_threshold() {
+    // A call near the head can be affected by a reorg
+    assert!(is_unfinalized(995, Some(1000), 250));
+    assert!(is_unfinalized(1000, Some(1000), 250));
+    // A call on a finalized block can not
+    assert!(!is_unfinalized(750, Some(1000), 250));
+    assert!(!is_unfinalized(100, Some(1000), 250));
+    // Without a chain head, we can't tell
+    assert!(is_unfinalized(100, None, 250));
+}
+
+#[test]
+fn hex_to_signed_big_int_uses_twos_complement() {
+    let signed = |hex, width| hex_to_signed_big_int(hex, width).unwrap().to_string();
+
+    assert_eq!("127", signed("0x7f", 8));
+    assert_eq!("-1", signed("0xff", 8));
+    assert_eq!("-128", signed("80", 8));
+    // Shorter strings are padded with zeros, so the high bit is not set
+    assert_eq!("255", signed("0xff", 16));
+    assert_eq!(
+        "-2",
+        signed(format!("0x{}", "f".repeat(63) + "e").as_str(), 256)
+    );
+    assert_eq!("1000", signed("0x3e8", 256));
+
+    // Width mismatches
+    assert!(hex_to_signed_big_int("0x100", 8).is_err());
+    assert!(hex_to_signed_big_int("0x7f", 7).is_err());
+    assert!(hex_to_signed_big_int("0x7f", 0).is_err());
+    assert!(hex_to_signed_big_int("0x7f", 264).is_err());
+    assert!(hex_to_signed_big_int("0xzz", 8).is_err());
+    // Only one `0x` prefix is stripped
+    assert!(hex_to_signed_big_int("0x0x7f", 16).is_err());
+}
+
+#[test]
+fn is_own_address_compares_log_and_data_source_addresses() {
+    let own = [0x11; 20];
+    let other = [0x22; 20];
+    assert!(is_own_address(&own, &own));
+    assert!(!is_own_address(&own, &other));
+    // Data sources created without an address own no logs
+    assert!(!is_own_address(&[], &own));
+    assert!(!is_own_address(&[], &[]));
+}
+
+#[test]
+fn log_kv_attaches_fields_to_the_record() {
+    use std::fmt;
+    use std::sync::Mutex;
+
+    // Keeps the message and the key-value pairs of every record
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<(String, Vec<(String, String)>)>>>);
+
+    struct Pairs(Vec<(String, String)>);
+
+    impl slog::Serializer for Pairs {
+        fn emit_arguments(&mut self, key: slog::Key, val: &fmt::Arguments) -> slog::Result {
+            self.0.push((key.to_string(), val.to_string()));
+            Ok(())
+        }
+    }
+
+    impl slog::Drain for Capture {
+        type Ok = ();
+        type Err = slog::Never;
+
+        fn log(&self, record: &slog::Record, _: &slog::OwnedKVList) -> Result<(), slog::Never> {
+            let mut pairs = Pairs(Vec::new());
+            slog::KV::serialize(record.kv(), record, &mut pairs).unwrap();
+            let msg = record.msg().to_string();
+            self.0.lock().unwrap().push((msg, pairs.0));
+            Ok(())
+        }
+    }
+
+    let capture = Capture::default();
+    let logger = Logger::root(capture.clone(), o!());
+    let fields = BTreeMap::from_iter([
+        ("user".to_string(), "alice".to_string()),
+        ("pool".to_string(), "0xbeef".to_string()),
+    ]);
+    log_kv(&logger, slog::Level::Info, "Pool", "Swap", &fields);
+
+    let records = capture.0.lock().unwrap();
+    assert_eq!(1, records.len());
+    let (msg, pairs) = &records[0];
+    assert_eq!("Swap", msg);
+    assert!(pairs.contains(&("data_source".to_string(), "Pool".to_string())));
+    assert!(pairs.contains(&(
+        "fields".to_string(),
+        r#"{"pool":"0xbeef","user":"alice"}"#.to_string()
+    )));
+}
+
+#[test]
+fn format_token_amount_rounds_and_trims() {
+    let format = |amount: &str, decimals: u8, precision: u8| {
+        format_token_amount(BigInt::from_str(amount).unwrap(), decimals, precision).unwrap()
+    };
+
+    // 18 decimals, like ether
+    assert_eq!("123.46", format("123456789000000000000", 18, 2));
+    assert_eq!("123.4568", format("123456789000000000000", 18, 4));
+    assert_eq!("123", format("123456789000000000000", 18, 0));
+    assert_eq!("2", format("1500000000000000000", 18, 0));
+    assert_eq!("1", format("1000000000000000000", 18, 2));
+    assert_eq!("0.000000000000000001", format("1", 18, 18));
+    assert_eq!("0", format("1", 18, 17));
+
+    // 6 decimals, like USDC
+    assert_eq!("1.235", format("1234500", 6, 3));
+    assert_eq!("1.23", format("1234500", 6, 2));
+    assert_eq!("1.2345", format("1234500", 6, 10));
+    assert_eq!("1", format("999999", 6, 2));
+    assert_eq!("0", format("0", 6, 2));
+    assert_eq!("-1.23", format("-1234500", 6, 2));
+    assert_eq!("-1.235", format("-1234500", 6, 3));
+    assert_eq!("0", format("-4999", 6, 2));
+}
+
+#[test]
+fn big_decimal_variance_and_stddev_of_samples() {
+    let d = |s: &str| BigDecimal::from_str(s).unwrap();
+    let ds = |values: &[&str]| values.iter().copied().map(d).collect::<Vec<_>>();
+    let variance = |values: &[&str]| BigDecimal::from(population_variance(ds(values)).unwrap());
+    let stddev =
+        |values: &[&str]| big_decimal_nth_root(&population_variance(ds(values)).unwrap(), 2);
+
+    // Mean 5, squared deviations 9, 1, 1, 1, 0, 0, 4, 16
+    let sample = ["2", "4", "4", "4", "5", "5", "7", "9"];
+    assert_eq!(d("4"), variance(&sample));
+    assert_eq!(d("2"), stddev(&sample));
+
+    // Mean 2.5, squared deviations 2.25, 0.25, 0.25, 2.25
+    assert_eq!(d("1.25"), variance(&["1", "2", "3", "4"]));
+    assert_eq!(
+        d("1.118033988749894848204586834365638"),
+        stddev(&["1", "2", "3", "4"])
+    );
+
+    // Mean 0.2, squared deviations 0.01, 0, 0.01
+    assert_eq!(
+        d("0.006666666666666666666666666666666667"),
+        variance(&["0.1", "0.2", "0.3"])
+    );
+    assert_eq!(
+        d("0.08164965809277260327324280249019638"),
+        stddev(&["0.1", "0.2", "0.3"])
+    );
+
+    // Values of very different magnitudes are rounded like in other operations
+    assert_eq!(d("2.5E+1999"), variance(&["1E+1000", "1E-1000"]));
+    assert_eq!(d("5E+999"), stddev(&["1E+1000", "1E-1000"]));
+
+    // Constant samples don't vary
+    assert_eq!(BigDecimal::zero(), variance(&["42"]));
+    assert_eq!(BigDecimal::zero(), stddev(&["-1.5", "-1.5"]));
+
+    assert!(population_variance(vec![]).is_err());
 }
@@ -4,14 +4,17 @@ use futures::sync::mpsc;
 use futures03::channel::oneshot::Sender;
 use graph::blockchain::{Blockchain, HostFn};
 use graph::components::store::SubgraphFork;
-use graph::components::subgraph::{MappingError, SharedProofOfIndexing};
+use graph::components::subgraph::{LogAddresses, MappingError, SharedProofOfIndexing};
 use graph::data_source::{MappingTrigger, TriggerWithHandler};
 use graph::prelude::*;
 use graph::runtime::gas::Gas;
+use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
 use std::panic::AssertUnwindSafe;
-use std::sync::Arc;
-use std::{panic, thread};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::{fs, panic, thread};
 
 /// Spawn a wasm module in its own thread.
 pub fn spawn_module<C: Blockchain>(
@@ -26,7 +29,14 @@ pub fn spawn_module<C: Blockchain>(
 where
     <C as Blockchain>::MappingTrigger: ToAscPtr,
 {
-    let valid_module = Arc::new(ValidModule::new(&logger, raw_module)?);
+    let valid_module = match &ENV_VARS.mappings.module_cache_dir {
+        Some(dir) => {
+            let cache = ModuleCache::new(dir.clone());
+            ValidModule::with_cache(&logger, raw_module, &cache, &subgraph_id)?
+        }
+        None => ValidModule::new(&logger, raw_module)?,
+    };
+    let valid_module = Arc::new(valid_module);
 
     // Create channel for event handling requests
     let (mapping_request_sender, mapping_request_receiver) = mpsc::channel(100);
@@ -139,6 +149,20 @@ pub struct MappingContext<C: Blockchain> {
     pub logger: Logger,
     pub host_exports: Arc<crate::host_exports::HostExports<C>>,
     pub block_ptr: BlockPtr,
+    /// The head of the chain when the trigger was dispatched. This is only meant for
+    /// non-deterministic host exports since it differs between indexers.
+    pub chain_head_ptr: Option<BlockPtr>,
+    /// The timestamp of `chain_head_ptr` in seconds, if the chain store knows it. Like
+    /// `chain_head_ptr`, this is only meant for non-deterministic host exports.
+    pub chain_head_timestamp: Option<u64>,
+    /// The onchain trigger being handled, for host exports that read details of it. It is
+    /// `None` for offchain triggers.
+    pub trigger: Option<Arc<C::MappingTrigger>>,
+    /// The raw output of the most recent contract call the handler made, see
+    /// `HostFnCtx::last_return_data`.
+    pub last_return_data: Arc<Mutex<Option<Vec<u8>>>>,
+    /// See `BlockState::log_addresses`.
+    pub block_log_addresses: Arc<LogAddresses>,
     pub state: BlockState<C>,
     pub proof_of_indexing: SharedProofOfIndexing,
     pub host_fns: Arc<Vec<HostFn>>,
@@ -155,6 +179,11 @@ impl<C: Blockchain> MappingContext<C> {
             logger: self.logger.cheap_clone(),
             host_exports: self.host_exports.cheap_clone(),
             block_ptr: self.block_ptr.cheap_clone(),
+            chain_head_ptr: self.chain_head_ptr.cheap_clone(),
+            chain_head_timestamp: self.chain_head_timestamp,
+            trigger: self.trigger.cheap_clone(),
+            last_return_data: Default::default(),
+            block_log_addresses: self.block_log_addresses.cheap_clone(),
             state: BlockState::new(self.state.entity_cache.store.clone(), Default::default()),
             proof_of_indexing: self.proof_of_indexing.cheap_clone(),
             host_fns: self.host_fns.cheap_clone(),
@@ -183,44 +212,33 @@ pub struct ValidModule {
 impl ValidModule {
     /// Pre-process and validate the module.
     pub fn new(logger: &Logger, raw_module: &[u8]) -> Result<Self, anyhow::Error> {
-        // Add the gas calls here. Module name "gas" must match. See also
-        // e3f03e62-40e4-4f8c-b4a1-d0375cca0b76. We do this by round-tripping the module through
-        // parity - injecting gas then serializing again.
-        let parity_module = parity_wasm::elements::Module::from_bytes(raw_module)?;
-        let parity_module = match parity_module.parse_names() {
-            Ok(module) => module,
-            Err((errs, module)) => {
-                for (index, err) in errs {
-                    warn!(
-                        logger,
-                        "unable to parse function name for index {}: {}",
-                        index,
-                        err.to_string()
-                    );
-                }
+        let engine = engine()?;
+        let module = compile(logger, &engine, raw_module)?;
+        Ok(Self::from_module(module))
+    }
 
+    /// Like `new`, but reuses the compiled module from `cache` if `raw_module` has been compiled
+    /// for `subgraph_id` before, and stores the compiled module in `cache` otherwise.
+    pub fn with_cache(
+        logger: &Logger,
+        raw_module: &[u8],
+        cache: &ModuleCache,
+        subgraph_id: &DeploymentHash,
+    ) -> Result<Self, anyhow::Error> {
+        let engine = engine()?;
+        let instrumented = instrument(logger, raw_module)?;
+        let module = match cache.load(logger, &engine, subgraph_id, &instrumented) {
+            Some(module) => module,
+            None => {
+                let module = wasmtime::Module::from_binary(&engine, &instrumented)?;
+                cache.store(logger, &module, subgraph_id, &instrumented);
                 module
             }
         };
-        let parity_module = wasm_instrument::gas_metering::inject(parity_module, &GasRules, "gas")
-            .map_err(|_| anyhow!("Failed to inject gas counter"))?;
-        let raw_module = parity_module.into_bytes()?;
-
-        // We currently use Cranelift as a compilation engine. Cranelift is an optimizing compiler,
-        // but that should not cause determinism issues since it adheres to the Wasm spec. Still we
-        // turn off optional optimizations to be conservative.
-        let mut config = wasmtime::Config::new();
-        config.strategy(wasmtime::Strategy::Cranelift).unwrap();
-        config.interruptable(true); // For timeouts.
-        config.cranelift_nan_canonicalization(true); // For NaN determinism.
-        config.cranelift_opt_level(wasmtime::OptLevel::None);
-        config
-            .max_wasm_stack(ENV_VARS.mappings.max_stack_size)
-            .unwrap(); // Safe because this only panics if size passed is 0.
-
-        let engine = &wasmtime::Engine::new(&config)?;
-        let module = wasmtime::Module::from_binary(engine, &raw_module)?;
+        Ok(Self::from_module(module))
+    }
 
+    fn from_module(module: wasmtime::Module) -> Self {
         let mut import_name_to_modules: BTreeMap<String, Vec<String>> = BTreeMap::new();
 
         // Unwrap: Module linking is disabled.
@@ -234,9 +252,160 @@ impl ValidModule {
                 .push(module.to_string());
         }
 
-        Ok(ValidModule {
+        ValidModule {
             module,
             import_name_to_modules,
-        })
+        }
+    }
+}
+
+/// Identifies the settings of `engine()` that affect the code it compiles, so that cached modules
+/// are not used with a different configuration.
+fn engine_config_key() -> String {
+    format!(
+        "cranelift;opt_level=none;nan_canonicalization;interruptable;max_wasm_stack={}",
+        ENV_VARS.mappings.max_stack_size
+    )
+}
+
+fn engine() -> Result<wasmtime::Engine, anyhow::Error> {
+    // We currently use Cranelift as a compilation engine. Cranelift is an optimizing compiler,
+    // but that should not cause determinism issues since it adheres to the Wasm spec. Still we
+    // turn off optional optimizations to be conservative. Changes here must be reflected in
+    // `engine_config_key`.
+    let mut config = wasmtime::Config::new();
+    config.strategy(wasmtime::Strategy::Cranelift).unwrap();
+    config.interruptable(true); // For timeouts.
+    config.cranelift_nan_canonicalization(true); // For NaN determinism.
+    config.cranelift_opt_level(wasmtime::OptLevel::None);
+    config
+        .max_wasm_stack(ENV_VARS.mappings.max_stack_size)
+        .unwrap(); // Safe because this only panics if size passed is 0.
+
+    wasmtime::Engine::new(&config)
+}
+
+/// Inject gas metering into `raw_module` and compile it.
+fn compile(
+    logger: &Logger,
+    engine: &wasmtime::Engine,
+    raw_module: &[u8],
+) -> Result<wasmtime::Module, anyhow::Error> {
+    let raw_module = instrument(logger, raw_module)?;
+    wasmtime::Module::from_binary(engine, &raw_module)
+}
+
+/// Inject gas metering into `raw_module`.
+fn instrument(logger: &Logger, raw_module: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+    // Add the gas calls here. Module name "gas" must match. See also
+    // e3f03e62-40e4-4f8c-b4a1-d0375cca0b76. We do this by round-tripping the module through
+    // parity - injecting gas then serializing again.
+    let parity_module = parity_wasm::elements::Module::from_bytes(raw_module)?;
+    let parity_module = match parity_module.parse_names() {
+        Ok(module) => module,
+        Err((errs, module)) => {
+            for (index, err) in errs {
+                warn!(
+                    logger,
+                    "unable to parse function name for index {}: {}",
+                    index,
+                    err.to_string()
+                );
+            }
+
+            module
+        }
+    };
+    let parity_module = wasm_instrument::gas_metering::inject(parity_module, &GasRules, "gas")
+        .map_err(|_| anyhow!("Failed to inject gas counter"))?;
+    Ok(parity_module.into_bytes()?)
+}
+
+/// An on-disk cache of compiled modules, so that restarting a subgraph does not recompile its
+/// mappings. Entries are keyed by the deployment and a hash of the module bytes after gas
+/// metering was injected, which captures the `GasRules`, together with the configuration of the
+/// engine and the graph-node version.
+///
+/// Failing to read or write an entry is never an error; the module is simply compiled.
+///
+/// Entries are loaded with `wasmtime::Module::deserialize`, which does not validate them and
+/// runs the machine code they contain as is. The cache directory must therefore only be
+/// writable by graph-node, since anybody who can write an entry can run arbitrary code in it.
+pub struct ModuleCache {
+    dir: PathBuf,
+    hits: AtomicU64,
+}
+
+impl ModuleCache {
+    pub fn new(dir: PathBuf) -> Self {
+        ModuleCache {
+            dir,
+            hits: AtomicU64::new(0),
+        }
+    }
+
+    /// How many modules were loaded from this cache instead of being compiled.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::SeqCst)
+    }
+
+    fn path(&self, subgraph_id: &DeploymentHash, instrumented_module: &[u8]) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+        hasher.update(engine_config_key().as_bytes());
+        hasher.update(instrumented_module);
+        self.dir
+            .join(format!("{}-{:x}.cwasm", subgraph_id, hasher.finalize()))
+    }
+
+    fn load(
+        &self,
+        logger: &Logger,
+        engine: &wasmtime::Engine,
+        subgraph_id: &DeploymentHash,
+        instrumented_module: &[u8],
+    ) -> Option<wasmtime::Module> {
+        let path = self.path(subgraph_id, instrumented_module);
+        let bytes = fs::read(&path).ok()?;
+        // This trusts `bytes` to be code that wasmtime compiled, see the docs of `ModuleCache`
+        match wasmtime::Module::deserialize(engine, &bytes) {
+            Ok(module) => {
+                self.hits.fetch_add(1, Ordering::SeqCst);
+                debug!(logger, "Loaded compiled module from cache"; "path" => %path.display());
+                Some(module)
+            }
+            // Most likely the entry was written with a different engine configuration, in which
+            // case it gets overwritten once the module is compiled
+            Err(e) => {
+                warn!(logger, "Failed to load compiled module from cache";
+                      "path" => %path.display(), "error" => %e);
+                None
+            }
+        }
+    }
+
+    fn store(
+        &self,
+        logger: &Logger,
+        module: &wasmtime::Module,
+        subgraph_id: &DeploymentHash,
+        instrumented_module: &[u8],
+    ) {
+        let path = self.path(subgraph_id, instrumented_module);
+
+        // Write to a temporary file first so that concurrent readers never see a partial entry
+        let tmp = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+        let write = || -> Result<(), anyhow::Error> {
+            let bytes = module.serialize()?;
+            fs::create_dir_all(&self.dir)?;
+            fs::write(&tmp, bytes)?;
+            fs::rename(&tmp, &path)?;
+            Ok(())
+        };
+        if let Err(e) = write() {
+            let _ = fs::remove_file(&tmp);
+            warn!(logger, "Failed to store compiled module in cache";
+                  "path" => %path.display(), "error" => %e);
+        }
     }
 }
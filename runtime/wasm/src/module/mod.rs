@@ -1,5 +1,5 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::convert::TryFrom;
 use std::mem::MaybeUninit;
 use std::ops::{Deref, DerefMut};
@@ -16,7 +16,7 @@ use never::Never;
 use semver::Version;
 use wasmtime::{Memory, Trap};
 
-use graph::blockchain::{Blockchain, HostFnCtx};
+use graph::blockchain::{Blockchain, HostFnCtx, MappingTriggerTrait};
 use graph::data::store;
 use graph::data::subgraph::schema::SubgraphError;
 use graph::data_source::{offchain, MappingTrigger, TriggerWithHandler};
@@ -172,12 +172,14 @@ fn is_trap_deterministic(trap: &Trap) -> bool {
 }
 
 impl<C: Blockchain> WasmInstance<C> {
+    /// Calls `handler_name` with one JSON value of the file passed to `ipfs.map`, and returns the
+    /// block state together with the gas used by the call.
     pub(crate) fn handle_json_callback(
         mut self,
         handler_name: &str,
         value: &serde_json::Value,
         user_data: &store::Value,
-    ) -> Result<BlockState<C>, anyhow::Error> {
+    ) -> Result<(BlockState<C>, Gas), anyhow::Error> {
         let gas = GasCounter::default();
         let value = asc_new(self.instance_ctx_mut().deref_mut(), value, &gas)?;
         let user_data = asc_new(self.instance_ctx_mut().deref_mut(), user_data, &gas)?;
@@ -194,10 +196,43 @@ impl<C: Blockchain> WasmInstance<C> {
 
         self.instance_ctx_mut().ctx.state.exit_handler();
 
-        Ok(self.take_ctx().ctx.state)
+        let gas_used = self.gas.get();
+        Ok((self.take_ctx().ctx.state, gas_used))
     }
 
-    pub(crate) fn handle_trigger(
+    /// Calls `handler_name` with the accumulator and one element of the array passed to
+    /// `json.reduce`, and returns the new accumulator together with the block state and the gas
+    /// used by the call.
+    pub(crate) fn handle_json_reduce_callback(
+        mut self,
+        handler_name: &str,
+        accumulator: &store::Value,
+        value: &serde_json::Value,
+    ) -> Result<(store::Value, BlockState<C>, Gas), anyhow::Error> {
+        let gas = GasCounter::default();
+        let accumulator = asc_new(self.instance_ctx_mut().deref_mut(), accumulator, &gas)?;
+        let value = asc_new(self.instance_ctx_mut().deref_mut(), value, &gas)?;
+
+        self.instance_ctx_mut().ctx.state.enter_handler();
+
+        // Invoke the callback
+        let result: u32 = self
+            .instance
+            .get_func(handler_name)
+            .with_context(|| format!("function {} not found", handler_name))?
+            .typed()?
+            .call((accumulator.wasm_ptr(), value.wasm_ptr()))
+            .with_context(|| format!("Failed to handle callback '{}'", handler_name))?;
+
+        self.instance_ctx_mut().ctx.state.exit_handler();
+
+        let result: AscPtr<AscEnum<StoreValueKind>> = result.into();
+        let accumulator = asc_get(self.instance_ctx().deref(), result, &gas)?;
+        let gas_used = self.gas.get();
+        Ok((accumulator, self.take_ctx().ctx.state, gas_used))
+    }
+
+    pub fn handle_trigger(
         mut self,
         trigger: TriggerWithHandler<MappingTrigger<C>>,
     ) -> Result<(BlockState<C>, Gas), MappingError>
@@ -315,7 +350,11 @@ impl<C: Blockchain> WasmInstance<C> {
                 .state
                 .exit_handler_and_discard_changes_due_to_error(subgraph_error);
         } else {
-            self.instance_ctx_mut().ctx.state.exit_handler();
+            let mut instance_ctx = self.instance_ctx_mut();
+            let ctx = &mut instance_ctx.ctx;
+            let data_source = &ctx.host_exports.data_source_name;
+            ctx.state.record_triggered_handler(data_source, handler);
+            ctx.state.exit_handler();
         }
 
         let gas = self.gas.get();
@@ -326,6 +365,7 @@ impl<C: Blockchain> WasmInstance<C> {
 #[derive(Copy, Clone)]
 pub struct ExperimentalFeatures {
     pub allow_non_deterministic_ipfs: bool,
+    pub allow_debug_host_exports: bool,
 }
 
 pub struct WasmInstanceContext<C: Blockchain> {
@@ -345,6 +385,10 @@ pub struct WasmInstanceContext<C: Blockchain> {
 
     pub(crate) experimental_features: ExperimentalFeatures,
 
+    // The names of all host exports that were linked into the instance, for
+    // `runtime.hasCapability`.
+    pub(crate) linked_host_exports: Arc<BTreeSet<&'static str>>,
+
     asc_heap: AscHeapCtx,
 }
 
@@ -419,12 +463,18 @@ impl<C: Blockchain> WasmInstance<C> {
         let gas = GasCounter::default();
         let deterministic_host_trap = Rc::new(AtomicBool::new(false));
 
+        // Every host export is recorded here as it is linked, whether the module imports it or
+        // not, so that `runtime.hasCapability` can't disagree with what is actually linked.
+        let linked_host_exports: Rc<RefCell<BTreeSet<&'static str>>> = Default::default();
+
         macro_rules! link {
             ($wasm_name:expr, $rust_name:ident, $($param:ident),*) => {
                 link!($wasm_name, $rust_name, "host_export_other", $($param),*)
             };
 
             ($wasm_name:expr, $rust_name:ident, $section:expr, $($param:ident),*) => {
+                linked_host_exports.borrow_mut().insert($wasm_name);
+
                 let modules = valid_module
                     .import_name_to_modules
                     .get($wasm_name)
@@ -439,6 +489,7 @@ impl<C: Blockchain> WasmInstance<C> {
                     let timeout_stopwatch = timeout_stopwatch.cheap_clone();
                     let ctx = ctx.cheap_clone();
                     let gas = gas.cheap_clone();
+                    let linked_host_exports = linked_host_exports.cheap_clone();
                     linker.func(
                         module,
                         $wasm_name,
@@ -455,7 +506,8 @@ impl<C: Blockchain> WasmInstance<C> {
                                     host_metrics.cheap_clone(),
                                     timeout,
                                     timeout_stopwatch.cheap_clone(),
-                                    experimental_features.clone()
+                                    experimental_features.clone(),
+                                    Arc::new(linked_host_exports.borrow().clone()),
                                 ).unwrap())
                             }
 
@@ -490,6 +542,8 @@ impl<C: Blockchain> WasmInstance<C> {
 
         // Link chain-specifc host fns.
         for host_fn in host_fns.iter() {
+            linked_host_exports.borrow_mut().insert(host_fn.name);
+
             let modules = valid_module
                 .import_name_to_modules
                 .get(host_fn.name)
@@ -523,11 +577,14 @@ impl<C: Blockchain> WasmInstance<C> {
                     let _section =
                         stopwatch.start_section(&format!("host_export_{}", name_for_metrics));
 
+                    let mut chain_read_block = instance.ctx.block_ptr.number;
                     let ctx = HostFnCtx {
                         logger: instance.ctx.logger.cheap_clone(),
                         block_ptr: instance.ctx.block_ptr.cheap_clone(),
+                        last_return_data: instance.ctx.last_return_data.clone(),
                         heap: instance,
                         gas: gas.cheap_clone(),
+                        chain_read_block: &mut chain_read_block,
                     };
                     let ret = (host_fn.func)(ctx, call_ptr).map_err(|e| match e {
                         HostExportError::Deterministic(e) => {
@@ -540,6 +597,11 @@ impl<C: Blockchain> WasmInstance<C> {
                         }
                         HostExportError::Unknown(e) => e,
                     })?;
+                    instance.ctx.host_exports.record_chain_read(
+                        &mut instance.ctx.state,
+                        chain_read_block,
+                        instance.ctx.chain_head_ptr.as_ref(),
+                    );
                     instance.host_metrics.observe_host_fn_execution_time(
                         start.elapsed().as_secs_f64(),
                         &name_for_metrics,
@@ -551,6 +613,8 @@ impl<C: Blockchain> WasmInstance<C> {
 
         link!("ethereum.encode", ethereum_encode, params_ptr);
         link!("ethereum.decode", ethereum_decode, params_ptr, data_ptr);
+        link!("ethereum.encodePacked", ethereum_encode_packed, tokens_ptr);
+        link!("ethereum.lastReturnData", ethereum_last_return_data,);
 
         link!("abort", abort, message_ptr, file_name_ptr, line, column);
 
@@ -570,6 +634,13 @@ impl<C: Blockchain> WasmInstance<C> {
             entity,
             id
         );
+        link!(
+            "store.getInBlock",
+            store_get_in_block,
+            "host_export_store_get_in_block",
+            entity,
+            id
+        );
         link!(
             "store.set",
             store_set,
@@ -578,6 +649,14 @@ impl<C: Blockchain> WasmInstance<C> {
             id,
             data
         );
+        link!(
+            "store.setMany",
+            store_set_many,
+            "host_export_store_set_many",
+            entities,
+            ids,
+            data
+        );
 
         // All IPFS-related functions exported by the host WASM runtime should be listed in the
         // graph::data::subgraph::features::IPFS_ON_ETHEREUM_CONTRACTS_FUNCTION_NAMES array for
@@ -585,6 +664,13 @@ impl<C: Blockchain> WasmInstance<C> {
         //
         // For reference, search this codebase for: ff652476-e6ad-40e4-85b8-e815d6c6e5e2
         link!("ipfs.cat", ipfs_cat, "host_export_ipfs_cat", hash_ptr);
+        link!(
+            "ipfs.catWithTimeout",
+            ipfs_cat_with_timeout,
+            "host_export_ipfs_cat",
+            hash_ptr,
+            timeout_ms
+        );
         link!(
             "ipfs.map",
             ipfs_map,
@@ -603,14 +689,91 @@ impl<C: Blockchain> WasmInstance<C> {
                 hash_ptr
             );
         }
+        if experimental_features.allow_debug_host_exports {
+            link!("dataSource.resetCreated", data_source_reset_created,);
+            link!("store.cacheBytes", store_cache_bytes,);
+            link!("runtime.headTimestamp", chain_head_timestamp,);
+            link!("block.confirmations", block_confirmations,);
+            link!("runtime.isBackfilling", is_backfilling,);
+            link!("runtime.totalEntityWrites", runtime_total_entity_writes,);
+        }
 
         link!("store.remove", store_remove, entity_ptr, id_ptr);
+        link!("store.appendRaw", store_append_raw, tag_ptr, payload_ptr);
+        link!("store.blockCommitment", block_entity_commitment,);
+        link!("store.changedKeys", store_changed_keys,);
+        link!(
+            "store.entityToJson",
+            store_entity_to_json,
+            entity_ptr,
+            id_ptr
+        );
+        link!(
+            "store.isNewInBlock",
+            store_is_new_in_block,
+            entity_ptr,
+            id_ptr
+        );
+        link!(
+            "store.lastModifiedBlock",
+            store_last_modified_block,
+            entity_ptr,
+            id_ptr
+        );
+
+        link!("string.matches", string_matches, string_ptr, pattern_ptr);
+        link!("string.semverCompare", semver_compare, x_ptr, y_ptr);
 
         link!("typeConversion.bytesToString", bytes_to_string, ptr);
+        link!(
+            "typeConversion.bytesToStringUtf16",
+            bytes_to_string_utf16,
+            ptr,
+            little_endian
+        );
+        link!(
+            "typeConversion.bytesToStringLatin1",
+            bytes_to_string_latin1,
+            ptr
+        );
+        link!("typeConversion.stringToBytes", string_to_bytes, ptr);
+        link!("typeConversion.bytesIsValidUtf8", bytes_is_valid_utf8, ptr);
+        link!(
+            "typeConversion.bytesToU32",
+            bytes_to_u32,
+            ptr,
+            little_endian
+        );
+        link!(
+            "typeConversion.bytesToI32",
+            bytes_to_i32,
+            ptr,
+            little_endian
+        );
         link!("typeConversion.bytesToHex", bytes_to_hex, ptr);
         link!("typeConversion.bigIntToString", big_int_to_string, ptr);
+        link!(
+            "typeConversion.bigIntToBigDecimal",
+            big_int_to_big_decimal,
+            ptr
+        );
         link!("typeConversion.bigIntToHex", big_int_to_hex, ptr);
         link!("typeConversion.stringToH160", string_to_h160, ptr);
+        link!("typeConversion.normalizeAddress", address_normalize, ptr);
+        link!(
+            "typeConversion.hexToSignedBigInt",
+            hex_to_signed_big_int,
+            hex_ptr,
+            width
+        );
+        link!("typeConversion.isZeroAddress", is_zero_address, ptr);
+        link!(
+            "typeConversion.formatTokenAmount",
+            format_token_amount,
+            amount_ptr,
+            decimals,
+            precision
+        );
         link!("typeConversion.bytesToBase58", bytes_to_base58, ptr);
 
         link!("json.fromBytes", json_from_bytes, ptr);
@@ -619,8 +782,29 @@ impl<C: Blockchain> WasmInstance<C> {
         link!("json.toU64", json_to_u64, ptr);
         link!("json.toF64", json_to_f64, ptr);
         link!("json.toBigInt", json_to_big_int, ptr);
+        link!("json.toArray", json_to_array, ptr);
+        link!("json.toObject", json_to_object, ptr);
+        link!("json.toBool", json_to_bool, ptr);
+        link!("json.toString", json_to_string, ptr);
+        link!("json.reduce", json_reduce, array_ptr, callback, initial);
 
         link!("crypto.keccak256", crypto_keccak_256, ptr);
+        link!("crypto.sha256", crypto_sha256, ptr);
+        link!("crypto.sha3", crypto_sha3, ptr);
+        link!(
+            "crypto.structHash",
+            crypto_struct_hash,
+            type_hash_ptr,
+            fields_ptr
+        );
+        link!(
+            "crypto.ecrecover",
+            crypto_ecrecover,
+            hash_ptr,
+            v,
+            r_ptr,
+            s_ptr
+        );
 
         link!("bigInt.plus", big_int_plus, x_ptr, y_ptr);
         link!("bigInt.minus", big_int_minus, x_ptr, y_ptr);
@@ -632,16 +816,126 @@ impl<C: Blockchain> WasmInstance<C> {
         link!("bigInt.fromString", big_int_from_string, ptr);
         link!("bigInt.bitOr", big_int_bit_or, x_ptr, y_ptr);
         link!("bigInt.bitAnd", big_int_bit_and, x_ptr, y_ptr);
+        link!("bigInt.bitXor", big_int_bit_xor, x_ptr, y_ptr);
         link!("bigInt.leftShift", big_int_left_shift, x_ptr, bits);
         link!("bigInt.rightShift", big_int_right_shift, x_ptr, bits);
+        link!("bigInt.nextPowerOfTwo", big_int_next_power_of_two, x_ptr);
+        link!("bigInt.absDiff", big_int_abs_diff, x_ptr, y_ptr);
+        link!(
+            "bigInt.linearRecurrence",
+            big_int_linear_recurrence,
+            coefficients_ptr,
+            seeds_ptr,
+            n
+        );
+        link!(
+            "bigInt.rescale",
+            rescale_amount,
+            amount_ptr,
+            from_decimals,
+            to_decimals
+        );
 
         link!("bigDecimal.toString", big_decimal_to_string, ptr);
+        link!(
+            "bigDecimal.toStringTrimmed",
+            big_decimal_to_string_trimmed,
+            ptr
+        );
         link!("bigDecimal.fromString", big_decimal_from_string, ptr);
+        link!("bigDecimal.fromBigInt", big_int_to_big_decimal, ptr);
+        link!(
+            "bigDecimal.fromScientific",
+            big_decimal_from_scientific,
+            ptr
+        );
         link!("bigDecimal.plus", big_decimal_plus, x_ptr, y_ptr);
         link!("bigDecimal.minus", big_decimal_minus, x_ptr, y_ptr);
         link!("bigDecimal.times", big_decimal_times, x_ptr, y_ptr);
         link!("bigDecimal.dividedBy", big_decimal_divided_by, x, y);
+        link!("bigDecimal.mod", big_decimal_mod, x, y);
+        link!("bigDecimal.divOr", big_decimal_div_or, x, y, default);
+        link!("bigDecimal.inverse", big_decimal_inverse, x_ptr);
+        link!("bigDecimal.lerp", big_decimal_lerp, a_ptr, b_ptr, t_ptr);
+        link!(
+            "bigDecimal.twapUpdate",
+            big_decimal_twap_update,
+            accumulator_ptr,
+            price_ptr,
+            elapsed_ptr
+        );
+        link!(
+            "bigDecimal.compound",
+            big_decimal_compound,
+            principal_ptr,
+            rate_ptr,
+            periods
+        );
+        link!("bigDecimal.sum", big_decimal_sum, values_ptr);
+        link!("bigDecimal.geomean", big_decimal_geomean, values_ptr);
+        link!("bigDecimal.variance", big_decimal_variance, values_ptr);
+        link!("bigDecimal.stddev", big_decimal_stddev, values_ptr);
+        link!(
+            "bigDecimal.weightedMedian",
+            big_decimal_weighted_median,
+            values_ptr,
+            weights_ptr
+        );
+        link!("bigDecimal.toFixed", big_decimal_to_fixed, x_ptr, decimals);
+        link!(
+            "bigDecimal.priceImpact",
+            big_decimal_price_impact,
+            reserve_in_ptr,
+            reserve_out_ptr,
+            amount_in_ptr
+        );
         link!("bigDecimal.equals", big_decimal_equals, x_ptr, y_ptr);
+        link!(
+            "bigDecimal.approxEquals",
+            big_decimal_approx_equals,
+            x_ptr,
+            y_ptr,
+            tolerance_ptr
+        );
+
+        link!(
+            "runtime.timestampBucket",
+            timestamp_to_bucket,
+            ts_ptr,
+            bucket_size
+        );
+        link!("runtime.metricInc", metric_increment, name_ptr, value_ptr);
+        link!("runtime.warn", emit_warning, message_ptr);
+        link!("runtime.memoryPages", runtime_memory_pages,);
+        link!("runtime.hasCapability", host_has_capability, name_ptr);
+        link!("runtime.entityTypes", schema_entity_types,);
+        link!(
+            "runtime.fieldType",
+            schema_field_type,
+            entity_type_ptr,
+            field_ptr
+        );
+
+        link!(
+            "block.triggeredHandler",
+            block_triggered_handler,
+            handler_ptr
+        );
+        link!("block.bloomContains", block_bloom_contains, value_ptr);
+        link!("block.headerHash", block_header_hash,);
+        link!("block.uniqueLogAddresses", block_unique_log_addresses,);
+
+        link!("context.blockNumber", block_number,);
+        link!("context.blockHash", block_hash,);
+
+        link!("array.dedup", array_dedup, values_ptr);
+        link!(
+            "array.bigIntSortedUnique",
+            array_big_int_sorted_unique,
+            values_ptr
+        );
+
+        link!("call.input", call_input, inputs_ptr, name_ptr);
 
         link!("dataSource.create", data_source_create, name, params);
         link!(
@@ -652,12 +946,21 @@ impl<C: Blockchain> WasmInstance<C> {
             context
         );
         link!("dataSource.address", data_source_address,);
+        link!("dataSource.creatorAddress", data_source_creator_address,);
         link!("dataSource.network", data_source_network,);
+        link!("dataSource.instanceIndex", data_source_instance_index,);
         link!("dataSource.context", data_source_context,);
 
         link!("ens.nameByHash", ens_name_by_hash, ptr);
 
         link!("log.log", log_log, level, msg_ptr);
+        link!("log.logKv", log_log_kv, level, msg_ptr, fields_ptr);
+        link!("log.signatureOrdinal", log_signature_ordinal,);
+
+        link!("event.paramsMap", event_params_map,);
+        link!("transaction.selector", transaction_selector,);
+        link!("transaction.gasUsed", transaction_gas_used,);
+        link!("log.isOwnAddress", log_is_own_address,);
 
         // `arweave and `box` functionality was removed, but apiVersion <= 0.0.4 must link it.
         if api_version <= Version::new(0, 0, 4) {
@@ -695,6 +998,7 @@ impl<C: Blockchain> WasmInstance<C> {
                 timeout,
                 timeout_stopwatch,
                 experimental_features,
+                Arc::new(linked_host_exports.take()),
             )?);
         }
 
@@ -769,30 +1073,29 @@ impl AscHeap for AscHeapCtx {
         if size > self.arena_free_size {
             // Allocate a new arena. Any free space left in the previous arena is left unused. This
             // causes at most half of memory to be wasted, which is acceptable.
-            let arena_size = size.max(MIN_ARENA_SIZE);
+            let header_offset = match &self.api_version {
+                version if *version <= Version::new(0, 0, 4) => 0,
+                // This arithmetic is done because when you call AssemblyScripts's `__alloc`
+                // function, it isn't typed and it just returns `mmInfo` on it's header,
+                // differently from allocating on regular types (`__new` for example).
+                // `mmInfo` has size of 4, and everything allocated on AssemblyScript memory
+                // should have alignment of 16, this means we need to do a 12 offset on these
+                // big chunks of untyped allocation.
+                _ => 12,
+            };
+            let arena_size = (size + header_offset).max(MIN_ARENA_SIZE);
 
             // Unwrap: This may panic if more memory needs to be requested from the OS and that
             // fails. This error is not deterministic since it depends on the operating conditions
             // of the node.
-            self.arena_start_ptr = self.memory_allocate.call(arena_size).unwrap();
-            self.arena_free_size = arena_size;
-
-            match &self.api_version {
-                version if *version <= Version::new(0, 0, 4) => {}
-                _ => {
-                    // This arithmetic is done because when you call AssemblyScripts's `__alloc`
-                    // function, it isn't typed and it just returns `mmInfo` on it's header,
-                    // differently from allocating on regular types (`__new` for example).
-                    // `mmInfo` has size of 4, and everything allocated on AssemblyScript memory
-                    // should have alignment of 16, this means we need to do a 12 offset on these
-                    // big chunks of untyped allocation.
-                    self.arena_start_ptr += 12;
-                    self.arena_free_size -= 12;
-                }
-            };
+            self.arena_start_ptr = self.memory_allocate.call(arena_size).unwrap() + header_offset;
+            self.arena_free_size = arena_size - header_offset;
         };
 
         let ptr = self.arena_start_ptr as usize;
+        debug_assert!(
+            ptr + bytes.len() <= self.arena_start_ptr as usize + self.arena_free_size as usize
+        );
 
         // Unwrap: We have just allocated enough space for `bytes`.
         self.memory.write(ptr, bytes).unwrap();
@@ -873,6 +1176,7 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         timeout: Option<Duration>,
         timeout_stopwatch: Arc<std::sync::Mutex<TimeoutStopwatch>>,
         experimental_features: ExperimentalFeatures,
+        linked_host_exports: Arc<BTreeSet<&'static str>>,
     ) -> Result<Self, anyhow::Error> {
         // Provide access to the WASM runtime linear memory
         let memory = instance
@@ -918,6 +1222,7 @@ impl<C: Blockchain> WasmInstanceContext<C> {
             possible_reorg: false,
             deterministic_host_trap: false,
             experimental_features,
+            linked_host_exports,
         })
     }
 
@@ -929,6 +1234,7 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         timeout: Option<Duration>,
         timeout_stopwatch: Arc<std::sync::Mutex<TimeoutStopwatch>>,
         experimental_features: ExperimentalFeatures,
+        linked_host_exports: Arc<BTreeSet<&'static str>>,
     ) -> Result<Self, anyhow::Error> {
         let memory = caller
             .get_export("memory")
@@ -977,6 +1283,7 @@ impl<C: Blockchain> WasmInstanceContext<C> {
             possible_reorg: false,
             deterministic_host_trap: false,
             experimental_features,
+            linked_host_exports,
         })
     }
 
@@ -1115,6 +1422,55 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         Ok(())
     }
 
+    /// function store.setMany(entities: Array<string>, ids: Array<string>, data: Array<Entity>): void
+    ///
+    /// Equivalent to calling `store.set` for each position in the arrays, which must all have
+    /// the same length, but crosses the host boundary only once.
+    pub fn store_set_many(
+        &mut self,
+        gas: &GasCounter,
+        entities_ptr: AscPtr<Array<AscPtr<AscString>>>,
+        ids_ptr: AscPtr<Array<AscPtr<AscString>>>,
+        data_ptr: AscPtr<Array<AscPtr<AscEntity>>>,
+    ) -> Result<(), HostExportError> {
+        let stopwatch = &self.host_metrics.stopwatch;
+        stopwatch.start_section("host_export_store_set_many__wasm_instance_context_store_set_many");
+
+        let entities: Vec<String> = asc_get(self, entities_ptr, gas)?;
+        let ids: Vec<String> = asc_get(self, ids_ptr, gas)?;
+        let data: Vec<HashMap<Word, store::Value>> = asc_get(self, data_ptr, gas)?;
+
+        if entities.len() != ids.len() || entities.len() != data.len() {
+            return Err(HostExportError::Deterministic(anyhow!(
+                "store.setMany expects arrays of the same length, but got {} entity types, {} ids \
+                 and {} entities",
+                entities.len(),
+                ids.len(),
+                data.len()
+            )));
+        }
+
+        if self.ctx.instrument {
+            debug!(self.ctx.logger, "store_set_many";
+                    "count" => entities.len());
+        }
+
+        for ((entity, id), data) in entities.into_iter().zip(ids).zip(data) {
+            self.ctx.host_exports.store_set(
+                &self.ctx.logger,
+                &mut self.ctx.state,
+                &self.ctx.proof_of_indexing,
+                entity,
+                id,
+                data,
+                stopwatch,
+                gas,
+            )?;
+        }
+
+        Ok(())
+    }
+
     /// function store.remove(entity: string, id: string): void
     pub fn store_remove(
         &mut self,
@@ -1139,6 +1495,20 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         )
     }
 
+    /// function store.appendRaw(tag: string, payload: string): void
+    pub fn store_append_raw(
+        &mut self,
+        gas: &GasCounter,
+        tag_ptr: AscPtr<AscString>,
+        payload_ptr: AscPtr<AscString>,
+    ) -> Result<(), HostExportError> {
+        let tag: String = asc_get(self, tag_ptr, gas)?;
+        let payload: String = asc_get(self, payload_ptr, gas)?;
+        self.ctx
+            .host_exports
+            .store_append_raw(&mut self.ctx.state, tag, payload, gas)
+    }
+
     /// function store.get(entity: string, id: string): Entity | null
     pub fn store_get(
         &mut self,
@@ -1150,6 +1520,8 @@ impl<C: Blockchain> WasmInstanceContext<C> {
     }
 
     /// function store.get_in_block(entity: string, id: string): Entity | null
+    ///
+    /// Also linked as `store.getInBlock`.
     pub fn store_get_in_block(
         &mut self,
         gas: &GasCounter,
@@ -1159,6 +1531,27 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         self.store_get_scoped(gas, entity_ptr, id_ptr, GetScope::InBlock)
     }
 
+    /// function store.entityToJson(entity: string, id: string): string | null
+    pub fn store_entity_to_json(
+        &mut self,
+        gas: &GasCounter,
+        entity_ptr: AscPtr<AscString>,
+        id_ptr: AscPtr<AscString>,
+    ) -> Result<AscPtr<AscString>, HostExportError> {
+        let entity_type: String = asc_get(self, entity_ptr, gas)?;
+        let id: String = asc_get(self, id_ptr, gas)?;
+        let json = self.ctx.host_exports.store_entity_to_json(
+            &mut self.ctx.state,
+            entity_type,
+            id,
+            gas,
+        )?;
+        match json {
+            Some(json) => asc_new(self, json.as_str(), gas),
+            None => Ok(AscPtr::null()),
+        }
+    }
+
     /// function store.loadRelated(entity_type: string, id: string, field: string): Array<Entity>
     pub fn store_load_related(
         &mut self,
@@ -1184,6 +1577,105 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         Ok(ret)
     }
 
+    /// function store.cacheBytes(): BigInt
+    pub fn store_cache_bytes(
+        &mut self,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<AscBigInt>, HostExportError> {
+        let bytes = self
+            .ctx
+            .host_exports
+            .store_cache_bytes(&self.ctx.state, gas)?;
+        asc_new(self, &bytes, gas)
+    }
+
+    /// function store.changedKeys(): Array<string>
+    pub fn store_changed_keys(
+        &mut self,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<Array<AscPtr<AscString>>>, HostExportError> {
+        let keys = self
+            .ctx
+            .host_exports
+            .store_changed_keys(&self.ctx.state, gas)?;
+        asc_new(self, &keys, gas)
+    }
+
+    /// function store.blockCommitment(): Bytes
+    pub fn block_entity_commitment(
+        &mut self,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let commitment = self
+            .ctx
+            .host_exports
+            .block_entity_commitment(&self.ctx.state, gas)?;
+        asc_new(self, commitment.as_ref(), gas)
+    }
+
+    /// function store.isNewInBlock(entity: string, id: string): bool
+    pub fn store_is_new_in_block(
+        &mut self,
+        gas: &GasCounter,
+        entity_ptr: AscPtr<AscString>,
+        id_ptr: AscPtr<AscString>,
+    ) -> Result<bool, HostExportError> {
+        let entity_type: String = asc_get(self, entity_ptr, gas)?;
+        let id: String = asc_get(self, id_ptr, gas)?;
+        Ok(self.ctx.host_exports.store_is_new_in_block(
+            &mut self.ctx.state,
+            entity_type,
+            id,
+            gas,
+        )?)
+    }
+
+    /// function store.lastModifiedBlock(entity: string, id: string): BigInt | null
+    pub fn store_last_modified_block(
+        &mut self,
+        gas: &GasCounter,
+        entity_ptr: AscPtr<AscString>,
+        id_ptr: AscPtr<AscString>,
+    ) -> Result<AscPtr<AscBigInt>, HostExportError> {
+        let entity_type: String = asc_get(self, entity_ptr, gas)?;
+        let id: String = asc_get(self, id_ptr, gas)?;
+        let block = self.ctx.host_exports.store_last_modified_block(
+            &self.ctx.state,
+            entity_type,
+            id,
+            self.ctx.block_ptr.number,
+            gas,
+        )?;
+        match block {
+            Some(block) => asc_new(self, &block, gas),
+            None => Ok(AscPtr::null()),
+        }
+    }
+
+    /// function string.matches(string: string, pattern: string): bool
+    pub fn string_matches(
+        &mut self,
+        gas: &GasCounter,
+        string_ptr: AscPtr<AscString>,
+        pattern_ptr: AscPtr<AscString>,
+    ) -> Result<bool, HostExportError> {
+        let string: String = asc_get(self, string_ptr, gas)?;
+        let pattern: String = asc_get(self, pattern_ptr, gas)?;
+        Ok(self.ctx.host_exports.string_matches(string, pattern, gas)?)
+    }
+
+    /// function string.semverCompare(x: string, y: string): i32
+    pub fn semver_compare(
+        &mut self,
+        gas: &GasCounter,
+        x_ptr: AscPtr<AscString>,
+        y_ptr: AscPtr<AscString>,
+    ) -> Result<i32, HostExportError> {
+        let x: String = asc_get(self, x_ptr, gas)?;
+        let y: String = asc_get(self, y_ptr, gas)?;
+        Ok(self.ctx.host_exports.semver_compare(x, y, gas)?)
+    }
+
     /// function typeConversion.bytesToString(bytes: Bytes): string
     pub fn bytes_to_string(
         &mut self,
@@ -1198,6 +1690,87 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         asc_new(self, &string, gas)
     }
 
+    /// function typeConversion.bytesToStringUtf16(bytes: Bytes, littleEndian: bool): string
+    pub fn bytes_to_string_utf16(
+        &mut self,
+        gas: &GasCounter,
+        bytes_ptr: AscPtr<Uint8Array>,
+        little_endian: u32,
+    ) -> Result<AscPtr<AscString>, HostExportError> {
+        let string = self.ctx.host_exports.bytes_to_string_utf16(
+            asc_get(self, bytes_ptr, gas)?,
+            little_endian != 0,
+            gas,
+        )?;
+        asc_new(self, &string, gas)
+    }
+
+    /// function typeConversion.bytesToStringLatin1(bytes: Bytes): string
+    pub fn bytes_to_string_latin1(
+        &mut self,
+        gas: &GasCounter,
+        bytes_ptr: AscPtr<Uint8Array>,
+    ) -> Result<AscPtr<AscString>, HostExportError> {
+        let string = self
+            .ctx
+            .host_exports
+            .bytes_to_string_latin1(asc_get(self, bytes_ptr, gas)?, gas)?;
+        asc_new(self, &string, gas)
+    }
+
+    /// function typeConversion.bytesIsValidUtf8(bytes: Bytes): bool
+    pub fn bytes_is_valid_utf8(
+        &mut self,
+        gas: &GasCounter,
+        bytes_ptr: AscPtr<Uint8Array>,
+    ) -> Result<bool, HostExportError> {
+        Ok(self
+            .ctx
+            .host_exports
+            .bytes_is_valid_utf8(asc_get(self, bytes_ptr, gas)?, gas)?)
+    }
+
+    /// function typeConversion.bytesToU32(bytes: Bytes, littleEndian: bool): u32
+    pub fn bytes_to_u32(
+        &mut self,
+        gas: &GasCounter,
+        bytes_ptr: AscPtr<Uint8Array>,
+        little_endian: u32,
+    ) -> Result<u32, HostExportError> {
+        Ok(self.ctx.host_exports.bytes_to_u32(
+            asc_get(self, bytes_ptr, gas)?,
+            little_endian != 0,
+            gas,
+        )?)
+    }
+
+    /// function typeConversion.bytesToI32(bytes: Bytes, littleEndian: bool): i32
+    pub fn bytes_to_i32(
+        &mut self,
+        gas: &GasCounter,
+        bytes_ptr: AscPtr<Uint8Array>,
+        little_endian: u32,
+    ) -> Result<i32, HostExportError> {
+        Ok(self.ctx.host_exports.bytes_to_i32(
+            asc_get(self, bytes_ptr, gas)?,
+            little_endian != 0,
+            gas,
+        )?)
+    }
+
+    /// function typeConversion.stringToBytes(s: string): Bytes
+    pub fn string_to_bytes(
+        &mut self,
+        gas: &GasCounter,
+        str_ptr: AscPtr<AscString>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let bytes = self
+            .ctx
+            .host_exports
+            .string_to_bytes(asc_get(self, str_ptr, gas)?, gas)?;
+        asc_new(self, &*bytes, gas)
+    }
+
     /// Converts bytes to a hex string.
     /// function typeConversion.bytesToHex(bytes: Bytes): string
     /// References:
@@ -1263,24 +1836,81 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         asc_new(self, &h160, gas)
     }
 
-    /// function json.fromBytes(bytes: Bytes): JSONValue
-    pub fn json_from_bytes(
+    /// function typeConversion.hexToSignedBigInt(hex: string, width: u32): BigInt
+    pub fn hex_to_signed_big_int(
         &mut self,
         gas: &GasCounter,
-        bytes_ptr: AscPtr<Uint8Array>,
-    ) -> Result<AscPtr<AscEnum<JsonValueKind>>, HostExportError> {
-        let bytes: Vec<u8> = asc_get(self, bytes_ptr, gas)?;
-        let result = self
+        hex_ptr: AscPtr<AscString>,
+        width: u32,
+    ) -> Result<AscPtr<AscBigInt>, HostExportError> {
+        let hex: String = asc_get(self, hex_ptr, gas)?;
+        let n = self
             .ctx
             .host_exports
-            .json_from_bytes(&bytes, gas)
-            .with_context(|| {
-                format!(
-                    "Failed to parse JSON from byte array. Bytes (truncated to 1024 chars): `{:?}`",
-                    &bytes[..bytes.len().min(1024)],
-                )
-            })
-            .map_err(DeterministicHostError::from)?;
+            .hex_to_signed_big_int(&hex, width, gas)?;
+        asc_new(self, &n, gas)
+    }
+
+    /// function typeConversion.formatTokenAmount(amount: BigInt, decimals: u8, precision: u8): string
+    pub fn format_token_amount(
+        &mut self,
+        gas: &GasCounter,
+        amount_ptr: AscPtr<AscBigInt>,
+        decimals: u32,
+        precision: u32,
+    ) -> Result<AscPtr<AscString>, HostExportError> {
+        let decimals =
+            u8::try_from(decimals).map_err(|e| DeterministicHostError::from(Error::from(e)))?;
+        let precision =
+            u8::try_from(precision).map_err(|e| DeterministicHostError::from(Error::from(e)))?;
+        let result = self.ctx.host_exports.format_token_amount(
+            asc_get(self, amount_ptr, gas)?,
+            decimals,
+            precision,
+            gas,
+        )?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function typeConversion.normalizeAddress(address: string): string
+    pub fn address_normalize(
+        &mut self,
+        gas: &GasCounter,
+        address_ptr: AscPtr<AscString>,
+    ) -> Result<AscPtr<AscString>, HostExportError> {
+        let address: String = asc_get(self, address_ptr, gas)?;
+        let normalized = self.ctx.host_exports.address_normalize(&address, gas)?;
+        asc_new(self, &normalized, gas)
+    }
+
+    /// function typeConversion.isZeroAddress(address: Bytes): bool
+    pub fn is_zero_address(
+        &mut self,
+        gas: &GasCounter,
+        address_ptr: AscPtr<Uint8Array>,
+    ) -> Result<bool, HostExportError> {
+        let address: Vec<u8> = asc_get(self, address_ptr, gas)?;
+        Ok(self.ctx.host_exports.is_zero_address(&address, gas)?)
+    }
+
+    /// function json.fromBytes(bytes: Bytes): JSONValue
+    pub fn json_from_bytes(
+        &mut self,
+        gas: &GasCounter,
+        bytes_ptr: AscPtr<Uint8Array>,
+    ) -> Result<AscPtr<AscEnum<JsonValueKind>>, HostExportError> {
+        let bytes: Vec<u8> = asc_get(self, bytes_ptr, gas)?;
+        let result = self
+            .ctx
+            .host_exports
+            .json_from_bytes(&bytes, gas)
+            .with_context(|| {
+                format!(
+                    "Failed to parse JSON from byte array. Bytes (truncated to 1024 chars): `{:?}`",
+                    &bytes[..bytes.len().min(1024)],
+                )
+            })
+            .map_err(DeterministicHostError::from)?;
         asc_new(self, &result, gas)
     }
 
@@ -1315,6 +1945,26 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         &mut self,
         gas: &GasCounter,
         link_ptr: AscPtr<AscString>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        self.ipfs_cat_with_optional_timeout(gas, link_ptr, None)
+    }
+
+    /// function ipfs.catWithTimeout(link: String, timeoutMs: u32): Bytes
+    pub fn ipfs_cat_with_timeout(
+        &mut self,
+        gas: &GasCounter,
+        link_ptr: AscPtr<AscString>,
+        timeout_ms: u32,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let timeout = Duration::from_millis(timeout_ms as u64);
+        self.ipfs_cat_with_optional_timeout(gas, link_ptr, Some(timeout))
+    }
+
+    fn ipfs_cat_with_optional_timeout(
+        &mut self,
+        gas: &GasCounter,
+        link_ptr: AscPtr<AscString>,
+        timeout: Option<Duration>,
     ) -> Result<AscPtr<Uint8Array>, HostExportError> {
         // Note on gas: There is no gas costing for the ipfs call itself,
         // since it's not enabled on the network.
@@ -1326,7 +1976,11 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         }
 
         let link = asc_get(self, link_ptr, gas)?;
-        let ipfs_res = self.ctx.host_exports.ipfs_cat(&self.ctx.logger, link);
+        let start = Instant::now();
+        let ipfs_res = self
+            .ctx
+            .host_exports
+            .ipfs_cat(&self.ctx.logger, link, timeout);
         match ipfs_res {
             Ok(bytes) => asc_new(self, &*bytes, gas).map_err(Into::into),
 
@@ -1334,7 +1988,8 @@ impl<C: Blockchain> WasmInstanceContext<C> {
             Err(e) => {
                 info!(&self.ctx.logger, "Failed ipfs.cat, returning `null`";
                                     "link" => asc_get::<String, _, _>(self, link_ptr, gas)?,
-                                    "error" => e.to_string());
+                                    "error" => e.to_string(),
+                                    "elapsed_ms" => start.elapsed().as_millis());
                 Ok(AscPtr::null())
             }
         }
@@ -1379,9 +2034,8 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         user_data: AscPtr<AscEnum<StoreValueKind>>,
         flags: AscPtr<Array<AscPtr<AscString>>>,
     ) -> Result<(), HostExportError> {
-        // Note on gas:
-        // Ideally we would consume gas the same as ipfs_cat and then share
-        // gas across the spawned modules for callbacks.
+        // Note on gas: Fetching the file is free like in `ipfs_cat`, but the gas used by the
+        // callbacks is charged to `gas`.
 
         if !self.experimental_features.allow_non_deterministic_ipfs {
             return Err(HostExportError::Deterministic(anyhow!(
@@ -1395,7 +2049,8 @@ impl<C: Blockchain> WasmInstanceContext<C> {
 
         let flags = asc_get(self, flags, gas)?;
 
-        // Pause the timeout while running ipfs_map, ensure it will be restarted by using a guard.
+        // Pause the timeout while waiting on IPFS, ensure it will be restarted by using a guard.
+        // `HostExports::ipfs_map` resumes it while the callbacks run.
         self.timeout_stopwatch.lock().unwrap().stop();
         let defer_stopwatch = self.timeout_stopwatch.clone();
         let _stopwatch_guard = defer::defer(|| defer_stopwatch.lock().unwrap().start());
@@ -1408,6 +2063,7 @@ impl<C: Blockchain> WasmInstanceContext<C> {
             &callback,
             user_data,
             flags,
+            gas,
         )?;
 
         debug!(
@@ -1425,6 +2081,27 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         Ok(())
     }
 
+    /// function json.reduce(array: JSONValue, callback: string, initial: Value): Value
+    pub fn json_reduce(
+        &mut self,
+        gas: &GasCounter,
+        array_ptr: AscPtr<AscEnum<JsonValueKind>>,
+        callback: AscPtr<AscString>,
+        initial: AscPtr<AscEnum<StoreValueKind>>,
+    ) -> Result<AscPtr<AscEnum<StoreValueKind>>, HostExportError> {
+        let array: serde_json::Value = asc_get(self, array_ptr, gas)?;
+        let callback: String = asc_get(self, callback, gas)?;
+        let initial: store::Value = asc_get(self, initial, gas)?;
+
+        let (result, output_states) =
+            HostExports::json_reduce(self, array, &callback, initial, gas)?;
+        for output_state in output_states {
+            self.ctx.state.extend(output_state);
+        }
+
+        asc_new(self, &result, gas)
+    }
+
     /// Expects a decimal string.
     /// function json.toI64(json: String): i64
     pub fn json_to_i64(
@@ -1475,6 +2152,57 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         asc_new(self, &*big_int, gas)
     }
 
+    /// function json.toArray(json: JSONValue): Array<JSONValue>
+    pub fn json_to_array(
+        &mut self,
+        gas: &GasCounter,
+        json_ptr: AscPtr<AscEnum<JsonValueKind>>,
+    ) -> Result<AscPtr<Array<AscPtr<AscEnum<JsonValueKind>>>>, HostExportError> {
+        let values = self
+            .ctx
+            .host_exports
+            .json_to_array(asc_get(self, json_ptr, gas)?, gas)?;
+        asc_new(self, values.as_slice(), gas)
+    }
+
+    /// function json.toObject(json: JSONValue): TypedMap<string, JSONValue>
+    pub fn json_to_object(
+        &mut self,
+        gas: &GasCounter,
+        json_ptr: AscPtr<AscEnum<JsonValueKind>>,
+    ) -> Result<AscPtr<AscJson>, HostExportError> {
+        let object = self
+            .ctx
+            .host_exports
+            .json_to_object(asc_get(self, json_ptr, gas)?, gas)?;
+        asc_new(self, &object, gas)
+    }
+
+    /// function json.toBool(json: JSONValue): bool
+    pub fn json_to_bool(
+        &mut self,
+        gas: &GasCounter,
+        json_ptr: AscPtr<AscEnum<JsonValueKind>>,
+    ) -> Result<bool, HostExportError> {
+        Ok(self
+            .ctx
+            .host_exports
+            .json_to_bool(asc_get(self, json_ptr, gas)?, gas)?)
+    }
+
+    /// function json.toString(json: JSONValue): string
+    pub fn json_to_string(
+        &mut self,
+        gas: &GasCounter,
+        json_ptr: AscPtr<AscEnum<JsonValueKind>>,
+    ) -> Result<AscPtr<AscString>, HostExportError> {
+        let string = self
+            .ctx
+            .host_exports
+            .json_to_string(asc_get(self, json_ptr, gas)?, gas)?;
+        asc_new(self, &string, gas)
+    }
+
     /// function crypto.keccak256(input: Bytes): Bytes
     pub fn crypto_keccak_256(
         &mut self,
@@ -1488,6 +2216,70 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         asc_new(self, input.as_ref(), gas)
     }
 
+    /// function crypto.sha256(input: Bytes): Bytes
+    pub fn crypto_sha256(
+        &mut self,
+        gas: &GasCounter,
+        input_ptr: AscPtr<Uint8Array>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let input = self
+            .ctx
+            .host_exports
+            .crypto_sha256(asc_get(self, input_ptr, gas)?, gas)?;
+        asc_new(self, input.as_ref(), gas)
+    }
+
+    /// function crypto.sha3(input: Bytes): Bytes
+    pub fn crypto_sha3(
+        &mut self,
+        gas: &GasCounter,
+        input_ptr: AscPtr<Uint8Array>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let input = self
+            .ctx
+            .host_exports
+            .crypto_sha3(asc_get(self, input_ptr, gas)?, gas)?;
+        asc_new(self, input.as_ref(), gas)
+    }
+
+    /// function crypto.structHash(typeHash: Bytes, fields: Array<Bytes>): Bytes
+    pub fn crypto_struct_hash(
+        &mut self,
+        gas: &GasCounter,
+        type_hash_ptr: AscPtr<Uint8Array>,
+        fields_ptr: AscPtr<Array<AscPtr<Uint8Array>>>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let type_hash: Vec<u8> = asc_get(self, type_hash_ptr, gas)?;
+        let fields: Vec<Vec<u8>> = asc_get(self, fields_ptr, gas)?;
+        let hash = self
+            .ctx
+            .host_exports
+            .crypto_struct_hash(type_hash, fields, gas)?;
+        asc_new(self, hash.as_ref(), gas)
+    }
+
+    /// function crypto.ecrecover(hash: Bytes, v: i32, r: Bytes, s: Bytes): Address | null
+    pub fn crypto_ecrecover(
+        &mut self,
+        gas: &GasCounter,
+        hash_ptr: AscPtr<Uint8Array>,
+        v: u32,
+        r_ptr: AscPtr<Uint8Array>,
+        s_ptr: AscPtr<Uint8Array>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let signer = self.ctx.host_exports.crypto_ecrecover(
+            asc_get(self, hash_ptr, gas)?,
+            v as i32,
+            asc_get(self, r_ptr, gas)?,
+            asc_get(self, s_ptr, gas)?,
+            gas,
+        )?;
+        match signer {
+            Some(signer) => asc_new(self, &signer, gas),
+            None => Ok(AscPtr::null()),
+        }
+    }
+
     /// function bigInt.plus(x: BigInt, y: BigInt): BigInt
     pub fn big_int_plus(
         &mut self,
@@ -1623,6 +2415,21 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         asc_new(self, &result, gas)
     }
 
+    /// function bigInt.bitXor(x: BigInt, y: BigInt): BigInt
+    pub fn big_int_bit_xor(
+        &mut self,
+        gas: &GasCounter,
+        x_ptr: AscPtr<AscBigInt>,
+        y_ptr: AscPtr<AscBigInt>,
+    ) -> Result<AscPtr<AscBigInt>, HostExportError> {
+        let result = self.ctx.host_exports.big_int_bit_xor(
+            asc_get(self, x_ptr, gas)?,
+            asc_get(self, y_ptr, gas)?,
+            gas,
+        )?;
+        asc_new(self, &result, gas)
+    }
+
     /// function bigInt.leftShift(x: BigInt, bits: u8): BigInt
     pub fn big_int_left_shift(
         &mut self,
@@ -1653,6 +2460,72 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         asc_new(self, &result, gas)
     }
 
+    /// function bigInt.nextPowerOfTwo(x: BigInt): BigInt
+    pub fn big_int_next_power_of_two(
+        &mut self,
+        gas: &GasCounter,
+        x_ptr: AscPtr<AscBigInt>,
+    ) -> Result<AscPtr<AscBigInt>, HostExportError> {
+        let result = self
+            .ctx
+            .host_exports
+            .big_int_next_power_of_two(asc_get(self, x_ptr, gas)?, gas)?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function bigInt.absDiff(x: BigInt, y: BigInt): BigInt
+    pub fn big_int_abs_diff(
+        &mut self,
+        gas: &GasCounter,
+        x_ptr: AscPtr<AscBigInt>,
+        y_ptr: AscPtr<AscBigInt>,
+    ) -> Result<AscPtr<AscBigInt>, HostExportError> {
+        let result = self.ctx.host_exports.big_int_abs_diff(
+            asc_get(self, x_ptr, gas)?,
+            asc_get(self, y_ptr, gas)?,
+            gas,
+        )?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function bigInt.linearRecurrence(coefficients: Array<BigInt>, seeds: Array<BigInt>, n: u32): BigInt
+    pub fn big_int_linear_recurrence(
+        &mut self,
+        gas: &GasCounter,
+        coefficients_ptr: AscPtr<Array<AscPtr<AscBigInt>>>,
+        seeds_ptr: AscPtr<Array<AscPtr<AscBigInt>>>,
+        n: u32,
+    ) -> Result<AscPtr<AscBigInt>, HostExportError> {
+        let result = self.ctx.host_exports.big_int_linear_recurrence(
+            asc_get(self, coefficients_ptr, gas)?,
+            asc_get(self, seeds_ptr, gas)?,
+            n,
+            gas,
+        )?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function bigInt.rescale(amount: BigInt, fromDecimals: u8, toDecimals: u8): BigInt
+    pub fn rescale_amount(
+        &mut self,
+        gas: &GasCounter,
+        amount_ptr: AscPtr<AscBigInt>,
+        from_decimals: u32,
+        to_decimals: u32,
+    ) -> Result<AscPtr<AscBigInt>, HostExportError> {
+        let from_decimals = u8::try_from(from_decimals)
+            .map_err(|e| DeterministicHostError::from(Error::from(e)))?;
+        let to_decimals =
+            u8::try_from(to_decimals).map_err(|e| DeterministicHostError::from(Error::from(e)))?;
+        let result = self.ctx.host_exports.rescale_amount(
+            asc_get(self, amount_ptr, gas)?,
+            from_decimals,
+            to_decimals,
+            gas,
+        )?;
+        asc_new(self, &result, gas)
+    }
+
     /// function typeConversion.bytesToBase58(bytes: Bytes): string
     pub fn bytes_to_base58(
         &mut self,
@@ -1679,6 +2552,32 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         asc_new(self, &result, gas)
     }
 
+    /// function bigDecimal.toStringTrimmed(x: BigDecimal): string
+    pub fn big_decimal_to_string_trimmed(
+        &mut self,
+        gas: &GasCounter,
+        big_decimal_ptr: AscPtr<AscBigDecimal>,
+    ) -> Result<AscPtr<AscString>, HostExportError> {
+        let result = self
+            .ctx
+            .host_exports
+            .big_decimal_to_string_trimmed(asc_get(self, big_decimal_ptr, gas)?, gas)?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function typeConversion.bigIntToBigDecimal(x: BigInt): BigDecimal
+    pub fn big_int_to_big_decimal(
+        &mut self,
+        gas: &GasCounter,
+        x_ptr: AscPtr<AscBigInt>,
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        let result = self
+            .ctx
+            .host_exports
+            .big_int_to_big_decimal(asc_get(self, x_ptr, gas)?, gas)?;
+        asc_new(self, &result, gas)
+    }
+
     /// function bigDecimal.fromString(x: string): BigDecimal
     pub fn big_decimal_from_string(
         &mut self,
@@ -1692,6 +2591,19 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         asc_new(self, &result, gas)
     }
 
+    /// function bigDecimal.fromScientific(x: string): BigDecimal
+    pub fn big_decimal_from_scientific(
+        &mut self,
+        gas: &GasCounter,
+        string_ptr: AscPtr<AscString>,
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        let result = self
+            .ctx
+            .host_exports
+            .big_decimal_from_scientific(asc_get(self, string_ptr, gas)?, gas)?;
+        asc_new(self, &result, gas)
+    }
+
     /// function bigDecimal.plus(x: BigDecimal, y: BigDecimal): BigDecimal
     pub fn big_decimal_plus(
         &mut self,
@@ -1737,67 +2649,596 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         asc_new(self, &result, gas)
     }
 
-    /// function bigDecimal.dividedBy(x: BigDecimal, y: BigDecimal): BigDecimal
-    pub fn big_decimal_divided_by(
+    /// function bigDecimal.divOr(x: BigDecimal, y: BigDecimal, default: BigDecimal): BigDecimal
+    pub fn big_decimal_div_or(
         &mut self,
         gas: &GasCounter,
         x_ptr: AscPtr<AscBigDecimal>,
         y_ptr: AscPtr<AscBigDecimal>,
+        default_ptr: AscPtr<AscBigDecimal>,
     ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
-        let result = self.ctx.host_exports.big_decimal_divided_by(
+        let result = self.ctx.host_exports.big_decimal_div_or(
             asc_get(self, x_ptr, gas)?,
             asc_get(self, y_ptr, gas)?,
+            asc_get(self, default_ptr, gas)?,
             gas,
         )?;
         asc_new(self, &result, gas)
     }
 
-    /// function bigDecimal.equals(x: BigDecimal, y: BigDecimal): bool
-    pub fn big_decimal_equals(
+    /// function bigDecimal.mod(x: BigDecimal, y: BigDecimal): BigDecimal
+    pub fn big_decimal_mod(
         &mut self,
         gas: &GasCounter,
         x_ptr: AscPtr<AscBigDecimal>,
         y_ptr: AscPtr<AscBigDecimal>,
-    ) -> Result<bool, HostExportError> {
-        self.ctx.host_exports.big_decimal_equals(
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        let result = self.ctx.host_exports.big_decimal_mod(
             asc_get(self, x_ptr, gas)?,
             asc_get(self, y_ptr, gas)?,
             gas,
-        )
+        )?;
+        asc_new(self, &result, gas)
     }
 
-    /// function dataSource.create(name: string, params: Array<string>): void
-    pub fn data_source_create(
+    /// function bigDecimal.dividedBy(x: BigDecimal, y: BigDecimal): BigDecimal
+    pub fn big_decimal_divided_by(
         &mut self,
         gas: &GasCounter,
-        name_ptr: AscPtr<AscString>,
-        params_ptr: AscPtr<Array<AscPtr<AscString>>>,
-    ) -> Result<(), HostExportError> {
-        let name: String = asc_get(self, name_ptr, gas)?;
-        let params: Vec<String> = asc_get(self, params_ptr, gas)?;
-        self.ctx.host_exports.data_source_create(
-            &self.ctx.logger,
-            &mut self.ctx.state,
-            name,
-            params,
-            None,
-            self.ctx.block_ptr.number,
+        x_ptr: AscPtr<AscBigDecimal>,
+        y_ptr: AscPtr<AscBigDecimal>,
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        let result = self.ctx.host_exports.big_decimal_divided_by(
+            asc_get(self, x_ptr, gas)?,
+            asc_get(self, y_ptr, gas)?,
             gas,
-        )
+        )?;
+        asc_new(self, &result, gas)
     }
 
-    /// function createWithContext(name: string, params: Array<string>, context: DataSourceContext): void
-    pub fn data_source_create_with_context(
+    /// function bigDecimal.inverse(x: BigDecimal): BigDecimal
+    pub fn big_decimal_inverse(
         &mut self,
         gas: &GasCounter,
-        name_ptr: AscPtr<AscString>,
-        params_ptr: AscPtr<Array<AscPtr<AscString>>>,
-        context_ptr: AscPtr<AscEntity>,
-    ) -> Result<(), HostExportError> {
-        let name: String = asc_get(self, name_ptr, gas)?;
-        let params: Vec<String> = asc_get(self, params_ptr, gas)?;
-        let context: HashMap<_, _> = asc_get(self, context_ptr, gas)?;
-        let context = DataSourceContext::from(context);
+        x_ptr: AscPtr<AscBigDecimal>,
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        let result = self
+            .ctx
+            .host_exports
+            .big_decimal_inverse(asc_get(self, x_ptr, gas)?, gas)?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function bigDecimal.compound(principal: BigDecimal, rate: BigDecimal, periods: u32): BigDecimal
+    pub fn big_decimal_compound(
+        &mut self,
+        gas: &GasCounter,
+        principal_ptr: AscPtr<AscBigDecimal>,
+        rate_ptr: AscPtr<AscBigDecimal>,
+        periods: u32,
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        let result = self.ctx.host_exports.big_decimal_compound(
+            asc_get(self, principal_ptr, gas)?,
+            asc_get(self, rate_ptr, gas)?,
+            periods,
+            gas,
+        )?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function bigDecimal.lerp(a: BigDecimal, b: BigDecimal, t: BigDecimal): BigDecimal
+    pub fn big_decimal_lerp(
+        &mut self,
+        gas: &GasCounter,
+        a_ptr: AscPtr<AscBigDecimal>,
+        b_ptr: AscPtr<AscBigDecimal>,
+        t_ptr: AscPtr<AscBigDecimal>,
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        let result = self.ctx.host_exports.big_decimal_lerp(
+            asc_get(self, a_ptr, gas)?,
+            asc_get(self, b_ptr, gas)?,
+            asc_get(self, t_ptr, gas)?,
+            gas,
+        )?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function bigDecimal.twapUpdate(accumulator: BigDecimal, price: BigDecimal, elapsed: BigInt): BigDecimal
+    pub fn big_decimal_twap_update(
+        &mut self,
+        gas: &GasCounter,
+        accumulator_ptr: AscPtr<AscBigDecimal>,
+        price_ptr: AscPtr<AscBigDecimal>,
+        elapsed_ptr: AscPtr<AscBigInt>,
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        let result = self.ctx.host_exports.big_decimal_twap_update(
+            asc_get(self, accumulator_ptr, gas)?,
+            asc_get(self, price_ptr, gas)?,
+            asc_get(self, elapsed_ptr, gas)?,
+            gas,
+        )?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function bigDecimal.priceImpact(reserveIn: BigDecimal, reserveOut: BigDecimal, amountIn: BigDecimal): BigDecimal
+    pub fn big_decimal_price_impact(
+        &mut self,
+        gas: &GasCounter,
+        reserve_in_ptr: AscPtr<AscBigDecimal>,
+        reserve_out_ptr: AscPtr<AscBigDecimal>,
+        amount_in_ptr: AscPtr<AscBigDecimal>,
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        let result = self.ctx.host_exports.big_decimal_price_impact(
+            asc_get(self, reserve_in_ptr, gas)?,
+            asc_get(self, reserve_out_ptr, gas)?,
+            asc_get(self, amount_in_ptr, gas)?,
+            gas,
+        )?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function bigDecimal.sum(values: Array<BigDecimal>): BigDecimal
+    pub fn big_decimal_sum(
+        &mut self,
+        gas: &GasCounter,
+        values_ptr: AscPtr<Array<AscPtr<AscBigDecimal>>>,
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        let values: Vec<BigDecimal> = asc_get(self, values_ptr, gas)?;
+        let result = self.ctx.host_exports.big_decimal_sum(values, gas)?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function bigDecimal.geomean(values: Array<BigDecimal>): BigDecimal
+    pub fn big_decimal_geomean(
+        &mut self,
+        gas: &GasCounter,
+        values_ptr: AscPtr<Array<AscPtr<AscBigDecimal>>>,
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        let values: Vec<BigDecimal> = asc_get(self, values_ptr, gas)?;
+        let result = self.ctx.host_exports.big_decimal_geomean(values, gas)?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function bigDecimal.variance(values: Array<BigDecimal>): BigDecimal
+    pub fn big_decimal_variance(
+        &mut self,
+        gas: &GasCounter,
+        values_ptr: AscPtr<Array<AscPtr<AscBigDecimal>>>,
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        let values: Vec<BigDecimal> = asc_get(self, values_ptr, gas)?;
+        let result = self.ctx.host_exports.big_decimal_variance(values, gas)?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function bigDecimal.stddev(values: Array<BigDecimal>): BigDecimal
+    pub fn big_decimal_stddev(
+        &mut self,
+        gas: &GasCounter,
+        values_ptr: AscPtr<Array<AscPtr<AscBigDecimal>>>,
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        let values: Vec<BigDecimal> = asc_get(self, values_ptr, gas)?;
+        let result = self.ctx.host_exports.big_decimal_stddev(values, gas)?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function bigDecimal.weightedMedian(values: Array<BigDecimal>, weights: Array<BigDecimal>): BigDecimal
+    pub fn big_decimal_weighted_median(
+        &mut self,
+        gas: &GasCounter,
+        values_ptr: AscPtr<Array<AscPtr<AscBigDecimal>>>,
+        weights_ptr: AscPtr<Array<AscPtr<AscBigDecimal>>>,
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        let values: Vec<BigDecimal> = asc_get(self, values_ptr, gas)?;
+        let weights: Vec<BigDecimal> = asc_get(self, weights_ptr, gas)?;
+        let result = self
+            .ctx
+            .host_exports
+            .big_decimal_weighted_median(values, weights, gas)?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function bigDecimal.toFixed(x: BigDecimal, decimals: u8): BigInt
+    pub fn big_decimal_to_fixed(
+        &mut self,
+        gas: &GasCounter,
+        x_ptr: AscPtr<AscBigDecimal>,
+        decimals: u32,
+    ) -> Result<AscPtr<AscBigInt>, HostExportError> {
+        let result = self.ctx.host_exports.big_decimal_to_fixed(
+            asc_get(self, x_ptr, gas)?,
+            decimals,
+            gas,
+        )?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function bigDecimal.equals(x: BigDecimal, y: BigDecimal): bool
+    pub fn big_decimal_equals(
+        &mut self,
+        gas: &GasCounter,
+        x_ptr: AscPtr<AscBigDecimal>,
+        y_ptr: AscPtr<AscBigDecimal>,
+    ) -> Result<bool, HostExportError> {
+        self.ctx.host_exports.big_decimal_equals(
+            asc_get(self, x_ptr, gas)?,
+            asc_get(self, y_ptr, gas)?,
+            gas,
+        )
+    }
+
+    /// function bigDecimal.approxEquals(x: BigDecimal, y: BigDecimal, tolerance: BigDecimal): bool
+    pub fn big_decimal_approx_equals(
+        &mut self,
+        gas: &GasCounter,
+        x_ptr: AscPtr<AscBigDecimal>,
+        y_ptr: AscPtr<AscBigDecimal>,
+        tolerance_ptr: AscPtr<AscBigDecimal>,
+    ) -> Result<bool, HostExportError> {
+        let x = asc_get(self, x_ptr, gas)?;
+        let y = asc_get(self, y_ptr, gas)?;
+        let tolerance = asc_get(self, tolerance_ptr, gas)?;
+        Ok(self
+            .ctx
+            .host_exports
+            .big_decimal_approx_equals(x, y, tolerance, gas)?)
+    }
+
+    /// function block.triggeredHandler(handler: string): bool
+    pub fn block_triggered_handler(
+        &mut self,
+        gas: &GasCounter,
+        handler_ptr: AscPtr<AscString>,
+    ) -> Result<bool, HostExportError> {
+        let handler: String = asc_get(self, handler_ptr, gas)?;
+        Ok(self
+            .ctx
+            .host_exports
+            .block_triggered_handler(&self.ctx.state, &handler, gas)?)
+    }
+
+    /// function block.uniqueLogAddresses(): i32
+    pub fn block_unique_log_addresses(&mut self, gas: &GasCounter) -> Result<i32, HostExportError> {
+        self.ctx
+            .host_exports
+            .block_unique_log_addresses(&self.ctx.block_log_addresses, gas)
+    }
+
+    /// function block.bloomContains(value: Bytes): bool
+    pub fn block_bloom_contains(
+        &mut self,
+        gas: &GasCounter,
+        value_ptr: AscPtr<Uint8Array>,
+    ) -> Result<bool, HostExportError> {
+        let value: Vec<u8> = asc_get(self, value_ptr, gas)?;
+        Ok(self
+            .ctx
+            .host_exports
+            .block_bloom_contains(self.ctx.trigger.as_deref(), &value, gas)?)
+    }
+
+    /// function block.headerHash(): Bytes | null
+    pub fn block_header_hash(
+        &mut self,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let hash = self
+            .ctx
+            .host_exports
+            .block_header_hash(self.ctx.trigger.as_deref(), gas)?;
+        match hash {
+            Some(hash) => asc_new(self, hash.as_ref(), gas),
+            None => Ok(AscPtr::null()),
+        }
+    }
+
+    /// function block.confirmations(): BigInt
+    pub fn block_confirmations(
+        &mut self,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<AscBigInt>, HostExportError> {
+        let confirmations = self.ctx.host_exports.block_confirmations(
+            self.ctx.block_ptr.number,
+            self.ctx.chain_head_ptr.as_ref(),
+            gas,
+        )?;
+        asc_new(self, &confirmations, gas)
+    }
+
+    /// function context.blockNumber(): BigInt
+    ///
+    /// The number of the block the current trigger belongs to.
+    pub fn block_number(&mut self, gas: &GasCounter) -> Result<AscPtr<AscBigInt>, HostExportError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        let number = BigInt::from(self.ctx.block_ptr.number);
+        asc_new(self, &number, gas)
+    }
+
+    /// function context.blockHash(): Bytes
+    ///
+    /// The hash of the block the current trigger belongs to.
+    pub fn block_hash(&mut self, gas: &GasCounter) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        let hash = self.ctx.block_ptr.hash.cheap_clone();
+        asc_new(self, hash.as_slice(), gas)
+    }
+
+    /// function event.paramsMap(): TypedMap<string, EthereumValue>
+    pub fn event_params_map(
+        &mut self,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<AscEthereumValueMap>, HostExportError> {
+        let params = self.ctx.trigger.as_ref().and_then(|t| t.event_params());
+        let params = self.ctx.host_exports.event_params_map(params, gas)?;
+        asc_new(self, &params, gas)
+    }
+
+    /// function log.signatureOrdinal(): i32
+    pub fn log_signature_ordinal(&mut self, gas: &GasCounter) -> Result<i32, HostExportError> {
+        let ordinal = self
+            .ctx
+            .trigger
+            .as_ref()
+            .and_then(|t| t.log_signature_ordinal());
+        Ok(self.ctx.host_exports.log_signature_ordinal(ordinal, gas)?)
+    }
+
+    /// function transaction.selector(): Bytes | null
+    pub fn transaction_selector(
+        &mut self,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let selector = self
+            .ctx
+            .trigger
+            .as_ref()
+            .and_then(|t| t.transaction_selector());
+        let selector = self.ctx.host_exports.transaction_selector(selector, gas)?;
+        match selector {
+            Some(selector) => asc_new(self, selector.as_ref(), gas),
+            None => Ok(AscPtr::null()),
+        }
+    }
+
+    /// function transaction.gasUsed(): BigInt | null
+    pub fn transaction_gas_used(
+        &mut self,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<AscBigInt>, HostExportError> {
+        let gas_used = self
+            .ctx
+            .trigger
+            .as_ref()
+            .and_then(|t| t.transaction_gas_used());
+        let gas_used = self.ctx.host_exports.transaction_gas_used(gas_used, gas)?;
+        match gas_used {
+            Some(gas_used) => asc_new(self, &gas_used, gas),
+            None => Ok(AscPtr::null()),
+        }
+    }
+
+    /// function log.isOwnAddress(): bool
+    pub fn log_is_own_address(&mut self, gas: &GasCounter) -> Result<bool, HostExportError> {
+        let log_address = self.ctx.trigger.as_ref().and_then(|t| t.log_address());
+        Ok(self.ctx.host_exports.log_is_own_address(log_address, gas)?)
+    }
+
+    /// function runtime.headTimestamp(): BigInt
+    pub fn chain_head_timestamp(
+        &mut self,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<AscBigInt>, HostExportError> {
+        let timestamp = self
+            .ctx
+            .host_exports
+            .chain_head_timestamp(self.ctx.chain_head_timestamp, gas)?;
+        asc_new(self, &timestamp, gas)
+    }
+
+    /// function runtime.totalEntityWrites(): BigInt
+    pub fn runtime_total_entity_writes(
+        &mut self,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<AscBigInt>, HostExportError> {
+        let writes = self
+            .ctx
+            .host_exports
+            .total_entity_writes(self.host_metrics.entity_writes(), gas)?;
+        asc_new(self, &writes, gas)
+    }
+
+    /// function runtime.isBackfilling(): bool
+    pub fn is_backfilling(&mut self, gas: &GasCounter) -> Result<bool, HostExportError> {
+        Ok(self.ctx.host_exports.is_backfilling(
+            self.ctx.block_ptr.number,
+            self.ctx.chain_head_ptr.as_ref(),
+            gas,
+        )?)
+    }
+
+    /// function runtime.memoryPages(): i32
+    ///
+    /// The size of the module's linear memory in 64KiB pages.
+    pub fn runtime_memory_pages(&mut self, gas: &GasCounter) -> Result<i32, HostExportError> {
+        gas.consume_host_fn(Gas::new(gas::DEFAULT_BASE_COST))?;
+        Ok(self.memory.size() as i32)
+    }
+
+    /// function runtime.entityTypes(): Array<string>
+    pub fn schema_entity_types(
+        &mut self,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<Array<AscPtr<AscString>>>, HostExportError> {
+        let entity_types = self
+            .ctx
+            .host_exports
+            .schema_entity_types(&self.ctx.state, gas)?;
+        asc_new(self, &entity_types, gas)
+    }
+
+    /// function runtime.fieldType(entityType: string, field: string): string
+    pub fn schema_field_type(
+        &mut self,
+        gas: &GasCounter,
+        entity_type_ptr: AscPtr<AscString>,
+        field_ptr: AscPtr<AscString>,
+    ) -> Result<AscPtr<AscString>, HostExportError> {
+        let entity_type: String = asc_get(self, entity_type_ptr, gas)?;
+        let field: String = asc_get(self, field_ptr, gas)?;
+        let field_type =
+            self.ctx
+                .host_exports
+                .schema_field_type(&self.ctx.state, &entity_type, &field, gas)?;
+        asc_new(self, &field_type, gas)
+    }
+
+    /// function runtime.hasCapability(name: string): bool
+    pub fn host_has_capability(
+        &mut self,
+        gas: &GasCounter,
+        name_ptr: AscPtr<AscString>,
+    ) -> Result<bool, HostExportError> {
+        let name: String = asc_get(self, name_ptr, gas)?;
+        Ok(self
+            .ctx
+            .host_exports
+            .has_capability(&name, &self.linked_host_exports, gas)?)
+    }
+
+    /// function runtime.warn(message: string): void
+    pub fn emit_warning(
+        &mut self,
+        gas: &GasCounter,
+        message_ptr: AscPtr<AscString>,
+    ) -> Result<(), HostExportError> {
+        let message: String = asc_get(self, message_ptr, gas)?;
+        self.ctx
+            .host_exports
+            .emit_warning(&mut self.ctx.state, message, gas)?;
+        Ok(())
+    }
+
+    /// function runtime.metricInc(name: string, value: BigInt): void
+    pub fn metric_increment(
+        &mut self,
+        gas: &GasCounter,
+        name_ptr: AscPtr<AscString>,
+        value_ptr: AscPtr<AscBigInt>,
+    ) -> Result<(), HostExportError> {
+        let name: String = asc_get(self, name_ptr, gas)?;
+        let value: BigInt = asc_get(self, value_ptr, gas)?;
+        self.ctx.host_exports.metric_increment(
+            &self.ctx.logger,
+            &self.host_metrics,
+            name,
+            value,
+            gas,
+        )?;
+        Ok(())
+    }
+
+    /// function call.input(inputs: Array<ethereum.EventParam>, name: string): ethereum.Value | null
+    pub fn call_input(
+        &mut self,
+        gas: &GasCounter,
+        inputs_ptr: AscPtr<Array<AscPtr<AscEventParam>>>,
+        name_ptr: AscPtr<AscString>,
+    ) -> Result<AscPtr<AscEnum<EthereumValueKind>>, HostExportError> {
+        let name: String = asc_get(self, name_ptr, gas)?;
+        let inputs = inputs_ptr
+            .read_ptr(self, gas)?
+            .to_vec(self, gas)?
+            .into_iter()
+            .map(|input| input.read_ptr(self, gas))
+            .collect::<Result<Vec<_>, _>>()?;
+        let names = inputs
+            .iter()
+            .map(|input| asc_get(self, input.name, gas))
+            .collect::<Result<Vec<String>, _>>()?;
+
+        let position = self.ctx.host_exports.call_input(&names, &name, gas)?;
+        Ok(position.map_or(AscPtr::null(), |position| inputs[position].value))
+    }
+
+    /// function array.dedup(values: Array<string>): Array<string>
+    pub fn array_dedup(
+        &mut self,
+        gas: &GasCounter,
+        values_ptr: AscPtr<Array<AscPtr<AscString>>>,
+    ) -> Result<AscPtr<Array<AscPtr<AscString>>>, HostExportError> {
+        let values: Vec<String> = asc_get(self, values_ptr, gas)?;
+        let result = self.ctx.host_exports.array_dedup(values, gas)?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function array.bigIntSortedUnique(values: Array<BigInt>): Array<BigInt>
+    pub fn array_big_int_sorted_unique(
+        &mut self,
+        gas: &GasCounter,
+        values_ptr: AscPtr<Array<AscPtr<AscBigInt>>>,
+    ) -> Result<AscPtr<Array<AscPtr<AscBigInt>>>, HostExportError> {
+        let values: Vec<BigInt> = asc_get(self, values_ptr, gas)?;
+        let result = self
+            .ctx
+            .host_exports
+            .array_big_int_sorted_unique(values, gas)?;
+        asc_new(self, result.as_slice(), gas)
+    }
+
+    /// function runtime.timestampBucket(timestamp: BigInt, bucketSize: u32): BigInt
+    pub fn timestamp_to_bucket(
+        &mut self,
+        gas: &GasCounter,
+        timestamp_ptr: AscPtr<AscBigInt>,
+        bucket_size: u32,
+    ) -> Result<AscPtr<AscBigInt>, HostExportError> {
+        let result = self.ctx.host_exports.timestamp_to_bucket(
+            asc_get(self, timestamp_ptr, gas)?,
+            bucket_size,
+            gas,
+        )?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function dataSource.create(name: string, params: Array<string>): void
+    pub fn data_source_create(
+        &mut self,
+        gas: &GasCounter,
+        name_ptr: AscPtr<AscString>,
+        params_ptr: AscPtr<Array<AscPtr<AscString>>>,
+    ) -> Result<(), HostExportError> {
+        let name: String = asc_get(self, name_ptr, gas)?;
+        let params: Vec<String> = asc_get(self, params_ptr, gas)?;
+        self.ctx.host_exports.data_source_create(
+            &self.ctx.logger,
+            &mut self.ctx.state,
+            name,
+            params,
+            None,
+            self.ctx.block_ptr.number,
+            gas,
+        )
+    }
+
+    /// function dataSource.resetCreated(): void
+    pub fn data_source_reset_created(&mut self, gas: &GasCounter) -> Result<(), HostExportError> {
+        self.ctx
+            .host_exports
+            .data_source_reset_created(&mut self.ctx.state, gas)?;
+        Ok(())
+    }
+
+    /// function createWithContext(name: string, params: Array<string>, context: DataSourceContext): void
+    pub fn data_source_create_with_context(
+        &mut self,
+        gas: &GasCounter,
+        name_ptr: AscPtr<AscString>,
+        params_ptr: AscPtr<Array<AscPtr<AscString>>>,
+        context_ptr: AscPtr<AscEntity>,
+    ) -> Result<(), HostExportError> {
+        let name: String = asc_get(self, name_ptr, gas)?;
+        let params: Vec<String> = asc_get(self, params_ptr, gas)?;
+        let context: HashMap<_, _> = asc_get(self, context_ptr, gas)?;
+        let context = DataSourceContext::from(context);
 
         self.ctx.host_exports.data_source_create(
             &self.ctx.logger,
@@ -1822,6 +3263,26 @@ impl<C: Blockchain> WasmInstanceContext<C> {
         )
     }
 
+    /// function dataSource.creatorAddress(): Bytes
+    pub fn data_source_creator_address(
+        &mut self,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        asc_new(
+            self,
+            self.ctx
+                .host_exports
+                .data_source_creator_address(gas)?
+                .as_slice(),
+            gas,
+        )
+    }
+
+    /// function dataSource.instanceIndex(): i32
+    pub fn data_source_instance_index(&mut self, gas: &GasCounter) -> Result<i32, HostExportError> {
+        Ok(self.ctx.host_exports.data_source_instance_index(gas)?)
+    }
+
     /// function dataSource.network(): String
     pub fn data_source_network(
         &mut self,
@@ -1879,6 +3340,22 @@ impl<C: Blockchain> WasmInstanceContext<C> {
             .log_log(&self.ctx.mapping_logger, level, msg, gas)
     }
 
+    /// function log.logKv(level: enum LogLevel, msg: String, fields: TypedMap<string, string>): void
+    pub fn log_log_kv(
+        &mut self,
+        gas: &GasCounter,
+        level: u32,
+        msg: AscPtr<AscString>,
+        fields: AscPtr<AscTypedMap<AscString, AscString>>,
+    ) -> Result<(), DeterministicHostError> {
+        let level = LogLevel::from(level).into();
+        let msg: String = asc_get(self, msg, gas)?;
+        let fields: HashMap<String, String> = asc_get(self, fields, gas)?;
+        self.ctx
+            .host_exports
+            .log_log_kv(&self.ctx.mapping_logger, level, msg, fields, gas)
+    }
+
     /// function encode(token: ethereum.Value): Bytes | null
     pub fn ethereum_encode(
         &mut self,
@@ -1895,6 +3372,35 @@ impl<C: Blockchain> WasmInstanceContext<C> {
             .unwrap_or(Ok(AscPtr::null()))
     }
 
+    /// function ethereum.lastReturnData(): Bytes | null
+    pub fn ethereum_last_return_data(
+        &mut self,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let last_return_data = self.ctx.last_return_data.lock().unwrap().clone();
+        let data = self
+            .ctx
+            .host_exports
+            .ethereum_last_return_data(last_return_data, gas)?;
+        match data {
+            Some(data) => asc_new(self, data.as_slice(), gas),
+            None => Ok(AscPtr::null()),
+        }
+    }
+
+    /// function encodePacked(tokens: Array<ethereum.Value>): Bytes
+    pub fn ethereum_encode_packed(
+        &mut self,
+        gas: &GasCounter,
+        tokens_ptr: AscEnumArray<EthereumValueKind>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let data = self
+            .ctx
+            .host_exports
+            .ethereum_encode_packed(asc_get(self, tokens_ptr, gas)?, gas)?;
+        asc_new(self, &*data, gas)
+    }
+
     /// function decode(types: String, data: Bytes): ethereum.Value | null
     pub fn ethereum_decode(
         &mut self,
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use ethabi;
 
 use graph::data::value::Word;
@@ -151,6 +153,29 @@ impl ToAscObj<Array<AscPtr<AscString>>> for Vec<String> {
     }
 }
 
+impl ToAscObj<Array<AscPtr<AscEnum<EthereumValueKind>>>> for Vec<ethabi::Token> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<Array<AscPtr<AscEnum<EthereumValueKind>>>, HostExportError> {
+        self.as_slice().to_asc_obj(heap, gas)
+    }
+}
+
+impl ToAscObj<AscEventParam> for ethabi::LogParam {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscEventParam, HostExportError> {
+        Ok(AscEventParam {
+            name: asc_new(heap, self.name.as_str(), gas)?,
+            value: asc_new(heap, &self.value, gas)?,
+        })
+    }
+}
+
 impl ToAscObj<AscEnum<EthereumValueKind>> for ethabi::Token {
     fn to_asc_obj<H: AscHeap + ?Sized>(
         &self,
@@ -329,6 +354,18 @@ impl ToAscObj<AscJson> for serde_json::Map<String, serde_json::Value> {
     }
 }
 
+impl ToAscObj<AscEthereumValueMap> for Vec<(String, ethabi::Token)> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscEthereumValueMap, HostExportError> {
+        Ok(AscTypedMap {
+            entries: asc_new(heap, self.as_slice(), gas)?,
+        })
+    }
+}
+
 // Used for serializing entities.
 impl ToAscObj<AscEntity> for Vec<(Word, store::Value)> {
     fn to_asc_obj<H: AscHeap + ?Sized>(
@@ -391,6 +428,47 @@ impl ToAscObj<AscEnum<JsonValueKind>> for serde_json::Value {
     }
 }
 
+impl FromAscObj<AscEnum<JsonValueKind>> for serde_json::Value {
+    fn from_asc_obj<H: AscHeap + ?Sized>(
+        asc_enum: AscEnum<JsonValueKind>,
+        heap: &H,
+        gas: &GasCounter,
+        depth: usize,
+    ) -> Result<Self, DeterministicHostError> {
+        use serde_json::Value;
+
+        let payload = asc_enum.payload;
+        Ok(match asc_enum.kind {
+            JsonValueKind::Null => Value::Null,
+            JsonValueKind::Bool => Value::Bool(bool::from(payload)),
+            JsonValueKind::Number => {
+                let ptr: AscPtr<AscString> = AscPtr::from(payload);
+                let number: String = asc_get(heap, ptr, gas, depth)?;
+                let number = serde_json::from_str(&number).map_err(|_| {
+                    DeterministicHostError::from(anyhow::anyhow!(
+                        "JSON number `{}` is not a valid number",
+                        number
+                    ))
+                })?;
+                Value::Number(number)
+            }
+            JsonValueKind::String => {
+                let ptr: AscPtr<AscString> = AscPtr::from(payload);
+                Value::String(asc_get(heap, ptr, gas, depth)?)
+            }
+            JsonValueKind::Array => {
+                let ptr: AscPtr<Array<AscPtr<AscEnum<JsonValueKind>>>> = AscPtr::from(payload);
+                Value::Array(asc_get(heap, ptr, gas, depth)?)
+            }
+            JsonValueKind::Object => {
+                let ptr: AscPtr<AscJson> = AscPtr::from(payload);
+                let object: HashMap<String, Value> = asc_get(heap, ptr, gas, depth)?;
+                Value::Object(object.into_iter().collect())
+            }
+        })
+    }
+}
+
 impl From<u32> for LogLevel {
     fn from(i: u32) -> Self {
         match i {
@@ -434,10 +512,10 @@ where
                 },
                 error: AscPtr::null(),
             },
-            Err(_) => AscResult {
+            Err(error) => AscResult {
                 value: AscPtr::null(),
                 error: {
-                    let wrapped = AscWrapped { inner: true };
+                    let wrapped = AscWrapped { inner: *error };
                     asc_new(heap, &wrapped, gas)?
                 },
             },
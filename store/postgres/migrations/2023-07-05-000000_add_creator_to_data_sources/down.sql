@@ -0,0 +1,14 @@
+do $$
+declare
+  deployments cursor for
+     select t.table_schema as sgd
+       from information_schema.columns t
+      where t.table_schema like 'sgd%'
+        and t.table_name = 'data_sources$'
+        and t.column_name = 'creator';
+begin
+  for d in deployments loop
+    execute 'alter table ' || d.sgd || '.data_sources$ drop column creator';
+  end loop;
+end;
+$$;
@@ -0,0 +1,3 @@
+alter table subgraphs.subgraph_deployment
+  drop column warnings,
+  drop column warnings_block_number;
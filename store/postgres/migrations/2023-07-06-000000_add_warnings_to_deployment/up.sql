@@ -0,0 +1,3 @@
+alter table subgraphs.subgraph_deployment
+  add column warnings text[] not null default array[]::text[],
+  add column warnings_block_number int;
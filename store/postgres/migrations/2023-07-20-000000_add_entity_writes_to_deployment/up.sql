@@ -0,0 +1,2 @@
+alter table subgraphs.subgraph_deployment
+  add column entity_writes numeric not null default 0;
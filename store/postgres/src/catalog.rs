@@ -18,7 +18,7 @@ use std::time::Duration;
 
 use graph::prelude::anyhow::anyhow;
 use graph::{
-    data::subgraph::schema::POI_TABLE,
+    data::subgraph::schema::{POI_TABLE, RAW_EVENT_TABLE},
     prelude::{lazy_static, StoreError},
 };
 
@@ -175,6 +175,9 @@ pub struct Catalog {
     text_columns: HashMap<String, HashSet<String>>,
 
     pub use_poi: bool,
+    /// Whether the deployment has a table for raw events; deployments
+    /// created before raw events were introduced don't have one
+    pub use_raw_events: bool,
     /// Whether `bytea` columns are indexed with just a prefix (`true`) or
     /// in their entirety. This influences both DDL generation and how
     /// queries are generated
@@ -198,12 +201,14 @@ impl Catalog {
     ) -> Result<Self, StoreError> {
         let text_columns = get_text_columns(conn, &site.namespace)?;
         let use_poi = supports_proof_of_indexing(conn, &site.namespace)?;
+        let use_raw_events = supports_raw_events(conn, &site.namespace)?;
         let has_minmax_multi_ops = has_minmax_multi_ops(conn)?;
 
         Ok(Catalog {
             site,
             text_columns,
             use_poi,
+            use_raw_events,
             use_bytea_prefix,
             entities_with_causality_region: entities_with_causality_region.into_iter().collect(),
             has_minmax_multi_ops,
@@ -223,6 +228,8 @@ impl Catalog {
             text_columns: HashMap::default(),
             // DDL generation creates a POI table
             use_poi: true,
+            // and a table for raw events
+            use_raw_events: true,
             // DDL generation creates indexes for prefixes of bytes columns
             // see: attr-bytea-prefix
             use_bytea_prefix: true,
@@ -242,6 +249,7 @@ impl Catalog {
             site,
             text_columns: HashMap::default(),
             use_poi: false,
+            use_raw_events: false,
             use_bytea_prefix: true,
             entities_with_causality_region,
             has_minmax_multi_ops: false,
@@ -331,6 +339,16 @@ pub fn supports_proof_of_indexing(
     table_exists(conn, namespace.as_str(), &POI_TABLE_NAME)
 }
 
+pub fn supports_raw_events(
+    conn: &diesel::pg::PgConnection,
+    namespace: &Namespace,
+) -> Result<bool, StoreError> {
+    lazy_static! {
+        static ref RAW_EVENT_TABLE_NAME: SqlName = SqlName::verbatim(RAW_EVENT_TABLE.to_owned());
+    }
+    table_exists(conn, namespace.as_str(), &RAW_EVENT_TABLE_NAME)
+}
+
 pub fn current_servers(conn: &PgConnection) -> Result<Vec<String>, StoreError> {
     #[derive(QueryableByName)]
     struct Srv {
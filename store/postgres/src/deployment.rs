@@ -137,6 +137,7 @@ table! {
         last_healthy_ethereum_block_hash -> Nullable<Binary>,
         last_healthy_ethereum_block_number -> Nullable<Numeric>,
         entity_count -> Numeric,
+        entity_writes -> Numeric,
         graft_base -> Nullable<Text>,
         graft_block_hash -> Nullable<Binary>,
         graft_block_number -> Nullable<Numeric>,
@@ -145,6 +146,8 @@ table! {
         current_reorg_depth -> Integer,
         max_reorg_depth -> Integer,
         firehose_cursor -> Nullable<Text>,
+        warnings -> Array<Text>,
+        warnings_block_number -> Nullable<Integer>,
     }
 }
 
@@ -411,6 +414,7 @@ pub fn transact_block(
     ptr: &BlockPtr,
     firehose_cursor: &FirehoseCursor,
     count: i32,
+    entity_writes: usize,
 ) -> Result<BlockNumber, StoreError> {
     use crate::diesel::BoolExpressionMethods;
     use subgraph_deployment as d;
@@ -419,6 +423,7 @@ pub fn transact_block(
     let number = format!("{}::numeric", ptr.number);
 
     let count_sql = entity_count_sql(count);
+    let entity_writes_sql = format!("entity_writes + {entity_writes}");
 
     let rows = update(
         d::table.filter(d::id.eq(site.id)).filter(
@@ -433,6 +438,7 @@ pub fn transact_block(
         d::latest_ethereum_block_hash.eq(ptr.hash_slice()),
         d::firehose_cursor.eq(firehose_cursor.as_ref()),
         d::entity_count.eq(sql(&count_sql)),
+        d::entity_writes.eq(sql(&entity_writes_sql)),
         d::current_reorg_depth.eq(0),
     ))
     .returning(d::earliest_block_number)
@@ -544,9 +550,21 @@ pub fn revert_block_ptr(
             d::current_reorg_depth.eq(d::current_reorg_depth + 1),
             d::max_reorg_depth.eq(sql("greatest(current_reorg_depth + 1, max_reorg_depth)")),
         ))
-        .execute(conn)
-        .map(|_| ())
-        .map_err(|e| e.into())
+        .execute(conn)?;
+
+    // Forget warnings that were emitted for blocks that are being reverted
+    update(
+        d::table
+            .filter(d::deployment.eq(id.as_str()))
+            .filter(d::warnings_block_number.gt(ptr.number)),
+    )
+    .set((
+        d::warnings.eq::<Vec<String>>(vec![]),
+        d::warnings_block_number.eq::<Option<i32>>(None),
+    ))
+    .execute(conn)
+    .map(|_| ())
+    .map_err(|e| e.into())
 }
 
 pub fn block_ptr(conn: &PgConnection, id: &DeploymentHash) -> Result<Option<BlockPtr>, StoreError> {
@@ -802,6 +820,26 @@ pub fn update_non_fatal_errors(
     Ok(())
 }
 
+/// Replace the warnings of the deployment with the ones that handlers
+/// emitted for `block`
+pub fn update_warnings(
+    conn: &PgConnection,
+    deployment_id: &DeploymentHash,
+    block: BlockNumber,
+    warnings: &[String],
+) -> Result<(), StoreError> {
+    use subgraph_deployment as d;
+
+    update(d::table.filter(d::deployment.eq(deployment_id.as_str())))
+        .set((
+            d::warnings.eq(warnings),
+            d::warnings_block_number.eq(block),
+        ))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(StoreError::from)
+}
+
 /// If `block` is `None`, assumes the latest block.
 pub(crate) fn has_deterministic_errors(
     conn: &PgConnection,
@@ -1094,6 +1132,7 @@ pub fn create_deployment(
         d::latest_ethereum_block_hash.eq(sql("null")),
         d::latest_ethereum_block_number.eq(sql("null")),
         d::entity_count.eq(sql("0")),
+        d::entity_writes.eq(sql("0")),
         d::graft_base.eq(graft_base.as_ref().map(|s| s.as_str())),
         d::graft_block_hash.eq(b(&graft_block)),
         d::graft_block_number.eq(n(&graft_block)),
@@ -1158,6 +1197,24 @@ pub fn update_entity_count(conn: &PgConnection, site: &Site, count: i32) -> Resu
     Ok(())
 }
 
+/// The number of entity modifications that were ever transacted for the
+/// deployment. Unlike the entity count, this is not reduced by reverts
+pub fn entity_writes(conn: &PgConnection, site: &Site) -> Result<u64, StoreError> {
+    use subgraph_deployment as d;
+
+    let writes = d::table
+        .filter(d::id.eq(site.id))
+        .select(d::entity_writes)
+        .first::<BigDecimal>(conn)?;
+    writes.to_u64().ok_or_else(|| {
+        constraint_violation!(
+            "the entity writes of deployment `{}` are not a u64: {}",
+            site.deployment,
+            writes
+        )
+    })
+}
+
 /// Set the deployment's entity count to whatever `full_count_query` produces
 pub fn set_entity_count(
     conn: &PgConnection,
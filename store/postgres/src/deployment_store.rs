@@ -464,6 +464,31 @@ impl DeploymentStore {
         self.layout_cache.get(&self.logger, conn, site)
     }
 
+    /// Add the table for raw events to a deployment that was created
+    /// before raw events were introduced, and return whether the
+    /// deployment now has it
+    pub(crate) fn upgrade_raw_events(&self, site: Arc<Site>) -> Result<bool, StoreError> {
+        let layout = self.find_layout(site.cheap_clone())?;
+        if layout.supports_raw_events() {
+            return Ok(true);
+        }
+
+        let conn = self.get_conn()?;
+        conn.transaction(|| -> Result<(), StoreError> {
+            // Another node might have added the table since the layout
+            // was loaded
+            if !catalog::supports_raw_events(&conn, &site.namespace)? {
+                layout.create_raw_event_table(&conn)?;
+            }
+            Ok(())
+        })?;
+
+        // Invalidate the layout cache for this site so that the next access
+        // will see the new table
+        self.layout_cache.remove(&site);
+        Ok(self.find_layout(site)?.supports_raw_events())
+    }
+
     /// Return the layout for a deployment. This might use a database
     /// connection for the lookup and should only be called if the caller
     /// does not have a connection currently. If it does, use `layout`
@@ -1026,6 +1051,19 @@ impl DeploymentStore {
         layout.find(&conn, key, block)
     }
 
+    /// Return the block at which the entity for `key` was last written,
+    /// considering only changes up to and including `block`
+    pub(crate) fn get_last_modified_block(
+        &self,
+        site: Arc<Site>,
+        key: &EntityKey,
+        block: BlockNumber,
+    ) -> Result<Option<BlockNumber>, StoreError> {
+        let conn = self.get_conn()?;
+        let layout = self.layout(&conn, site)?;
+        layout.find_last_modified_block(&conn, key, block)
+    }
+
     /// Retrieve all the entities matching `ids_for_type`, both the type and causality region, from
     /// the deployment `site`. Only consider entities as of the given `block`
     pub(crate) fn get_many(
@@ -1134,12 +1172,17 @@ impl DeploymentStore {
                     }
                 }
 
+                if let Some((block, warnings)) = &batch.warnings {
+                    deployment::update_warnings(&conn, &site.deployment, *block, warnings)?;
+                }
+
                 let earliest_block = deployment::transact_block(
                     &conn,
                     &site,
                     &batch.block_ptr,
                     &batch.firehose_cursor,
                     count,
+                    batch.entity_writes,
                 )?;
 
                 Ok((layout, earliest_block))
@@ -1808,6 +1851,11 @@ impl DeploymentStore {
             .await
     }
 
+    pub(crate) async fn entity_writes(&self, site: Arc<Site>) -> Result<u64, StoreError> {
+        self.with_conn(move |conn, _| deployment::entity_writes(conn, &site).map_err(Into::into))
+            .await
+    }
+
     pub(crate) async fn set_manifest_raw_yaml(
         &self,
         site: Arc<Site>,
@@ -58,6 +58,7 @@ pub struct DeploymentDetail {
     last_healthy_ethereum_block_hash: Option<Bytes>,
     last_healthy_ethereum_block_number: Option<BigDecimal>,
     pub entity_count: BigDecimal,
+    entity_writes: BigDecimal,
     graft_base: Option<String>,
     graft_block_hash: Option<Bytes>,
     graft_block_number: Option<BigDecimal>,
@@ -66,6 +67,8 @@ pub struct DeploymentDetail {
     current_reorg_depth: i32,
     max_reorg_depth: i32,
     firehose_cursor: Option<String>,
+    warnings: Vec<String>,
+    warnings_block_number: Option<i32>,
 }
 
 #[derive(Queryable, QueryableByName)]
@@ -200,6 +203,7 @@ pub(crate) fn info_from_details(
         graft_base: _,
         graft_block_hash: _,
         graft_block_number: _,
+        warnings,
         ..
     } = detail;
 
@@ -244,6 +248,7 @@ pub(crate) fn info_from_details(
         health,
         fatal_error,
         non_fatal_errors,
+        warnings,
         chains: vec![chain],
         entity_count,
         node: None,
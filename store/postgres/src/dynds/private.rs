@@ -33,6 +33,7 @@ pub(crate) struct DataSourcesTable {
     param: DynColumn<Nullable<Binary>>,
     context: DynColumn<Nullable<Jsonb>>,
     done_at: DynColumn<Nullable<Integer>>,
+    creator: DynColumn<Nullable<Binary>>,
 }
 
 impl DataSourcesTable {
@@ -52,6 +53,7 @@ impl DataSourcesTable {
             param: table.column("param"),
             context: table.column("context"),
             done_at: table.column("done_at"),
+            creator: table.column("creator"),
             table,
         }
     }
@@ -68,7 +70,8 @@ impl DataSourcesTable {
                 id bytea,
                 param bytea,
                 context jsonb,
-                done_at int
+                done_at int,
+                creator bytea
             );
 
             create index gist_block_range_data_sources$ on {nsp}.data_sources$ using gist (block_range);
@@ -94,6 +97,7 @@ impl DataSourcesTable {
             Option<serde_json::Value>,
             CausalityRegion,
             Option<i32>,
+            Option<Vec<u8>>,
         );
         let tuples = self
             .table
@@ -106,6 +110,7 @@ impl DataSourcesTable {
                 &self.context,
                 &self.causality_region,
                 &self.done_at,
+                &self.creator,
             ))
             .order_by(&self.vid)
             .load::<Tuple>(conn)?;
@@ -113,7 +118,15 @@ impl DataSourcesTable {
         let mut dses: Vec<_> = tuples
             .into_iter()
             .map(
-                |(block_range, manifest_idx, param, context, causality_region, done_at)| {
+                |(
+                    block_range,
+                    manifest_idx,
+                    param,
+                    context,
+                    causality_region,
+                    done_at,
+                    creator,
+                )| {
                     let creation_block = match block_range.0 {
                         Bound::Included(block) => Some(block),
 
@@ -130,6 +143,7 @@ impl DataSourcesTable {
                         creation_block,
                         done_at,
                         causality_region,
+                        creator_address: creator.map(|c| c.into()),
                     }
                 },
             )
@@ -158,6 +172,7 @@ impl DataSourcesTable {
                     creation_block,
                     done_at,
                     causality_region,
+                    creator_address,
                 } = ds;
 
                 if creation_block != &Some(block) {
@@ -171,8 +186,8 @@ impl DataSourcesTable {
                 // Offchain data sources have a unique causality region assigned from a sequence in the
                 // database, while onchain data sources always have causality region 0.
                 let query = format!(
-                "insert into {}(block_range, manifest_idx, param, context, causality_region, done_at) \
-                            values (int4range($1, null), $2, $3, $4, $5, $6)",
+                "insert into {}(block_range, manifest_idx, param, context, causality_region, done_at, creator) \
+                            values (int4range($1, null), $2, $3, $4, $5, $6, $7)",
                 self.qname
             );
 
@@ -182,7 +197,8 @@ impl DataSourcesTable {
                     .bind::<Nullable<Binary>, _>(param.as_ref().map(|p| &**p))
                     .bind::<Nullable<Jsonb>, _>(context)
                     .bind::<Integer, _>(causality_region)
-                    .bind::<Nullable<Integer>, _>(done_at);
+                    .bind::<Nullable<Integer>, _>(done_at)
+                    .bind::<Nullable<Binary>, _>(creator_address.as_ref().map(|c| &**c));
 
                 inserted_total += query.execute(conn)?;
             }
@@ -224,6 +240,7 @@ impl DataSourcesTable {
             Option<serde_json::Value>,
             i32,
             Option<i32>,
+            Option<Vec<u8>>,
         );
 
         let src_tuples = self
@@ -237,12 +254,14 @@ impl DataSourcesTable {
                 &self.context,
                 &self.causality_region,
                 &self.done_at,
+                &self.creator,
             ))
             .order_by(&self.vid)
             .load::<Tuple>(conn)?;
 
         let mut count = 0;
-        for (block_range, src_manifest_idx, param, context, causality_region, done_at) in src_tuples
+        for (block_range, src_manifest_idx, param, context, causality_region, done_at, creator) in
+            src_tuples
         {
             let name = &src_manifest_idx_and_name
                 .iter()
@@ -257,12 +276,12 @@ impl DataSourcesTable {
 
             let query = format!(
                 "\
-             insert into {dst}(block_range, manifest_idx, param, context, causality_region, done_at)
+             insert into {dst}(block_range, manifest_idx, param, context, causality_region, done_at, creator)
              values(case
                  when upper($2) <= $1 then $2
                  else int4range(lower($2), null)
              end,
-             $3, $4, $5, $6, $7)
+             $3, $4, $5, $6, $7, $8)
              ",
                 dst = dst.qname
             );
@@ -275,6 +294,7 @@ impl DataSourcesTable {
                 .bind::<Nullable<Jsonb>, _>(context)
                 .bind::<Integer, _>(causality_region)
                 .bind::<Nullable<Integer>, _>(done_at)
+                .bind::<Nullable<Binary>, _>(creator)
                 .execute(conn)?;
         }
 
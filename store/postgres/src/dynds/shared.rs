@@ -86,6 +86,9 @@ pub(super) fn load(
             // subgraphs that use file data sources.
             done_at: None,
             causality_region: CausalityRegion::ONCHAIN,
+
+            // The shared schema has no column for the creator.
+            creator_address: None,
         };
 
         if data_sources.last().and_then(|d| d.creation_block) > data_source.creation_block {
@@ -124,6 +127,7 @@ pub(super) fn insert(
                     creation_block: _,
                     done_at: _,
                     causality_region,
+                    creator_address: _,
                 } = ds;
 
                 if causality_region != &CausalityRegion::ONCHAIN {
@@ -55,7 +55,10 @@ use crate::{
 use graph::components::store::{DerivedEntityQuery, EntityKey, EntityType};
 use graph::data::graphql::ext::{DirectiveFinder, ObjectTypeExt};
 use graph::data::store::BYTES_SCALAR;
-use graph::data::subgraph::schema::{POI_DIGEST, POI_OBJECT, POI_TABLE};
+use graph::data::subgraph::schema::{
+    POI_DIGEST, POI_OBJECT, POI_TABLE, RAW_EVENT_OBJECT, RAW_EVENT_PAYLOAD, RAW_EVENT_SEQUENCE,
+    RAW_EVENT_TABLE, RAW_EVENT_TAG,
+};
 use graph::prelude::{
     anyhow, info, BlockNumber, DeploymentHash, Entity, EntityChange, EntityOperation, Logger,
     QueryExecutionError, StoreError, StoreEvent, ValueType, BLOCK_NUMBER_MAX,
@@ -348,6 +351,9 @@ impl Layout {
         if catalog.use_poi {
             tables.push(Self::make_poi_table(&catalog, tables.len()))
         }
+        if catalog.use_raw_events {
+            tables.push(Self::make_raw_event_table(&catalog, tables.len()))
+        }
 
         let tables: Vec<_> = tables.into_iter().map(Arc::new).collect();
 
@@ -428,10 +434,44 @@ impl Layout {
         }
     }
 
+    /// The append-only table for the records that handlers write with
+    /// `store.appendRaw`
+    fn make_raw_event_table(catalog: &Catalog, position: usize) -> Table {
+        let table_name = SqlName::verbatim(RAW_EVENT_TABLE.to_owned());
+        let column = |name: &str, scalar: &str, column_type: ColumnType| Column {
+            name: SqlName::from(name),
+            field: name.to_owned(),
+            field_type: q::Type::NonNullType(Box::new(q::Type::NamedType(scalar.to_owned()))),
+            column_type,
+            fulltext_fields: None,
+            is_reference: false,
+            use_prefix_comparison: false,
+        };
+        Table {
+            object: RAW_EVENT_OBJECT.to_owned(),
+            qualified_name: SqlName::qualified_name(&catalog.site.namespace, &table_name),
+            name: table_name,
+            columns: vec![
+                column(PRIMARY_KEY_COLUMN, "String", ColumnType::String),
+                column(RAW_EVENT_TAG.as_str(), "String", ColumnType::String),
+                column(RAW_EVENT_PAYLOAD.as_str(), "String", ColumnType::String),
+                column(RAW_EVENT_SEQUENCE.as_str(), "Int", ColumnType::Int),
+            ],
+            position: position as u32,
+            is_account_like: false,
+            immutable: true,
+            has_causality_region: false,
+        }
+    }
+
     pub fn supports_proof_of_indexing(&self) -> bool {
         self.tables.contains_key(&*POI_OBJECT)
     }
 
+    pub fn supports_raw_events(&self) -> bool {
+        self.tables.contains_key(&*RAW_EVENT_OBJECT)
+    }
+
     pub fn create_relational_schema(
         conn: &PgConnection,
         site: Arc<Site>,
@@ -448,6 +488,17 @@ impl Layout {
         Ok(layout)
     }
 
+    /// Create the table for raw events for a deployment that was created
+    /// before raw events were introduced. Since the layout does not know
+    /// about the new table, it needs to be reloaded afterwards
+    pub fn create_raw_event_table(&self, conn: &PgConnection) -> Result<(), StoreError> {
+        let table = Self::make_raw_event_table(&self.catalog, self.tables.len());
+        let mut sql = String::new();
+        table.as_ddl(&self.catalog, &mut sql)?;
+        conn.batch_execute(&sql)?;
+        Ok(())
+    }
+
     /// Determine if it is possible to copy the data of `source` into `self`
     /// by checking that our schema is compatible with `source`.
     /// Returns a list of errors if copying is not possible. An empty
@@ -528,6 +579,22 @@ impl Layout {
             .transpose()
     }
 
+    /// Return the block at which the version of the entity for `key` that
+    /// is current as of `block` was written, or `None` if no such entity
+    /// exists
+    pub fn find_last_modified_block(
+        &self,
+        conn: &PgConnection,
+        key: &EntityKey,
+        block: BlockNumber,
+    ) -> Result<Option<BlockNumber>, StoreError> {
+        let table = self.table_for_entity(&key.entity_type)?;
+        Ok(FindQuery::new(table.as_ref(), key, block)
+            .get_result::<EntityData>(conn)
+            .optional()?
+            .and_then(|entity_data| entity_data.version_block()))
+    }
+
     // An optimization when looking up multiple entities, it will generate a single sql query using `UNION ALL`.
     pub fn find_many(
         &self,
@@ -516,6 +516,26 @@ impl EntityData {
         EntityType::new(self.entity.clone())
     }
 
+    /// The block at which the version of the entity in `self` was written,
+    /// i.e., the lower bound of the `block_range` for mutable entities and
+    /// the `block$` for immutable entities. Returns `None` if the query
+    /// that produced `self` did not include either column
+    pub fn version_block(&self) -> Option<BlockNumber> {
+        use serde_json::Value as j;
+        match (
+            self.data.get(BLOCK_RANGE_COLUMN),
+            self.data.get(BLOCK_COLUMN),
+        ) {
+            (Some(j::String(range)), _) => range
+                .trim_start_matches('[')
+                .split(',')
+                .next()
+                .and_then(|lower| lower.parse().ok()),
+            (_, Some(j::Number(block))) => block.as_i64().map(|block| block as BlockNumber),
+            _ => None,
+        }
+    }
+
     /// Map the `EntityData` using the schema information in `Layout`
     pub fn deserialize_with_layout<T: FromEntityData>(
         self,
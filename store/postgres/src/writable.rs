@@ -77,6 +77,7 @@ struct SyncStore {
     writable: Arc<DeploymentStore>,
     site: Arc<Site>,
     input_schema: Arc<InputSchema>,
+    supports_raw_events: bool,
     manifest_idx_and_name: Arc<Vec<(u32, String)>>,
 }
 
@@ -90,12 +91,14 @@ impl SyncStore {
         let store = WritableSubgraphStore(subgraph_store.clone());
         let writable = subgraph_store.for_site(site.as_ref())?.clone();
         let input_schema = subgraph_store.input_schema(&site.deployment)?;
+        let supports_raw_events = writable.upgrade_raw_events(site.cheap_clone())?;
         Ok(Self {
             logger,
             store,
             writable,
             site,
             input_schema,
+            supports_raw_events,
             manifest_idx_and_name,
         })
     }
@@ -212,6 +215,17 @@ impl SyncStore {
         })
     }
 
+    fn last_modified_block(
+        &self,
+        key: &EntityKey,
+        block: BlockNumber,
+    ) -> Result<Option<BlockNumber>, StoreError> {
+        retry::forever(&self.logger, "last_modified_block", || {
+            self.writable
+                .get_last_modified_block(self.site.cheap_clone(), key, block)
+        })
+    }
+
     fn transact_block_operations(
         &self,
         batch: &Batch,
@@ -366,9 +380,20 @@ impl SyncStore {
         .await
     }
 
+    async fn entity_writes(&self) -> Result<u64, StoreError> {
+        retry::forever_async(&self.logger, "entity_writes", || async {
+            self.writable.entity_writes(self.site.cheap_clone()).await
+        })
+        .await
+    }
+
     fn input_schema(&self) -> Arc<InputSchema> {
         self.input_schema.clone()
     }
+
+    fn supports_raw_events(&self) -> bool {
+        self.supports_raw_events
+    }
 }
 
 /// Track block numbers we see in a few methods that traverse the queue to
@@ -1053,6 +1078,24 @@ impl Queue {
         }
     }
 
+    /// Get the block at which the entity for `key` was last written by
+    /// looking at both the queue and the store
+    fn last_modified_block(&self, key: &EntityKey) -> Result<Option<BlockNumber>, StoreError> {
+        let (op, query_block) = BlockTracker::find_map(&self.queue, |batch, at| {
+            batch
+                .last_op_with_block(key, at)
+                .map(|(block, op)| match op {
+                    EntityOp::Write { .. } => Some(block),
+                    EntityOp::Remove { .. } => None,
+                })
+        });
+
+        match op {
+            Some(block) => Ok(block),
+            None => self.store.last_modified_block(key, query_block),
+        }
+    }
+
     /// Get many entities at once by looking at both the queue and the store
     fn get_many(
         &self,
@@ -1285,6 +1328,13 @@ impl Writer {
         }
     }
 
+    fn last_modified_block(&self, key: &EntityKey) -> Result<Option<BlockNumber>, StoreError> {
+        match self {
+            Writer::Sync(store) => store.last_modified_block(key, BLOCK_NUMBER_MAX),
+            Writer::Async { queue, .. } => queue.last_modified_block(key),
+        }
+    }
+
     fn get_many(
         &self,
         keys: BTreeSet<EntityKey>,
@@ -1395,6 +1445,10 @@ impl ReadStore for WritableStore {
         self.writer.get(key)
     }
 
+    fn last_modified_block(&self, key: &EntityKey) -> Result<Option<BlockNumber>, StoreError> {
+        self.writer.last_modified_block(key)
+    }
+
     fn get_many(
         &self,
         keys: BTreeSet<EntityKey>,
@@ -1412,6 +1466,10 @@ impl ReadStore for WritableStore {
     fn input_schema(&self) -> Arc<InputSchema> {
         self.store.input_schema()
     }
+
+    fn supports_raw_events(&self) -> bool {
+        self.store.supports_raw_events()
+    }
 }
 
 impl DeploymentCursorTracker for WritableStore {
@@ -1494,6 +1552,7 @@ impl WritableStoreTrait for WritableStore {
         stopwatch: &StopwatchMetrics,
         data_sources: Vec<StoredDynamicDataSource>,
         deterministic_errors: Vec<SubgraphError>,
+        warnings: Vec<String>,
         processed_data_sources: Vec<StoredDynamicDataSource>,
         is_non_fatal_errors_active: bool,
     ) -> Result<(), StoreError> {
@@ -1504,6 +1563,7 @@ impl WritableStoreTrait for WritableStore {
             mods,
             data_sources,
             deterministic_errors,
+            warnings,
             processed_data_sources,
             is_non_fatal_errors_active,
         )?;
@@ -1551,6 +1611,12 @@ impl WritableStoreTrait for WritableStore {
         self.store.health().await
     }
 
+    async fn entity_writes(&self) -> Result<u64, StoreError> {
+        // The count is only updated when a batch is written
+        self.writer.flush().await?;
+        self.store.entity_writes().await
+    }
+
     async fn flush(&self) -> Result<(), StoreError> {
         self.writer.flush().await
     }
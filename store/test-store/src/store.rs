@@ -292,6 +292,7 @@ pub async fn transact_errors(
             Vec::new(),
             errs,
             Vec::new(),
+            Vec::new(),
             is_non_fatal_errors_active,
         )
         .await?;
@@ -371,6 +372,7 @@ pub async fn transact_entities_and_dynamic_data_sources(
             data_sources,
             Vec::new(),
             Vec::new(),
+            Vec::new(),
             false,
         )
         .await
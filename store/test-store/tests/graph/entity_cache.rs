@@ -59,6 +59,11 @@ impl ReadStore for MockStore {
         Ok(self.get_many_res.get(key).cloned())
     }
 
+    fn last_modified_block(&self, key: &EntityKey) -> Result<Option<BlockNumber>, StoreError> {
+        // Pretend that all entities in the mock store were written at block 0
+        Ok(self.get_many_res.get(key).map(|_| 0))
+    }
+
     fn get_many(
         &self,
         _keys: BTreeSet<EntityKey>,
@@ -76,6 +81,10 @@ impl ReadStore for MockStore {
     fn input_schema(&self) -> Arc<InputSchema> {
         SCHEMA.clone()
     }
+
+    fn supports_raw_events(&self) -> bool {
+        true
+    }
 }
 impl DeploymentCursorTracker for MockStore {
     fn block_ptr(&self) -> Option<BlockPtr> {
@@ -129,6 +138,7 @@ impl WritableStore for MockStore {
         _: &StopwatchMetrics,
         _: Vec<StoredDynamicDataSource>,
         _: Vec<SubgraphError>,
+        _: Vec<String>,
         _: Vec<StoredDynamicDataSource>,
         _: bool,
     ) -> Result<(), StoreError> {
@@ -162,6 +172,10 @@ impl WritableStore for MockStore {
         unimplemented!()
     }
 
+    async fn entity_writes(&self) -> Result<u64, StoreError> {
+        unimplemented!()
+    }
+
     async fn flush(&self) -> Result<(), StoreError> {
         unimplemented!()
     }
@@ -225,6 +239,153 @@ fn insert_modifications() {
     );
 }
 
+#[test]
+fn pending_weight_grows_with_changes() {
+    let store = MockStore::new(entity_version_map(
+        "Band",
+        vec![entity! { SCHEMA => id: "mogwai", name: "Mogwai" }],
+    ));
+    let mut cache = EntityCache::new(Arc::new(store));
+    assert_eq!(0, cache.pending_weight());
+
+    // Reading does not count
+    cache
+        .get(&make_band_key("mogwai"), GetScope::Store)
+        .unwrap();
+    assert_eq!(0, cache.pending_weight());
+
+    let mut last_weight = 0;
+    for (id, name) in [("hiatus", "Hiatus Kaiyote"), ("sigurros", "Sigur Ros")] {
+        let data = entity! { SCHEMA => id: id, name: name };
+        cache.set(make_band_key(id), data).unwrap();
+        assert!(cache.pending_weight() > last_weight);
+        last_weight = cache.pending_weight();
+    }
+}
+
+#[test]
+fn encode_pending_changes_is_order_independent() {
+    let encode = |bands: &[(&'static str, &str)]| {
+        let mut cache = EntityCache::new(Arc::new(MockStore::new(BTreeMap::new())));
+        for (id, name) in bands {
+            let data = entity! { SCHEMA => id: *id, name: *name };
+            cache.set(make_band_key(*id), data).unwrap();
+        }
+        cache.encode_pending_changes().unwrap()
+    };
+
+    let changes = encode(&[("hiatus", "Hiatus Kaiyote"), ("sigurros", "Sigur Ros")]);
+    assert_eq!(
+        changes,
+        encode(&[("sigurros", "Sigur Ros"), ("hiatus", "Hiatus Kaiyote")])
+    );
+    assert_ne!(
+        changes,
+        encode(&[("hiatus", "Hiatus Kaiyote"), ("sigurros", "Sigur Rós")])
+    );
+}
+
+#[test]
+fn changed_keys_are_sorted() {
+    let store = MockStore::new(entity_version_map(
+        "Band",
+        vec![entity! { SCHEMA => id: "mogwai", name: "Mogwai" }],
+    ));
+    let mut cache = EntityCache::new(Arc::new(store));
+
+    // Reading does not change anything
+    cache
+        .get(&make_band_key("mogwai"), GetScope::Store)
+        .unwrap();
+    assert!(cache.changed_keys().is_empty());
+
+    for (id, name) in [
+        ("sigurros", "Sigur Ros"),
+        ("hiatus", "Hiatus Kaiyote"),
+        ("mogwai", "Mogwai"),
+    ] {
+        let data = entity! { SCHEMA => id: id, name: name };
+        cache.set(make_band_key(id), data).unwrap();
+    }
+    cache.remove(make_band_key("blur"));
+
+    assert_eq!(
+        vec!["Band:blur", "Band:hiatus", "Band:mogwai", "Band:sigurros"],
+        cache.changed_keys()
+    );
+}
+
+#[test]
+fn is_new_in_block() {
+    let store = {
+        let entities = vec![
+            entity! { SCHEMA => id: "mogwai", name: "Mogwai" },
+            entity! { SCHEMA => id: "sigurros", name: "Sigur Ros" },
+        ];
+        MockStore::new(entity_version_map("Band", entities))
+    };
+    let mut cache = EntityCache::new(Arc::new(store));
+
+    let mogwai_key = make_band_key("mogwai");
+    let sigurros_key = make_band_key("sigurros");
+    let hiatus_key = make_band_key("hiatus");
+
+    // Created in this block
+    let hiatus_data = entity! { SCHEMA => id: "hiatus", name: "Hiatus Kaiyote" };
+    cache.set(hiatus_key.clone(), hiatus_data).unwrap();
+    assert!(cache.is_new_in_block(&hiatus_key).unwrap());
+
+    // Only loaded from the store
+    assert!(cache.get(&mogwai_key, GetScope::Store).unwrap().is_some());
+    assert!(!cache.is_new_in_block(&mogwai_key).unwrap());
+
+    // Loaded from the store and updated
+    let sigurros_data = entity! { SCHEMA => id: "sigurros", name: "Sigur Ros", founded: 1994 };
+    cache.set(sigurros_key.clone(), sigurros_data).unwrap();
+    assert!(!cache.is_new_in_block(&sigurros_key).unwrap());
+
+    // Created and then removed again
+    cache.remove(hiatus_key.clone());
+    assert!(!cache.is_new_in_block(&hiatus_key).unwrap());
+}
+
+#[test]
+fn last_modified_block() {
+    let store = {
+        let entities = vec![
+            entity! { SCHEMA => id: "mogwai", name: "Mogwai" },
+            entity! { SCHEMA => id: "sigurros", name: "Sigur Ros" },
+        ];
+        MockStore::new(entity_version_map("Band", entities))
+    };
+    let mut cache = EntityCache::new(Arc::new(store));
+
+    let mogwai_key = make_band_key("mogwai");
+    let sigurros_key = make_band_key("sigurros");
+    let hiatus_key = make_band_key("hiatus");
+    let unknown_key = make_band_key("unknown");
+
+    // A freshly set entity reports the current block, regardless of
+    // whether it already existed in the store
+    let hiatus_data = entity! { SCHEMA => id: "hiatus", name: "Hiatus Kaiyote" };
+    cache.set(hiatus_key.clone(), hiatus_data).unwrap();
+    let mogwai_data = entity! { SCHEMA => id: "mogwai", name: "Mogwai", founded: 1995 };
+    cache.set(mogwai_key.clone(), mogwai_data).unwrap();
+    cache.remove(sigurros_key.clone());
+
+    assert_eq!(Some(7), cache.last_modified_block(&hiatus_key, 7).unwrap());
+    assert_eq!(Some(7), cache.last_modified_block(&mogwai_key, 7).unwrap());
+    assert_eq!(None, cache.last_modified_block(&sigurros_key, 7).unwrap());
+    assert_eq!(None, cache.last_modified_block(&unknown_key, 7).unwrap());
+
+    // Entities that were not touched in this block come from the store
+    let cache = EntityCache::new(Arc::new(MockStore::new(entity_version_map(
+        "Band",
+        vec![entity! { SCHEMA => id: "mogwai", name: "Mogwai" }],
+    ))));
+    assert_eq!(Some(0), cache.last_modified_block(&mogwai_key, 7).unwrap());
+}
+
 fn entity_version_map(entity_type: &str, entities: Vec<Entity>) -> BTreeMap<EntityKey, Entity> {
     let mut map = BTreeMap::new();
     for entity in entities {
@@ -860,6 +860,40 @@ async fn layout_cache() {
     .unwrap();
 }
 
+#[tokio::test]
+async fn create_raw_event_table_for_old_deployments() {
+    // We need to use `block_on` to call the `create_test_subgraph` function which must be called
+    // from a sync context, so we replicate what we do `spawn_module`.
+    let runtime = tokio::runtime::Handle::current();
+    std::thread::spawn(move || {
+        run_test_with_conn(|conn| {
+            let _runtime_guard = runtime.enter();
+
+            let id = DeploymentHash::new("rawEventUpgrade").unwrap();
+            let _loc = graph::block_on(create_test_subgraph(&id, THINGS_GQL));
+            let site = Arc::new(primary_mirror().find_active_site(&id).unwrap().unwrap());
+            let layout = |conn: &PgConnection| {
+                LayoutCache::new(Duration::from_millis(10))
+                    .get(&LOGGER, conn, site.clone())
+                    .expect("we can get the layout")
+            };
+            assert!(layout(conn).supports_raw_events());
+
+            // Deployments created before raw events were introduced don't
+            // have a table for them
+            let query = format!("drop table {}.\"raw_event$\"", site.namespace);
+            conn.batch_execute(&query).unwrap();
+            let old_layout = layout(conn);
+            assert!(!old_layout.supports_raw_events());
+
+            old_layout.create_raw_event_table(conn).unwrap();
+            assert!(layout(conn).supports_raw_events());
+        })
+    })
+    .join()
+    .unwrap();
+}
+
 #[test]
 fn conflicting_entity() {
     // `id` is the id of an entity to create, `cat`, `dog`, and `ferret` are
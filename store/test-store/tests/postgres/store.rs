@@ -1114,6 +1114,7 @@ fn mock_data_source() -> graph_chain_ethereum::DataSource {
         manifest_idx: 0,
         network: Some(String::from("mainnet")),
         address: Some(Address::from_str("0123123123012312312301231231230123123123").unwrap()),
+        creator_address: None,
         start_block: 0,
         mapping: Mapping {
             kind: String::from("ethereum/events"),
@@ -1241,6 +1242,49 @@ fn revert_block_with_dynamic_data_source_operations() {
     })
 }
 
+#[test]
+fn dynamic_data_source_creator_is_stored_separately() {
+    run_test(|store, writable, deployment| async move {
+        let subgraph_store = store.subgraph_store();
+        let manifest_idx_and_name = vec![(0, "example data source".to_string())];
+        let creator = Address::from_str("0000000000000000000000000000000000000abc").unwrap();
+
+        // A data source without a creator, as all data sources stored before the creator was
+        // tracked are, and one that was created by another data source
+        let mut plain = mock_data_source();
+        plain.creation_block = Some(TEST_BLOCK_3_PTR.number);
+        let mut created = mock_data_source();
+        created.creation_block = Some(TEST_BLOCK_3_PTR.number);
+        created.creator_address = Some(creator);
+
+        transact_entities_and_dynamic_data_sources(
+            &subgraph_store,
+            deployment.clone(),
+            TEST_BLOCK_3_PTR.clone(),
+            vec![
+                plain.as_stored_dynamic_data_source(),
+                created.as_stored_dynamic_data_source(),
+            ],
+            vec![],
+            manifest_idx_and_name.clone(),
+        )
+        .await
+        .unwrap();
+
+        let loaded_dds = writable
+            .load_dynamic_data_sources(manifest_idx_and_name)
+            .await
+            .unwrap();
+        assert_eq!(2, loaded_dds.len());
+        for ds in &loaded_dds {
+            // The param still only holds the address of the data source
+            assert_eq!(plain.address.unwrap().0, **ds.param.as_ref().unwrap());
+        }
+        assert_eq!(None, loaded_dds[0].creator_address);
+        assert_eq!(creator.0, **loaded_dds[1].creator_address.as_ref().unwrap());
+    })
+}
+
 #[test]
 fn entity_changes_are_fired_and_forwarded_to_subscriptions() {
     run_test(|store, _, _| async move {
@@ -1536,6 +1580,7 @@ fn handle_large_string_with_index() {
                 Vec::new(),
                 Vec::new(),
                 Vec::new(),
+                Vec::new(),
                 false,
             )
             .await
@@ -1636,6 +1681,7 @@ fn handle_large_bytea_with_index() {
                 Vec::new(),
                 Vec::new(),
                 Vec::new(),
+                Vec::new(),
                 false,
             )
             .await
@@ -1,5 +1,6 @@
 use graph::blockchain::block_stream::FirehoseCursor;
-use graph::data::subgraph::schema::DeploymentCreate;
+use graph::components::subgraph::RawEvent;
+use graph::data::subgraph::schema::{DeploymentCreate, RAW_EVENT_OBJECT};
 use graph::schema::InputSchema;
 use lazy_static::lazy_static;
 use std::marker::PhantomData;
@@ -8,6 +9,7 @@ use test_store::*;
 use graph::components::store::{DeploymentLocator, EntityKey, WritableStore};
 use graph::data::subgraph::*;
 use graph::semver::Version;
+use graph::util::lfu_cache::LfuCache;
 use graph::{entity, prelude::*};
 use graph_store_postgres::layout_for_tests::writable;
 use graph_store_postgres::{Store as DieselStore, SubgraphStore as DieselSubgraphStore};
@@ -216,3 +218,123 @@ fn restart() {
         writable.flush().await.unwrap();
     })
 }
+
+#[test]
+fn append_raw_events() {
+    run_test(|_, writable, deployment| async move {
+        let stopwatch = StopwatchMetrics::new(
+            LOGGER.clone(),
+            deployment.hash.clone(),
+            "transact",
+            METRICS_REGISTRY.clone(),
+        );
+        let append = |block: u8, events: &[(&str, &str)]| {
+            let mut state = BlockState::<graph_chain_ethereum::Chain>::new(
+                writable.cheap_clone(),
+                LfuCache::new(),
+            );
+            state.enter_handler();
+            for (tag, payload) in events {
+                state.push_raw_event(RawEvent {
+                    tag: tag.to_string(),
+                    payload: payload.to_string(),
+                });
+            }
+            state.exit_handler();
+            state.store_raw_events(block as BlockNumber).unwrap();
+            state
+                .entity_cache
+                .as_modifications(block as BlockNumber)
+                .unwrap()
+                .modifications
+        };
+        let read = |id: &str| {
+            let key = EntityKey::data(RAW_EVENT_OBJECT.as_str(), id);
+            let event = writable.get(&key).unwrap().unwrap();
+            (
+                event.get("tag").unwrap().as_str().unwrap().to_owned(),
+                event.get("payload").unwrap().as_str().unwrap().to_owned(),
+                event.get("sequence").unwrap().as_int().unwrap(),
+            )
+        };
+
+        // New deployments have a table for raw events
+        assert!(writable.supports_raw_events());
+
+        let mods = append(
+            1,
+            &[("Swap", r#"{"amount":1}"#), ("Sync", r#"{"reserve":2}"#)],
+        );
+        writable
+            .transact_block_operations(
+                block_pointer(1),
+                FirehoseCursor::None,
+                mods,
+                &stopwatch,
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                false,
+            )
+            .await
+            .unwrap();
+        let mods = append(2, &[("Swap", r#"{"amount":3}"#)]);
+        writable
+            .transact_block_operations(
+                block_pointer(2),
+                FirehoseCursor::None,
+                mods,
+                &stopwatch,
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                false,
+            )
+            .await
+            .unwrap();
+        writable.flush().await.unwrap();
+
+        // Events are numbered in the order in which they were appended in
+        // their block
+        assert_eq!(
+            ("Swap".to_owned(), r#"{"amount":1}"#.to_owned(), 0),
+            read("1-0")
+        );
+        assert_eq!(
+            ("Sync".to_owned(), r#"{"reserve":2}"#.to_owned(), 1),
+            read("1-1")
+        );
+        assert_eq!(
+            ("Swap".to_owned(), r#"{"amount":3}"#.to_owned(), 0),
+            read("2-0")
+        );
+    })
+}
+
+#[test]
+fn entity_writes() {
+    run_test(|store, writable, deployment| async move {
+        let subgraph_store = store.subgraph_store();
+
+        assert_eq!(0, writable.entity_writes().await.unwrap());
+
+        for count in 1..4 {
+            insert_count(&subgraph_store, &deployment, count).await;
+        }
+        // The count comes from the deployment metadata once the queue is
+        // written, and therefore survives restarts
+        assert_eq!(3, writable.entity_writes().await.unwrap());
+
+        // Reverting blocks does not take back the writes they made
+        writable
+            .revert_block_operations(block_pointer(2), FirehoseCursor::None)
+            .await
+            .unwrap();
+        assert_eq!(3, writable.entity_writes().await.unwrap());
+
+        insert_count(&subgraph_store, &deployment, 3).await;
+        assert_eq!(4, writable.entity_writes().await.unwrap());
+    })
+}
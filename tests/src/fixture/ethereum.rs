@@ -140,5 +140,6 @@ pub fn push_test_log(block: &mut BlockWithTriggers<Chain>, payload: impl Into<St
             removed: None,
         }),
         None,
+        None,
     ))
 }
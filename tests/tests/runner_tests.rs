@@ -14,7 +14,8 @@ use graph::ipfs_client::IpfsClient;
 use graph::object;
 use graph::prelude::ethabi::ethereum_types::H256;
 use graph::prelude::{
-    CheapClone, DeploymentHash, SubgraphAssignmentProvider, SubgraphName, SubgraphStore,
+    CheapClone, DeploymentHash, RuntimeHost, SubgraphAssignmentProvider, SubgraphName,
+    SubgraphStore,
 };
 use graph_tests::fixture::ethereum::{chain, empty_block, genesis, push_test_log};
 use graph_tests::fixture::{
@@ -463,6 +464,54 @@ async fn retry_create_ds() {
     assert_eq!(runner.context().instance().hosts().len(), 2);
 }
 
+#[tokio::test]
+async fn template_instance_index() {
+    let RunnerTestRecipe {
+        stores,
+        subgraph_name,
+        hash,
+    } = RunnerTestRecipe::new("data-source-revert").await;
+
+    let blocks = {
+        let block0 = genesis();
+        let block1 = empty_block(block0.ptr(), test_ptr(1));
+        let block2 = empty_block(block1.ptr(), test_ptr(2));
+        vec![block0, block1, block2]
+    };
+    let stop_block = blocks.last().unwrap().block.ptr();
+    let chain = chain(blocks, &stores, None).await;
+    let ctx = fixture::setup(subgraph_name.clone(), &hash, &stores, &chain, None, None).await;
+
+    let runner = ctx
+        .runner(stop_block)
+        .await
+        .run_for_test(true)
+        .await
+        .unwrap();
+
+    // The static data source has index 0, and every block handler
+    // created another instance of the template
+    let hosts = runner.context().instance().hosts();
+    let (instances, statics): (Vec<_>, Vec<_>) = hosts
+        .iter()
+        .partition(|host| host.creation_block_number().is_some());
+    assert_eq!(
+        statics
+            .iter()
+            .map(|host| host.instance_index())
+            .collect::<Vec<_>>(),
+        vec![0]
+    );
+    assert!(instances.len() > 1);
+    assert_eq!(
+        instances
+            .iter()
+            .map(|host| host.instance_index())
+            .collect::<Vec<_>>(),
+        (0..instances.len() as i32).collect::<Vec<_>>()
+    );
+}
+
 #[tokio::test]
 async fn fatal_error() -> anyhow::Result<()> {
     let RunnerTestRecipe {